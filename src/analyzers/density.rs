@@ -0,0 +1,82 @@
+use crate::models::common::KeyType;
+use crate::models::generic::GenericManiaChart;
+
+// hands and note types are separate groups, an empty group keeps everything in it
+def_const_type_enum!(pub DensityFilter => u32 {
+    ALL => 0,
+    LEFT_HAND => 1 << 0,
+    RIGHT_HAND => 1 << 1,
+    NOTES => 1 << 2,
+    LNS => 1 << 3,
+    MINES => 1 << 4,
+});
+
+const HANDS: u32 = DensityFilter::LEFT_HAND | DensityFilter::RIGHT_HAND;
+const NOTE_TYPES: u32 = DensityFilter::NOTES | DensityFilter::LNS | DensityFilter::MINES;
+
+// the middle lane of odd key counts belongs to both hands
+fn in_hands(lane: u8, key_count: u8, filter: u32) -> bool {
+    let hands = match filter & HANDS {
+        0 => HANDS,
+        hands => hands,
+    };
+
+    let (lane, key_count) = (lane as u32, key_count as u32);
+    let left = lane * 2 <= key_count + 1;
+    let right = lane * 2 > key_count;
+
+    (left && hands & DensityFilter::LEFT_HAND != 0) || (right && hands & DensityFilter::RIGHT_HAND != 0)
+}
+
+fn note_type_flag(key_type: KeyType) -> Option<u32> {
+    match key_type {
+        KeyType::Normal => Some(DensityFilter::NOTES),
+        KeyType::SliderStart => Some(DensityFilter::LNS),
+        KeyType::Mine => Some(DensityFilter::MINES),
+        _ => None,
+    }
+}
+
+// notes per second sampled every step_ms from 0 until the last object, sample i is centered on i * step_ms
+// without a note type in the filter taps and hold heads are counted
+pub(crate) fn density(chart: &GenericManiaChart, window_ms: f64, step_ms: f64, filter: u32) -> Vec<f32> {
+    if !(window_ms > 0.0 && step_ms > 0.0) {
+        return Vec::new();
+    }
+
+    let note_types = match filter & NOTE_TYPES {
+        0 => DensityFilter::NOTES | DensityFilter::LNS,
+        note_types => note_types,
+    };
+    let key_count = chart.chartinfo.key_count;
+
+    let mut times: Vec<f64> = chart
+        .hitobjects
+        .iter()
+        .filter(|obj| note_type_flag(obj.key.key_type).is_some_and(|flag| flag & note_types != 0))
+        .filter(|obj| in_hands(obj.lane, key_count, filter))
+        .map(|obj| obj.time)
+        .collect();
+    times.sort_by(|a, b| a.total_cmp(b));
+
+    let end_time = chart.hitobjects.iter().map(|obj| obj.time).fold(0.0, f64::max);
+    let samples = (end_time / step_ms).ceil() as usize + 1;
+    let half_window = window_ms / 2.0;
+
+    let mut series = Vec::with_capacity(samples);
+    let (mut first, mut last) = (0, 0);
+    for sample in 0..samples {
+        let time = sample as f64 * step_ms;
+        while first < times.len() && times[first] < time - half_window {
+            first += 1;
+        }
+        last = last.max(first);
+        while last < times.len() && times[last] < time + half_window {
+            last += 1;
+        }
+
+        series.push(((last - first) as f64 * 1000.0 / window_ms) as f32);
+    }
+
+    series
+}
@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+
+use crate::wasm_bindgen;
+use crate::models::common::{KeyType, TimingChangeType};
+use crate::models::generic::{track_key, GenericManiaChart, HitObject, SampleContext, TimingEffects, TimingPoint};
+
+// notes further apart than this are an add and a remove instead of a move
+pub(crate) const MATCH_TOLERANCE_MS: f64 = 10.0;
+// float noise from going through beats and back
+const SAME_TIME_MS: f64 = 1e-3;
+// relative, osu! stores bpms as beat lengths so they don't come back exactly
+const SAME_VALUE: f32 = 1e-4;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteDiff {
+    pub lane: u8,
+    pub key_type: KeyType,
+    pub time: f64,
+    // index in the hitobjects of the chart the note is in
+    pub index: usize,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovedNote {
+    pub lane: u8,
+    pub old_time: f64,
+    pub new_time: f64,
+    // positive when the note got later
+    pub delta: f64,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LnChange {
+    pub lane: u8,
+    // time of the head in the new chart
+    pub time: f64,
+    // 0 for taps, so a tap turning into a hold shows up here too
+    pub old_length: f64,
+    pub new_length: f64,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingPointDiff {
+    pub kind: DiffKind,
+    pub change_type: TimingChangeType,
+    #[wasm_bindgen(getter_with_clone)]
+    pub group: String,
+    // none on the side the point isn't in
+    pub old_time: Option<f64>,
+    pub new_time: Option<f64>,
+    pub old_value: Option<f32>,
+    pub new_value: Option<f32>,
+    // what differs between the two points, empty for added and removed ones
+    #[wasm_bindgen(getter_with_clone)]
+    pub fields: Vec<FieldChange>,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    #[wasm_bindgen(getter_with_clone)]
+    pub field: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub old_value: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub new_value: String,
+}
+
+// what it takes to go from the old chart to the new one
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartDiff {
+    #[wasm_bindgen(getter_with_clone)]
+    pub added: Vec<NoteDiff>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub removed: Vec<NoteDiff>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub moved: Vec<MovedNote>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub ln_changes: Vec<LnChange>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub timing_points: Vec<TimingPointDiff>,
+    // metadata and chart info fields
+    #[wasm_bindgen(getter_with_clone)]
+    pub metadata: Vec<FieldChange>,
+}
+
+#[wasm_bindgen]
+impl ChartDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.ln_changes.is_empty()
+            && self.timing_points.is_empty()
+            && self.metadata.is_empty()
+    }
+
+    pub fn notes_changed(&self) -> bool {
+        !(self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.ln_changes.is_empty())
+    }
+}
+
+// pairs up two sorted lists of times, unpaired ones come back with none on the other side
+// exact matches go first so a nudged note can't take the partner of the note next to it
+fn align(old: &[f64], new: &[f64], tolerance_ms: f64) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut old_pairs: Vec<Option<usize>> = vec![None; old.len()];
+    let mut new_paired = vec![false; new.len()];
+
+    for tolerance in [SAME_TIME_MS, tolerance_ms] {
+        let old_left: Vec<usize> = (0..old.len()).filter(|i| old_pairs[*i].is_none()).collect();
+        let new_left: Vec<usize> = (0..new.len()).filter(|i| !new_paired[*i]).collect();
+
+        let (mut i, mut j) = (0, 0);
+        while i < old_left.len() && j < new_left.len() {
+            let (o, n) = (old_left[i], new_left[j]);
+            if (old[o] - new[n]).abs() <= tolerance {
+                old_pairs[o] = Some(n);
+                new_paired[n] = true;
+                i += 1;
+                j += 1;
+            } else if old[o] < new[n] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+
+    let mut pairs: Vec<(Option<usize>, Option<usize>)> = old_pairs.into_iter().enumerate().map(|(o, n)| (Some(o), n)).collect();
+    pairs.extend(new_paired.iter().enumerate().filter(|(_, paired)| !**paired).map(|(n, _)| (None, Some(n))));
+    pairs
+}
+
+// taps and holds on a lane get matched with each other, everything else only with its own type
+fn note_class(key_type: KeyType) -> Option<KeyType> {
+    match key_type {
+        KeyType::Normal | KeyType::SliderStart => Some(KeyType::Normal),
+        KeyType::SliderEnd | KeyType::Empty => None,
+        other => Some(other),
+    }
+}
+
+fn ln_length(obj: &HitObject) -> f64 {
+    match obj.key.key_type {
+        KeyType::SliderStart => obj.key.slider_end_time.map_or(0.0, |end| (end - obj.time).max(0.0)),
+        _ => 0.0,
+    }
+}
+
+fn note_groups(chart: &GenericManiaChart) -> BTreeMap<(u8, u8), Vec<usize>> {
+    let mut groups: BTreeMap<(u8, u8), Vec<usize>> = BTreeMap::new();
+    for (index, obj) in chart.hitobjects.iter().enumerate() {
+        if let Some(class) = note_class(obj.key.key_type) {
+            groups.entry((obj.lane, class as u8)).or_default().push(index);
+        }
+    }
+
+    let objects = &chart.hitobjects.objects;
+    for indices in groups.values_mut() {
+        indices.sort_by(|a, b| objects[*a].time.total_cmp(&objects[*b].time));
+    }
+
+    groups
+}
+
+fn diff_notes(old: &GenericManiaChart, new: &GenericManiaChart, tolerance_ms: f64, diff: &mut ChartDiff) {
+    let (old_objects, new_objects) = (&old.hitobjects.objects, &new.hitobjects.objects);
+    let old_groups = note_groups(old);
+    let new_groups = note_groups(new);
+
+    let empty = Vec::new();
+    let mut keys: Vec<&(u8, u8)> = old_groups.keys().chain(new_groups.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let note = |obj: &HitObject, index: usize| NoteDiff { lane: obj.lane, key_type: obj.key.key_type, time: obj.time, index };
+
+    for key in keys {
+        let old_indices = old_groups.get(key).unwrap_or(&empty);
+        let new_indices = new_groups.get(key).unwrap_or(&empty);
+        let old_times: Vec<f64> = old_indices.iter().map(|i| old_objects[*i].time).collect();
+        let new_times: Vec<f64> = new_indices.iter().map(|i| new_objects[*i].time).collect();
+
+        for pair in align(&old_times, &new_times, tolerance_ms) {
+            match pair {
+                (Some(o), Some(n)) => {
+                    let (old_obj, new_obj) = (&old_objects[old_indices[o]], &new_objects[new_indices[n]]);
+                    let delta = new_obj.time - old_obj.time;
+                    if delta.abs() > SAME_TIME_MS {
+                        diff.moved.push(MovedNote { lane: new_obj.lane, old_time: old_obj.time, new_time: new_obj.time, delta });
+                    }
+
+                    let (old_length, new_length) = (ln_length(old_obj), ln_length(new_obj));
+                    if (old_length - new_length).abs() > SAME_TIME_MS {
+                        diff.ln_changes.push(LnChange { lane: new_obj.lane, time: new_obj.time, old_length, new_length });
+                    }
+                }
+                (Some(o), None) => diff.removed.push(note(&old_objects[old_indices[o]], old_indices[o])),
+                (None, Some(n)) => diff.added.push(note(&new_objects[new_indices[n]], new_indices[n])),
+                (None, None) => {}
+            }
+        }
+    }
+
+    diff.added.sort_by(|a, b| a.time.total_cmp(&b.time));
+    diff.removed.sort_by(|a, b| a.time.total_cmp(&b.time));
+    diff.moved.sort_by(|a, b| a.old_time.total_cmp(&b.old_time));
+    diff.ln_changes.sort_by(|a, b| a.time.total_cmp(&b.time));
+}
+
+#[inline]
+fn same_value(a: f32, b: f32) -> bool {
+    (a - b).abs() <= SAME_VALUE * a.abs().max(b.abs()).max(1.0)
+}
+
+// kiai, volume and meter changes count too, not just the value
+fn same_point(a: &TimingPoint, b: &TimingPoint) -> bool {
+    (a.time - b.time).abs() <= SAME_TIME_MS
+        && same_value(a.change.value, b.change.value)
+        && a.sample == b.sample
+        && a.effects == b.effects
+        && a.signature == b.signature
+}
+
+fn sample_str(sample: Option<SampleContext>) -> String {
+    sample.map_or_else(
+        || "none".to_string(),
+        |s| format!("set {}, index {}, volume {}", s.sample_set, s.sample_index, s.volume),
+    )
+}
+
+fn effects_str(effects: Option<TimingEffects>) -> String {
+    let effects = effects.unwrap_or_default();
+    let names: Vec<&str> = [
+        (effects.kiai, "kiai"),
+        (effects.omit_first_barline, "omit first barline"),
+        (effects.hide_barlines, "hide barlines"),
+    ]
+    .into_iter()
+    .filter_map(|(on, name)| on.then_some(name))
+    .collect();
+
+    if names.is_empty() { "none".to_string() } else { names.join(", ") }
+}
+
+// same checks as same_point, one entry per field that doesn't match
+fn point_changes(old: &TimingPoint, new: &TimingPoint) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut compare = |field: &str, changed: bool, old_value: String, new_value: String| {
+        if changed {
+            changes.push(FieldChange { field: field.to_string(), old_value, new_value });
+        }
+    };
+
+    compare("time", (old.time - new.time).abs() > SAME_TIME_MS, old.time.to_string(), new.time.to_string());
+    compare(
+        "value",
+        !same_value(old.change.value, new.change.value),
+        old.change.value.to_string(),
+        new.change.value.to_string(),
+    );
+    compare("sample", old.sample != new.sample, sample_str(old.sample), sample_str(new.sample));
+    compare("effects", old.effects != new.effects, effects_str(old.effects), effects_str(new.effects));
+    compare(
+        "signature",
+        old.signature != new.signature,
+        old.signature.map_or_else(|| "none".to_string(), |s| s.to_string()),
+        new.signature.map_or_else(|| "none".to_string(), |s| s.to_string()),
+    );
+
+    changes
+}
+
+fn timing_groups(chart: &GenericManiaChart) -> BTreeMap<(String, u8), Vec<&TimingPoint>> {
+    let mut groups: BTreeMap<(String, u8), Vec<&TimingPoint>> = BTreeMap::new();
+    for point in chart.timing_points.iter() {
+        groups
+            .entry((track_key(&point.group).to_string(), point.change.change_type as u8))
+            .or_default()
+            .push(point);
+    }
+
+    for points in groups.values_mut() {
+        points.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    groups
+}
+
+fn diff_timing_points(old: &GenericManiaChart, new: &GenericManiaChart, tolerance_ms: f64, diff: &mut ChartDiff) {
+    let old_groups = timing_groups(old);
+    let new_groups = timing_groups(new);
+
+    let empty = Vec::new();
+    let mut keys: Vec<&(String, u8)> = old_groups.keys().chain(new_groups.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let old_points = old_groups.get(key).unwrap_or(&empty);
+        let new_points = new_groups.get(key).unwrap_or(&empty);
+        let old_times: Vec<f64> = old_points.iter().map(|p| p.time).collect();
+        let new_times: Vec<f64> = new_points.iter().map(|p| p.time).collect();
+
+        for pair in align(&old_times, &new_times, tolerance_ms) {
+            let (old_point, new_point) = (pair.0.map(|o| old_points[o]), pair.1.map(|n| new_points[n]));
+            let kind = match (old_point, new_point) {
+                (Some(o), Some(n)) if same_point(o, n) => continue,
+                (Some(_), Some(_)) => DiffKind::Changed,
+                (Some(_), None) => DiffKind::Removed,
+                (None, Some(_)) => DiffKind::Added,
+                (None, None) => continue,
+            };
+
+            let Some(point) = new_point.or(old_point) else {
+                continue;
+            };
+            diff.timing_points.push(TimingPointDiff {
+                kind,
+                change_type: point.change.change_type,
+                group: point.group.clone(),
+                old_time: old_point.map(|p| p.time),
+                new_time: new_point.map(|p| p.time),
+                old_value: old_point.map(|p| p.change.value),
+                new_value: new_point.map(|p| p.change.value),
+                fields: match (old_point, new_point) {
+                    (Some(o), Some(n)) => point_changes(o, n),
+                    _ => Vec::new(),
+                },
+            });
+        }
+    }
+
+    let time = |d: &TimingPointDiff| d.new_time.or(d.old_time).unwrap_or_default();
+    diff.timing_points.sort_by(|a, b| time(a).total_cmp(&time(b)));
+}
+
+fn diff_metadata(old: &GenericManiaChart, new: &GenericManiaChart, diff: &mut ChartDiff) {
+    let mut compare = |field: &str, old_value: String, new_value: String| {
+        if old_value != new_value {
+            diff.metadata.push(FieldChange { field: field.to_string(), old_value, new_value });
+        }
+    };
+
+    let (old_meta, new_meta) = (&old.metadata, &new.metadata);
+    compare("title", old_meta.title.clone(), new_meta.title.clone());
+    compare("alt_title", old_meta.alt_title.clone(), new_meta.alt_title.clone());
+    compare("artist", old_meta.artist.clone(), new_meta.artist.clone());
+    compare("alt_artist", old_meta.alt_artist.clone(), new_meta.alt_artist.clone());
+    compare("creator", old_meta.creator.clone(), new_meta.creator.clone());
+    compare("genre", old_meta.genre.clone(), new_meta.genre.clone());
+    compare("source", old_meta.source.clone(), new_meta.source.clone());
+    compare("tags", old_meta.tags.join(" "), new_meta.tags.join(" "));
+
+    let (old_info, new_info) = (&old.chartinfo, &new.chartinfo);
+    compare("difficulty_name", old_info.difficulty_name.clone(), new_info.difficulty_name.clone());
+    compare("od", old_info.od.to_string(), new_info.od.to_string());
+    compare("hp", old_info.hp.to_string(), new_info.hp.to_string());
+    compare("bg_path", old_info.bg_path.clone(), new_info.bg_path.clone());
+    compare("video_path", old_info.video_path.clone(), new_info.video_path.clone());
+    compare("song_path", old_info.song_path.clone(), new_info.song_path.clone());
+    compare("audio_offset", old_info.audio_offset.to_string(), new_info.audio_offset.to_string());
+    compare("preview_time", old_info.preview_time.to_string(), new_info.preview_time.to_string());
+    compare("key_count", old_info.key_count.to_string(), new_info.key_count.to_string());
+    compare("bpm_affects_sv", old_info.bpm_affects_sv.to_string(), new_info.bpm_affects_sv.to_string());
+}
+
+// notes and timing points are matched by time within tolerance_ms, not by their index
+pub(crate) fn diff(old: &GenericManiaChart, new: &GenericManiaChart, tolerance_ms: f64) -> ChartDiff {
+    let mut diff = ChartDiff::default();
+    diff_notes(old, new, tolerance_ms, &mut diff);
+    diff_timing_points(old, new, tolerance_ms, &mut diff);
+    diff_metadata(old, new, &mut diff);
+    diff
+}
@@ -0,0 +1,361 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::wasm_bindgen;
+use crate::models::common::{Beat, Key, KeyType, TimingChangeType};
+use crate::models::generic::GenericManiaChart;
+use crate::utils::rhythm::{NOTE_TYPES, SNAP_TOLERANCE_MS};
+
+// holds that get cut for a tap inside them end this long before the tap
+const LN_GAP_MS: f64 = 30.0;
+const MIN_LN_MS: f64 = 30.0;
+// fixes can create new problems, like a snapped note landing on another one
+const FIX_PASSES: usize = 3;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    // two notes on the same lane at the same time
+    OverlappingNotes,
+    // a note in the body of a hold on the same lane
+    TapInsideLn,
+    MissingLnEnd,
+    // the hold ends before or when it starts
+    LnEndsBeforeStart,
+    // generic lanes go from 1 to the key count
+    LaneOutOfRange,
+    NoteBeforeFirstBpm,
+    // zero, negative or not a number
+    InvalidBpm,
+    // same time, group and type as another timing point
+    DuplicateTimingPoint,
+    UnsnappedNote,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    pub kind: LintKind,
+    pub time: f64,
+    // 0 for timing point lints
+    pub lane: u8,
+    // index in the hitobjects, or in the timing points for timing point lints
+    pub index: usize,
+    // whether fix can repair it
+    pub fixable: bool,
+    #[wasm_bindgen(getter_with_clone)]
+    pub message: String,
+}
+
+impl Lint {
+    fn note(kind: LintKind, chart: &GenericManiaChart, index: usize, fixable: bool, message: String) -> Self {
+        let obj = &chart.hitobjects.objects[index];
+        Self { kind, time: obj.time, lane: obj.lane, index, fixable, message }
+    }
+
+    fn timing_point(kind: LintKind, chart: &GenericManiaChart, index: usize, message: String) -> Self {
+        let point = &chart.timing_points.points[index];
+        Self { kind, time: point.time, lane: 0, index, fixable: true, message }
+    }
+}
+
+#[inline]
+fn is_playable(key_type: KeyType) -> bool {
+    matches!(key_type, KeyType::Normal | KeyType::SliderStart)
+}
+
+// indices of the objects of every lane in time order
+fn lanes(chart: &GenericManiaChart) -> HashMap<u8, Vec<usize>> {
+    let mut lanes: HashMap<u8, Vec<usize>> = HashMap::new();
+    for (index, obj) in chart.hitobjects.iter().enumerate() {
+        lanes.entry(obj.lane).or_default().push(index);
+    }
+
+    let objects = &chart.hitobjects.objects;
+    for indices in lanes.values_mut() {
+        indices.sort_by(|a, b| objects[*a].time.total_cmp(&objects[*b].time));
+    }
+
+    lanes
+}
+
+fn lint_notes(chart: &GenericManiaChart, lints: &mut Vec<Lint>) {
+    let objects = &chart.hitobjects.objects;
+    let key_count = chart.chartinfo.key_count;
+
+    for (index, obj) in objects.iter().enumerate() {
+        if obj.lane == 0 || obj.lane > key_count {
+            let message = format!("lane {} is outside of the {}k playfield", obj.lane, key_count);
+            lints.push(Lint::note(LintKind::LaneOutOfRange, chart, index, false, message));
+        }
+
+        if obj.key.key_type != KeyType::SliderStart {
+            continue;
+        }
+
+        match obj.key.slider_end_time() {
+            None => {
+                let message = format!("hold at {}ms has no end", obj.time);
+                lints.push(Lint::note(LintKind::MissingLnEnd, chart, index, true, message));
+            }
+            Some(end_time) if end_time <= obj.time => {
+                let message = format!("hold at {}ms ends at {}ms", obj.time, end_time);
+                lints.push(Lint::note(LintKind::LnEndsBeforeStart, chart, index, true, message));
+            }
+            _ => {}
+        }
+    }
+
+    for indices in lanes(chart).values() {
+        // holds are kept over taps when two notes overlap
+        for same_time in indices.chunk_by(|a, b| objects[*a].time == objects[*b].time) {
+            let playable: Vec<usize> = same_time.iter().copied().filter(|i| is_playable(objects[*i].key.key_type)).collect();
+            let kept = playable
+                .iter()
+                .copied()
+                .find(|i| objects[*i].key.key_type == KeyType::SliderStart)
+                .or(playable.first().copied());
+
+            for index in playable.into_iter().filter(|i| Some(*i) != kept) {
+                let message = format!("note at {}ms overlaps another note on lane {}", objects[index].time, objects[index].lane);
+                lints.push(Lint::note(LintKind::OverlappingNotes, chart, index, true, message));
+            }
+        }
+
+        let mut hold: Option<(f64, f64)> = None;
+        for &index in indices {
+            let obj = &objects[index];
+            if !is_playable(obj.key.key_type) {
+                continue;
+            }
+
+            if let Some((start_time, end_time)) = hold.filter(|(start, end)| obj.time > *start && obj.time < *end) {
+                let message = format!("note at {}ms is inside the hold from {start_time}ms to {end_time}ms", obj.time);
+                lints.push(Lint::note(LintKind::TapInsideLn, chart, index, true, message));
+                continue;
+            }
+
+            if let Some(end_time) = obj.key.slider_end_time().filter(|end| *end > obj.time) {
+                hold = Some((obj.time, end_time));
+            }
+        }
+    }
+}
+
+fn lint_timing_points(chart: &GenericManiaChart, lints: &mut Vec<Lint>) {
+    let points = &chart.timing_points.points;
+    let mut seen: HashMap<(u64, &str, TimingChangeType), usize> = HashMap::new();
+
+    for (index, point) in points.iter().enumerate() {
+        if point.change.change_type == TimingChangeType::Bpm && !(point.change.value > 0.0 && point.change.value.is_finite()) {
+            let message = format!("bpm of {} at {}ms", point.change.value, point.time);
+            lints.push(Lint::timing_point(LintKind::InvalidBpm, chart, index, message));
+        }
+
+        // the later point is the one games use, so the earlier one gets reported
+        let key = (point.time.to_bits(), point.group.as_str(), point.change.change_type);
+        if let Some(earlier) = seen.insert(key, index) {
+            let message = format!("{:?} point at {}ms is set again later", point.change.change_type, point.time);
+            lints.push(Lint::timing_point(LintKind::DuplicateTimingPoint, chart, earlier, message));
+        }
+    }
+
+    let first_bpm = first_bpm_index(chart).map(|index| points[index].time);
+    for (index, obj) in chart.hitobjects.iter().enumerate() {
+        if first_bpm.is_some_and(|time| obj.time < time) {
+            let message = format!("note at {}ms is before the first bpm point", obj.time);
+            lints.push(Lint::note(LintKind::NoteBeforeFirstBpm, chart, index, true, message));
+        }
+    }
+}
+
+fn first_bpm_index(chart: &GenericManiaChart) -> Option<usize> {
+    chart
+        .timing_points
+        .points
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.change.change_type == TimingChangeType::Bpm && p.change.value > 0.0 && p.change.value.is_finite())
+        .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time))
+        .map(|(index, _)| index)
+}
+
+fn lint_snaps(chart: &GenericManiaChart, lints: &mut Vec<Lint>) {
+    if first_bpm_index(chart).is_none() {
+        return;
+    }
+
+    let timing_map = chart.timing_map();
+    for (index, obj) in chart.hitobjects.iter().enumerate() {
+        if !matches!(obj.key.key_type, KeyType::Normal | KeyType::SliderStart | KeyType::SliderEnd | KeyType::Mine) {
+            continue;
+        }
+
+        if let Some(snap) = timing_map.snap_from_bpm(obj.time, &NOTE_TYPES) {
+            if snap.error.abs() > SNAP_TOLERANCE_MS {
+                let message = format!("note at {}ms is {:.1}ms off the closest snap", obj.time, snap.error);
+                lints.push(Lint::note(LintKind::UnsnappedNote, chart, index, true, message));
+            }
+        }
+    }
+}
+
+pub(crate) fn validate(chart: &GenericManiaChart) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    lint_notes(chart, &mut lints);
+    lint_timing_points(chart, &mut lints);
+    lint_snaps(chart, &mut lints);
+
+    lints.sort_by(|a, b| a.time.total_cmp(&b.time));
+    lints
+}
+
+// the slider end object a hold head points to
+fn slider_end_of(chart: &GenericManiaChart, index: usize) -> Option<usize> {
+    let head = &chart.hitobjects.objects[index];
+    let end_time = head.key.slider_end_time()?;
+
+    chart
+        .hitobjects
+        .iter()
+        .position(|obj| obj.lane == head.lane && obj.key.key_type == KeyType::SliderEnd && obj.time == end_time)
+}
+
+fn hold_around(chart: &GenericManiaChart, index: usize) -> Option<usize> {
+    let note = &chart.hitobjects.objects[index];
+
+    chart.hitobjects.iter().position(|obj| {
+        obj.lane == note.lane
+            && obj.key.key_type == KeyType::SliderStart
+            && obj.time < note.time
+            && obj.key.slider_end_time().is_some_and(|end| end > note.time)
+    })
+}
+
+fn fix_once(chart: &mut GenericManiaChart, lints: &[Lint]) {
+    let timing_map = chart.timing_map();
+    let mut removed_objects: HashSet<usize> = HashSet::new();
+    let mut removed_points: HashSet<usize> = HashSet::new();
+    let mut moved_before_bpm = false;
+    let mut moved_objects = false;
+
+    for lint in lints.iter().filter(|lint| lint.fixable) {
+        match lint.kind {
+            LintKind::OverlappingNotes => {
+                removed_objects.insert(lint.index);
+                removed_objects.extend(slider_end_of(chart, lint.index));
+            }
+            LintKind::TapInsideLn => {
+                let Some(head) = hold_around(chart, lint.index) else {
+                    continue;
+                };
+                let end = slider_end_of(chart, head);
+                let start_time = chart.hitobjects.objects[head].time;
+                let end_time = lint.time - LN_GAP_MS;
+
+                if end_time - start_time < MIN_LN_MS {
+                    chart.hitobjects.objects[head].key = Key::normal();
+                    removed_objects.extend(end);
+                } else {
+                    chart.hitobjects.objects[head].key.slider_end_time = Some(end_time);
+                    if let Some(end) = end {
+                        chart.hitobjects.objects[end].time = end_time;
+                        chart.hitobjects.objects[end].beat = timing_map.snapped_beat_at(end_time);
+                    }
+                }
+            }
+            LintKind::MissingLnEnd => {
+                let lane = lint.lane;
+                let end = chart
+                    .hitobjects
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, obj)| obj.lane == lane && obj.time > lint.time)
+                    .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time))
+                    .filter(|(_, obj)| obj.key.key_type == KeyType::SliderEnd)
+                    .map(|(index, obj)| (index, obj.time));
+
+                chart.hitobjects.objects[lint.index].key = match end {
+                    Some((_, end_time)) => Key::slider_start(Some(end_time)),
+                    None => Key::normal(),
+                };
+            }
+            LintKind::LnEndsBeforeStart => {
+                removed_objects.extend(slider_end_of(chart, lint.index));
+                chart.hitobjects.objects[lint.index].key = Key::normal();
+            }
+            LintKind::NoteBeforeFirstBpm if !moved_before_bpm => {
+                moved_before_bpm = true;
+                let Some(first) = first_bpm_index(chart) else {
+                    continue;
+                };
+
+                // whole measures before the first bpm point keep the barlines where they were
+                let earliest = chart.hitobjects.iter().map(|obj| obj.time).fold(f64::MAX, f64::min);
+                let point = &chart.timing_points.points[first];
+                let beats_per_measure = point.signature.as_ref().map_or(4, |s| s.beats_per_measure().round().max(1.0) as i64);
+                let measure_length = 60_000.0 / point.change.value as f64 * beats_per_measure as f64;
+                let measures = ((point.time - earliest) / measure_length).ceil() as i64;
+
+                let mut earlier = point.clone();
+                earlier.time = point.time - measures as f64 * measure_length;
+                earlier.beat = point.beat - Beat::whole(measures * beats_per_measure);
+                chart.timing_points.points.push(earlier);
+            }
+            LintKind::InvalidBpm | LintKind::DuplicateTimingPoint => {
+                removed_points.insert(lint.index);
+            }
+            LintKind::UnsnappedNote => {
+                let Some(snap) = timing_map.snap_from_bpm(lint.time, &NOTE_TYPES) else {
+                    continue;
+                };
+
+                let obj = &chart.hitobjects.objects[lint.index];
+                if obj.key.key_type == KeyType::SliderEnd {
+                    let (lane, time) = (obj.lane, obj.time);
+                    for head in chart.hitobjects.objects.iter_mut() {
+                        if head.lane == lane && head.key.key_type == KeyType::SliderStart && head.key.slider_end_time == Some(time) {
+                            head.key.slider_end_time = Some(snap.time);
+                        }
+                    }
+                }
+
+                let obj = &mut chart.hitobjects.objects[lint.index];
+                obj.time = snap.time;
+                obj.beat = timing_map.snapped_beat_at(snap.time);
+                moved_objects = true;
+            }
+            _ => {}
+        }
+    }
+
+    let mut index = 0;
+    chart.hitobjects.objects.retain(|_| {
+        index += 1;
+        !removed_objects.contains(&(index - 1))
+    });
+    if moved_objects {
+        chart.hitobjects.objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    let mut index = 0;
+    chart.timing_points.points.retain(|_| {
+        index += 1;
+        !removed_points.contains(&(index - 1))
+    });
+    if moved_before_bpm {
+        chart.timing_points.points.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+}
+
+// repairs what it can and returns what's left
+pub(crate) fn fix(chart: &mut GenericManiaChart) -> Vec<Lint> {
+    for _ in 0..FIX_PASSES {
+        let lints = validate(chart);
+        if !lints.iter().any(|lint| lint.fixable) {
+            return lints;
+        }
+        fix_once(chart, &lints);
+    }
+
+    validate(chart)
+}
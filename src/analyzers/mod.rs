@@ -0,0 +1,25 @@
+pub mod density;
+pub mod diff;
+pub mod lints;
+pub mod osu_mania;
+pub mod patterns;
+pub mod quaver;
+pub mod snaps;
+
+// osu!'s legacy mod bits, only the rate mods are listed since they're the only ones that change mania star rating
+def_const_type_enum!(pub OsuMods => u32 {
+    NONE => 0,
+    DOUBLE_TIME => 1 << 6,
+    HALF_TIME => 1 << 8,
+    NIGHTCORE => 1 << 9,
+});
+
+pub(crate) fn osu_clock_rate(mods: u32) -> f64 {
+    if mods & (OsuMods::DOUBLE_TIME | OsuMods::NIGHTCORE) != 0 {
+        1.5
+    } else if mods & OsuMods::HALF_TIME != 0 {
+        0.75
+    } else {
+        1.0
+    }
+}
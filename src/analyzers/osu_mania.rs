@@ -0,0 +1,190 @@
+use crate::analyzers::osu_clock_rate;
+use crate::models::common::KeyType;
+use crate::models::generic::GenericManiaChart;
+use crate::utils::legacy_sort::legacy_sort;
+
+const STAR_SCALING_FACTOR: f64 = 0.018;
+const INDIVIDUAL_DECAY_BASE: f64 = 0.125;
+const OVERALL_DECAY_BASE: f64 = 0.30;
+const RELEASE_THRESHOLD: f64 = 30.0;
+const SECTION_LENGTH: f64 = 400.0;
+const DECAY_WEIGHT: f64 = 0.9;
+
+// times are divided by the clock rate
+#[derive(Debug, Clone, Copy)]
+struct ManiaObject {
+    start_time: f64,
+    end_time: f64,
+    column: usize,
+}
+
+#[inline]
+fn apply_decay(value: f64, delta_time: f64, decay_base: f64) -> f64 {
+    value * decay_base.powf(delta_time / 1000.0)
+}
+
+// same as osu!'s Precision.DefinitelyBigger with a leniency of 1ms
+#[inline]
+fn definitely_bigger(value: f64, than: f64) -> bool {
+    value - 1.0 > than
+}
+
+struct Strain {
+    start_times: Vec<f64>,
+    end_times: Vec<f64>,
+    individual_strains: Vec<f64>,
+    individual_strain: f64,
+    overall_strain: f64,
+}
+
+impl Strain {
+    fn new(columns: usize) -> Self {
+        Self {
+            start_times: vec![0.0; columns],
+            end_times: vec![0.0; columns],
+            individual_strains: vec![0.0; columns],
+            individual_strain: 0.0,
+            overall_strain: 1.0,
+        }
+    }
+
+    fn strain_value_of(&mut self, current: &ManiaObject, delta_time: f64) -> f64 {
+        let ManiaObject { start_time, end_time, column } = *current;
+
+        let mut is_overlapping = false;
+        let mut closest_end_time = (end_time - start_time).abs();
+        // everything gets a bonus if something else is held meanwhile
+        let mut hold_factor = 1.0;
+        let mut hold_addition = 0.0;
+
+        for &other_end in &self.end_times {
+            // a previous hold overlaps the body of this one
+            is_overlapping |= definitely_bigger(other_end, start_time) && definitely_bigger(end_time, other_end);
+
+            if definitely_bigger(other_end, end_time) {
+                hold_factor = 1.25;
+            }
+
+            closest_end_time = closest_end_time.min((end_time - other_end).abs());
+        }
+
+        // releasing several holds together is as easy as releasing one, so close releases barely add anything
+        if is_overlapping {
+            hold_addition = 1.0 / (1.0 + (0.5 * (RELEASE_THRESHOLD - closest_end_time)).exp());
+        }
+
+        self.individual_strains[column] = apply_decay(
+            self.individual_strains[column],
+            start_time - self.start_times[column],
+            INDIVIDUAL_DECAY_BASE,
+        );
+        self.individual_strains[column] += 2.0 * hold_factor;
+
+        // a chord takes the hardest column it's made of
+        self.individual_strain = if delta_time <= 1.0 {
+            self.individual_strain.max(self.individual_strains[column])
+        } else {
+            self.individual_strains[column]
+        };
+
+        self.overall_strain = apply_decay(self.overall_strain, delta_time, OVERALL_DECAY_BASE);
+        self.overall_strain += (1.0 + hold_addition) * hold_factor;
+
+        self.start_times[column] = start_time;
+        self.end_times[column] = end_time;
+
+        self.individual_strain + self.overall_strain
+    }
+
+    fn initial_strain(&self, offset: f64, previous_start: f64) -> f64 {
+        apply_decay(self.individual_strain, offset - previous_start, INDIVIDUAL_DECAY_BASE)
+            + apply_decay(self.overall_strain, offset - previous_start, OVERALL_DECAY_BASE)
+    }
+}
+
+// holds count as one object that ends at their release, their tails and mines are skipped
+fn mania_objects(chart: &GenericManiaChart, clock_rate: f64) -> (Vec<ManiaObject>, usize) {
+    let mut objects: Vec<ManiaObject> = chart
+        .hitobjects
+        .iter()
+        .filter(|obj| obj.lane > 0)
+        .filter_map(|obj| {
+            let end_time = match obj.key.key_type {
+                KeyType::Normal => obj.time,
+                KeyType::SliderStart => obj.key.slider_end_time().unwrap_or(obj.time).max(obj.time),
+                _ => return None,
+            };
+
+            Some(ManiaObject {
+                start_time: obj.time,
+                end_time,
+                // generic lanes start at 1
+                column: obj.lane as usize - 1,
+            })
+        })
+        .collect();
+
+    // osu! sorts on whole milliseconds before the rate is applied, with its own unstable sort
+    // that decides the order inside chords, which changes what holds each note sees
+    legacy_sort(&mut objects, |a, b| {
+        (a.start_time.round_ties_even() as i64).cmp(&(b.start_time.round_ties_even() as i64))
+    });
+    for obj in objects.iter_mut() {
+        obj.start_time /= clock_rate;
+        obj.end_time /= clock_rate;
+    }
+
+    let columns = objects
+        .iter()
+        .map(|obj| obj.column + 1)
+        .max()
+        .unwrap_or(0)
+        .max(chart.chartinfo.key_count as usize);
+
+    (objects, columns)
+}
+
+// osu!'s strain based mania difficulty, every 400ms section keeps its hardest strain
+// and the sections are summed hardest first with a weight that decays by 0.9
+pub(crate) fn star_rating(chart: &GenericManiaChart, mods: u32) -> f64 {
+    let clock_rate = osu_clock_rate(mods);
+    let (objects, columns) = mania_objects(chart, clock_rate);
+
+    if objects.len() < 2 {
+        return 0.0;
+    }
+
+    let mut strain = Strain::new(columns);
+    let mut peaks = Vec::new();
+    let mut section_peak = 0.0;
+
+    // the first object has nothing to be compared to and doesn't generate any strain
+    let mut section_end = (objects[1].start_time / SECTION_LENGTH).ceil() * SECTION_LENGTH;
+
+    for pair in objects.windows(2) {
+        let (previous, current) = (&pair[0], &pair[1]);
+
+        while current.start_time > section_end {
+            peaks.push(section_peak);
+            section_peak = strain.initial_strain(section_end, previous.start_time);
+            section_end += SECTION_LENGTH;
+        }
+
+        let delta_time = current.start_time - previous.start_time;
+        section_peak = f64::max(section_peak, strain.strain_value_of(current, delta_time));
+    }
+    peaks.push(section_peak);
+
+    // empty sections can't add anything
+    peaks.retain(|peak| *peak > 0.0);
+    peaks.sort_by(|a, b| b.total_cmp(a));
+
+    let mut difficulty = 0.0;
+    let mut weight = 1.0;
+    for peak in peaks {
+        difficulty += peak * weight;
+        weight *= DECAY_WEIGHT;
+    }
+
+    difficulty * STAR_SCALING_FACTOR
+}
@@ -0,0 +1,355 @@
+use crate::wasm_bindgen;
+use crate::models::common::KeyType;
+use crate::models::generic::GenericManiaChart;
+use crate::models::timeline::HitObjectTimeline;
+
+// rows on each side that vote on the label of a row
+const SMOOTHING_ROWS: usize = 4;
+// shorter runs get merged into the section before them, or the one after when they come first
+const MIN_SECTION_ROWS: u32 = 6;
+// rolls have to sweep across at least this many columns and go through the sweep twice
+const MIN_ROLL_COLUMNS: usize = 3;
+const MIN_ROLL_PASSES: usize = 2;
+const LN_INVERSE_GAP_MS: f64 = 120.0;
+const RELEASE_TOLERANCE_MS: f64 = 10.0;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternType {
+    // single notes with no jacks
+    Stream,
+    // stream with jumps mixed in
+    Jumpstream,
+    // stream with hands or quads mixed in
+    Handstream,
+    // chords that repeat columns of the row before
+    Chordjack,
+    // a column hit three or more times in a row
+    Jack,
+    // a column hit twice in a row inside other patterns
+    Minijack,
+    // single notes going back and forth between two columns
+    Trill,
+    // single notes sweeping across neighbouring columns in one direction and wrapping back, like 12341234
+    Roll,
+    // jumps taking turns with no column in common, like [12][34] or [13][24]
+    Bracket,
+    // the hands take turns and each one trills on its own, like 1324
+    SplitHand,
+    // holds pressed right after the last one on the same column was released
+    LnInverse,
+    // holds released while other notes are being pressed
+    LnRelease,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternSection {
+    pub pattern: PatternType,
+    pub start_time: f64,
+    // time of the last row in the section
+    pub end_time: f64,
+    pub row_count: u32,
+    pub note_count: u32,
+    // notes per second over the section
+    pub intensity: f32,
+    // the bpm the rows would be 1/4 snaps at (16ths, 4 rows per beat), which is how players usually call it
+    pub bpm: f32,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patterns {
+    #[wasm_bindgen(getter_with_clone)]
+    pub sections: Vec<PatternSection>,
+}
+
+#[wasm_bindgen]
+impl Patterns {
+    // fraction of the rows labeled as the pattern
+    pub fn share(&self, pattern: PatternType) -> f64 {
+        let total: u32 = self.sections.iter().map(|s| s.row_count).sum();
+        if total == 0 {
+            return 0.0;
+        }
+
+        let rows: u32 = self.sections.iter().filter(|s| s.pattern == pattern).map(|s| s.row_count).sum();
+        rows as f64 / total as f64
+    }
+
+    pub fn dominant(&self) -> Option<PatternType> {
+        self.sections
+            .iter()
+            .map(|s| s.pattern)
+            .fold(None, |best: Option<(PatternType, f64)>, pattern| {
+                let share = self.share(pattern);
+                match best {
+                    Some((_, best_share)) if best_share >= share => best,
+                    _ => Some((pattern, share)),
+                }
+            })
+            .map(|(pattern, _)| pattern)
+    }
+
+    // weighted by the rows of every section with the pattern
+    pub fn average_bpm(&self, pattern: PatternType) -> Option<f32> {
+        let (rows, total) = self
+            .sections
+            .iter()
+            .filter(|s| s.pattern == pattern)
+            .fold((0.0, 0.0), |(rows, total), s| (rows + s.row_count as f64, total + s.bpm as f64 * s.row_count as f64));
+
+        (rows > 0.0).then(|| (total / rows) as f32)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PressRow {
+    time: f64,
+    // columns pressed as a bit mask
+    mask: u64,
+    inverse: bool,
+    release: bool,
+}
+
+#[inline]
+fn single_column(mask: u64) -> Option<u32> {
+    (mask.count_ones() == 1).then(|| mask.trailing_zeros())
+}
+
+// the middle column of odd key counts isn't on either hand
+fn hand_of(column: u32, key_count: u32) -> Option<bool> {
+    let lane = column + 1;
+    if lane * 2 <= key_count {
+        Some(false)
+    } else if lane * 2 > key_count + 1 {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+fn press_rows(chart: &GenericManiaChart) -> Vec<PressRow> {
+    let key_count = (chart.chartinfo.key_count as usize).min(u64::BITS as usize);
+    let rows = HitObjectTimeline::to_rows(&chart.hitobjects.objects, key_count);
+
+    let mut last_release: Vec<Option<f64>> = vec![None; key_count];
+    let mut presses: Vec<PressRow> = Vec::with_capacity(rows.len());
+    let mut hold_ends: Vec<(usize, f64)> = Vec::new();
+
+    for row in &rows {
+        let mut press = PressRow { time: row.time, mask: 0, inverse: false, release: false };
+
+        for (column, key) in row.keys.iter().enumerate() {
+            match key.key_type {
+                KeyType::Normal => press.mask |= 1 << column,
+                KeyType::SliderStart => {
+                    press.mask |= 1 << column;
+                    let gap = last_release[column].map(|release| row.time - release);
+                    press.inverse |= gap.is_some_and(|gap| (0.0..=LN_INVERSE_GAP_MS).contains(&gap));
+
+                    if let Some(end_time) = key.slider_end_time().filter(|end| *end > row.time) {
+                        last_release[column] = Some(end_time);
+                        hold_ends.push((presses.len(), end_time));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if press.mask != 0 {
+            presses.push(press);
+        }
+    }
+
+    // a release counts when something gets pressed during the hold and nothing is pressed with the release
+    let times: Vec<f64> = presses.iter().map(|p| p.time).collect();
+    for (index, end_time) in hold_ends {
+        let start_time = times[index];
+        let pressed_during = times[times.partition_point(|t| *t <= start_time)..]
+            .first()
+            .is_some_and(|t| *t < end_time - RELEASE_TOLERANCE_MS);
+        let near = times.partition_point(|t| *t < end_time - RELEASE_TOLERANCE_MS);
+        let pressed_with_release = times.get(near).is_some_and(|t| *t <= end_time + RELEASE_TOLERANCE_MS);
+
+        if pressed_during && !pressed_with_release {
+            presses[index].release = true;
+        }
+    }
+
+    presses
+}
+
+// marks single note rows that keep repeating a sweep like 1234 or 321, the repeat starts back at the first column
+fn roll_rows(rows: &[PressRow]) -> Vec<bool> {
+    let columns: Vec<Option<u32>> = rows.iter().map(|row| single_column(row.mask)).collect();
+    let step = |index: usize| match (columns[index - 1], columns[index]) {
+        (Some(a), Some(b)) => Some(b as i64 - a as i64),
+        _ => None,
+    };
+
+    let mut in_roll = vec![false; rows.len()];
+    let mut start = 0;
+    while start + 1 < rows.len() {
+        let Some(direction) = step(start + 1).filter(|step| step.abs() == 1) else {
+            start += 1;
+            continue;
+        };
+
+        // the first pass sets the columns every other pass has to repeat
+        let mut end = start + 1;
+        while end + 1 < rows.len() && step(end + 1) == Some(direction) {
+            end += 1;
+        }
+        let pass = end - start + 1;
+        while end + 1 < rows.len() && columns[end + 1].is_some() && columns[end + 1] == columns[end + 1 - pass] {
+            end += 1;
+        }
+
+        if pass >= MIN_ROLL_COLUMNS && end - start + 1 >= pass * MIN_ROLL_PASSES {
+            in_roll[start..=end].fill(true);
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+
+    in_roll
+}
+
+fn label_rows(rows: &[PressRow], key_count: u32) -> Vec<PatternType> {
+    let mut labels = Vec::with_capacity(rows.len());
+    let mut jack_run = 0;
+    let in_roll = roll_rows(rows);
+
+    for (index, row) in rows.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| rows[i].mask).unwrap_or(0);
+        let notes = row.mask.count_ones();
+        let shared = row.mask & previous != 0;
+        jack_run = if shared { jack_run + 1 } else { 0 };
+
+        // chords nearby decide which kind of stream this is
+        let nearby = &rows[index.saturating_sub(2)..(index + 3).min(rows.len())];
+        let biggest_nearby = nearby.iter().map(|r| r.mask.count_ones()).max().unwrap_or(0);
+        let stream_kind = match biggest_nearby {
+            0 | 1 => PatternType::Stream,
+            2 => PatternType::Jumpstream,
+            _ => PatternType::Handstream,
+        };
+
+        let back = |offset: usize| index.checked_sub(offset).map(|i| rows[i].mask);
+
+        let label = if row.inverse {
+            PatternType::LnInverse
+        } else if row.release {
+            PatternType::LnRelease
+        } else if shared && notes > 1 {
+            PatternType::Chordjack
+        } else if shared {
+            if jack_run >= 2 { PatternType::Jack } else { PatternType::Minijack }
+        } else if notes == 2 {
+            let alternates = back(1).is_some_and(|m| m.count_ones() == 2) && back(2) == Some(row.mask);
+            if alternates { PatternType::Bracket } else { stream_kind }
+        } else if notes > 2 {
+            PatternType::Handstream
+        } else if in_roll[index] {
+            PatternType::Roll
+        } else {
+            single_label(rows, index, key_count).unwrap_or(stream_kind)
+        };
+
+        labels.push(label);
+    }
+
+    labels
+}
+
+fn single_label(rows: &[PressRow], index: usize, key_count: u32) -> Option<PatternType> {
+    let column = |offset: usize| index.checked_sub(offset).and_then(|i| single_column(rows[i].mask));
+    let (current, first, second) = (column(0)?, column(1)?, column(2)?);
+
+    if current == second && current != first {
+        return Some(PatternType::Trill);
+    }
+
+    let hand = |column: u32| hand_of(column, key_count);
+    let hands_alternate = hand(current).is_some()
+        && hand(first).is_some()
+        && hand(current) != hand(first)
+        && hand(first) != hand(second);
+    if hands_alternate && current != second && column(4) == Some(current) {
+        return Some(PatternType::SplitHand);
+    }
+
+    None
+}
+
+// every row takes the most common label around it so single rows don't break sections apart
+fn smooth(labels: &[PatternType]) -> Vec<PatternType> {
+    (0..labels.len())
+        .map(|index| {
+            let window = &labels[index.saturating_sub(SMOOTHING_ROWS)..(index + SMOOTHING_ROWS + 1).min(labels.len())];
+            let mut best = (labels[index], 0);
+            for label in window {
+                let count = window.iter().filter(|other| *other == label).count();
+                // ties keep the row's own label
+                if count > best.1 || (count == best.1 && *label == labels[index]) {
+                    best = (*label, count);
+                }
+            }
+            best.0
+        })
+        .collect()
+}
+
+fn section_of(rows: &[PressRow], pattern: PatternType) -> PatternSection {
+    let start_time = rows[0].time;
+    let end_time = rows[rows.len() - 1].time;
+    let note_count: u32 = rows.iter().map(|r| r.mask.count_ones()).sum();
+
+    let mut gaps: Vec<f64> = rows.windows(2).map(|pair| pair[1].time - pair[0].time).filter(|gap| *gap > 0.0).collect();
+    gaps.sort_by(|a, b| a.total_cmp(b));
+
+    let duration = end_time - start_time;
+    let intensity = if duration > 0.0 { (note_count - rows[rows.len() - 1].mask.count_ones()) as f64 * 1000.0 / duration } else { 0.0 };
+    let bpm = gaps.get(gaps.len() / 2).map_or(0.0, |gap| 15_000.0 / gap);
+
+    PatternSection {
+        pattern,
+        start_time,
+        end_time,
+        row_count: rows.len() as u32,
+        note_count,
+        intensity: intensity as f32,
+        bpm: bpm as f32,
+    }
+}
+
+pub(crate) fn patterns(chart: &GenericManiaChart) -> Patterns {
+    let rows = press_rows(chart);
+    if rows.is_empty() {
+        return Patterns::default();
+    }
+
+    let labels = smooth(&label_rows(&rows, chart.chartinfo.key_count as u32));
+
+    // runs of the same label, short ones join whatever came before them
+    let mut runs: Vec<(PatternType, usize, usize)> = Vec::new();
+    let mut start = 0;
+    for (index, label) in labels.iter().enumerate() {
+        if index + 1 == labels.len() || labels[index + 1] != *label {
+            let short = ((index + 1 - start) as u32) < MIN_SECTION_ROWS;
+            match runs.last_mut() {
+                Some(last) if short || last.0 == *label => last.2 = index + 1,
+                // nothing came before a short first run, so its rows go to the next run
+                None if short && index + 1 < labels.len() => continue,
+                _ => runs.push((*label, start, index + 1)),
+            }
+            start = index + 1;
+        }
+    }
+
+    Patterns {
+        sections: runs.into_iter().map(|(pattern, start, end)| section_of(&rows[start..end], pattern)).collect(),
+    }
+}
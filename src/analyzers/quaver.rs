@@ -0,0 +1,412 @@
+use crate::errors;
+use crate::models::common::KeyType;
+use crate::models::generic::GenericManiaChart;
+
+const CHORD_CLUMP_TOLERANCE_MS: f64 = 8.0;
+const LN_END_THRESHOLD_MS: f64 = 42.0;
+const BIN_SIZE_MS: f64 = 1000.0;
+const LOWEST_DIFFICULTY: f64 = 1.0;
+
+// (lower boundary ms, upper boundary ms, max strain, curve exponential)
+const SIMPLE_JACK: (f64, f64, f64, f64) = (40.0, 330.0, 68.0, 1.17);
+const TECHNICAL_JACK: (f64, f64, f64, f64) = (40.0, 330.0, 70.0, 1.14);
+const ROLL: (f64, f64, f64, f64) = (30.0, 230.0, 55.0, 1.13);
+const BRACKET: (f64, f64, f64, f64) = (30.0, 230.0, 56.0, 1.13);
+
+const LN_BASE_MULTIPLIER: f64 = 0.6;
+const LN_LAYER_TOLERANCE_MS: f64 = 60.0;
+const LN_LAYER_THRESHOLD_MS: f64 = 93.7;
+const LN_RELEASE_AFTER_MULTIPLIER: f64 = 1.0;
+const LN_RELEASE_BEFORE_MULTIPLIER: f64 = 1.3;
+const LN_TAP_MULTIPLIER: f64 = 1.05;
+
+const VIBRO_ACTION_DURATION_MS: f64 = 88.2;
+const VIBRO_ACTION_TOLERANCE_MS: f64 = 88.2;
+const VIBRO_MULTIPLIER: f64 = 0.75;
+const VIBRO_LENGTH_MULTIPLIER: f64 = 0.3;
+const VIBRO_MAX_LENGTH: f64 = 6.0;
+
+const ROLL_RATIO_TOLERANCE_MS: f64 = 2.0;
+const ROLL_RATIO_MULTIPLIER: f64 = 0.25;
+const ROLL_LENGTH_MULTIPLIER: f64 = 0.6;
+const ROLL_MAX_LENGTH: f64 = 14.0;
+
+// fingers are bit flags so chords can be compared in one go
+const INDEX: u8 = 1 << 0;
+const MIDDLE: u8 = 1 << 1;
+const RING: u8 = 1 << 2;
+const THUMB: u8 = 1 << 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hand {
+    Left,
+    Right,
+    Ambiguous,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FingerAction {
+    None,
+    SimpleJack,
+    TechnicalJack,
+    Roll,
+    Bracket,
+}
+
+fn hand_and_finger(key_count: u8, lane: u8) -> Option<(Hand, u8)> {
+    match (key_count, lane) {
+        (4, 1) => Some((Hand::Left, MIDDLE)),
+        (4, 2) => Some((Hand::Left, INDEX)),
+        (4, 3) => Some((Hand::Right, INDEX)),
+        (4, 4) => Some((Hand::Right, MIDDLE)),
+        (7, 1) => Some((Hand::Left, RING)),
+        (7, 2) => Some((Hand::Left, MIDDLE)),
+        (7, 3) => Some((Hand::Left, INDEX)),
+        (7, 4) => Some((Hand::Ambiguous, THUMB)),
+        (7, 5) => Some((Hand::Right, INDEX)),
+        (7, 6) => Some((Hand::Right, MIDDLE)),
+        (7, 7) => Some((Hand::Right, RING)),
+        _ => None,
+    }
+}
+
+// notes on the same hand pressed together, times are divided by the rate
+#[derive(Debug, Clone)]
+struct StrainSolverData {
+    start_time: f64,
+    end_time: f64,
+    hand: Hand,
+    fingers: u8,
+    // start and end of every note, the end is the start for taps
+    notes: Vec<(f64, f64)>,
+    ln_multipliers: Vec<f64>,
+    next_on_hand: Option<usize>,
+    action: FingerAction,
+    action_duration: f64,
+    action_coefficient: f64,
+    roll_multiplier: f64,
+    jack_multiplier: f64,
+    total_strain: f64,
+}
+
+impl StrainSolverData {
+    fn new(start_time: f64, end_time: f64, hand: Hand, finger: u8) -> Self {
+        Self {
+            start_time,
+            end_time,
+            hand,
+            fingers: finger,
+            notes: vec![(start_time, end_time)],
+            ln_multipliers: vec![1.0],
+            next_on_hand: None,
+            action: FingerAction::None,
+            action_duration: 0.0,
+            action_coefficient: 1.0,
+            roll_multiplier: 1.0,
+            jack_multiplier: 1.0,
+            total_strain: 0.0,
+        }
+    }
+
+    #[inline]
+    fn is_chord(&self) -> bool {
+        self.notes.len() > 1
+    }
+
+    fn calculate_strain_value(&mut self) {
+        let base = self.action_coefficient * self.roll_multiplier * self.jack_multiplier;
+        self.total_strain = self.ln_multipliers.iter().map(|ln| base * ln).sum::<f64>() / self.notes.len() as f64;
+    }
+}
+
+// faster actions are harder, anything slower than the upper boundary is as easy as it gets
+fn coefficient_value(duration: f64, (lower, upper, strain_max, exponential): (f64, f64, f64, f64)) -> f64 {
+    let ratio = 1.0 - ((duration - lower) / (upper - lower)).clamp(0.0, 1.0);
+    LOWEST_DIFFICULTY + (strain_max - LOWEST_DIFFICULTY) * ratio.powf(exponential)
+}
+
+fn base_strain_states(chart: &GenericManiaChart, rate: f64, assume_hand: Hand) -> Vec<StrainSolverData> {
+    let key_count = chart.chartinfo.key_count;
+
+    let mut data: Vec<StrainSolverData> = chart
+        .hitobjects
+        .iter()
+        .filter_map(|obj| {
+            let end_time = match obj.key.key_type {
+                KeyType::Normal => obj.time,
+                KeyType::SliderStart => obj.key.slider_end_time().unwrap_or(obj.time).max(obj.time),
+                _ => return None,
+            };
+
+            let (hand, finger) = hand_and_finger(key_count, obj.lane)?;
+            let hand = if hand == Hand::Ambiguous { assume_hand } else { hand };
+            Some(StrainSolverData::new(obj.time / rate, end_time / rate, hand, finger))
+        })
+        .collect();
+
+    data.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    data
+}
+
+fn compute_for_chords(data: Vec<StrainSolverData>) -> Vec<StrainSolverData> {
+    let mut chords: Vec<StrainSolverData> = Vec::with_capacity(data.len());
+
+    for current in data {
+        let chord = chords
+            .iter_mut()
+            .rev()
+            .take_while(|other| current.start_time - other.start_time <= CHORD_CLUMP_TOLERANCE_MS)
+            .find(|other| other.hand == current.hand);
+
+        match chord {
+            Some(chord) => {
+                chord.fingers |= current.fingers;
+                chord.end_time = chord.end_time.max(current.end_time);
+                chord.notes.extend(current.notes);
+                chord.ln_multipliers.extend(current.ln_multipliers);
+            }
+            None => chords.push(current),
+        }
+    }
+
+    chords
+}
+
+fn compute_for_finger_actions(data: &mut [StrainSolverData]) {
+    let mut next_on_hand: [Option<usize>; 2] = [None, None];
+
+    for index in (0..data.len()).rev() {
+        let hand = (data[index].hand == Hand::Right) as usize;
+        let next = next_on_hand[hand].replace(index);
+
+        let Some(next) = next else {
+            continue;
+        };
+
+        let (current_fingers, next_fingers) = (data[index].fingers, data[next].fingers);
+        let chord_found = data[index].is_chord() || data[next].is_chord();
+        let same_state = current_fingers == next_fingers;
+        let jack_found = current_fingers & next_fingers != 0;
+        let duration = data[next].start_time - data[index].start_time;
+
+        let (action, constants) = if !chord_found && !same_state {
+            (FingerAction::Roll, ROLL)
+        } else if same_state {
+            (FingerAction::SimpleJack, SIMPLE_JACK)
+        } else if jack_found {
+            (FingerAction::TechnicalJack, TECHNICAL_JACK)
+        } else {
+            (FingerAction::Bracket, BRACKET)
+        };
+
+        let current = &mut data[index];
+        current.next_on_hand = Some(next);
+        current.action = action;
+        current.action_duration = duration;
+        current.action_coefficient = coefficient_value(duration, constants);
+    }
+}
+
+// uneven rolls can be played like chords, the longer they go the more they get nerfed
+fn compute_for_roll_manipulation(data: &mut [StrainSolverData]) {
+    let mut manipulation_index = 0.0;
+
+    for index in 0..data.len() {
+        let mut manipulation_found = false;
+
+        if let Some(middle) = data[index].next_on_hand {
+            let current = &data[index];
+            let middle = &data[middle];
+
+            // only a roll that comes back to the same fingers two notes later counts, like Quaver's FingerState check
+            let returns = middle.next_on_hand.is_some_and(|last| data[last].fingers == current.fingers);
+            if returns && current.action == FingerAction::Roll && middle.action == FingerAction::Roll {
+                let duration_ratio = (current.action_duration / middle.action_duration)
+                    .max(middle.action_duration / current.action_duration);
+
+                if duration_ratio >= ROLL_RATIO_TOLERANCE_MS {
+                    let duration_multiplier = 1.0 / (1.0 + (duration_ratio - 1.0) * ROLL_RATIO_MULTIPLIER);
+                    let length_ratio = 1.0 - manipulation_index / ROLL_MAX_LENGTH * (1.0 - ROLL_LENGTH_MULTIPLIER);
+
+                    data[index].roll_multiplier = duration_multiplier * length_ratio;
+                    manipulation_found = true;
+                    if manipulation_index < ROLL_MAX_LENGTH {
+                        manipulation_index += 1.0;
+                    }
+                }
+            }
+        }
+
+        if !manipulation_found && manipulation_index > 0.0 {
+            manipulation_index -= 1.0;
+        }
+    }
+}
+
+// fast jacks get vibroed instead of hit one by one
+fn compute_for_jack_manipulation(data: &mut [StrainSolverData]) {
+    let mut manipulation_index = 0.0;
+
+    for index in 0..data.len() {
+        let mut manipulation_found = false;
+
+        if let Some(next) = data[index].next_on_hand {
+            let current = &data[index];
+            if current.action == FingerAction::SimpleJack && data[next].action == FingerAction::SimpleJack {
+                let duration_value = ((VIBRO_ACTION_DURATION_MS + VIBRO_ACTION_TOLERANCE_MS - current.action_duration)
+                    / VIBRO_ACTION_TOLERANCE_MS)
+                    .clamp(0.0, 1.0);
+                let duration_multiplier = 1.0 - duration_value * (1.0 - VIBRO_MULTIPLIER);
+                let length_ratio = 1.0 - manipulation_index / VIBRO_MAX_LENGTH * (1.0 - VIBRO_LENGTH_MULTIPLIER);
+
+                data[index].jack_multiplier = duration_multiplier * length_ratio;
+                manipulation_found = true;
+                if manipulation_index < VIBRO_MAX_LENGTH {
+                    manipulation_index += 1.0;
+                }
+            }
+        }
+
+        if !manipulation_found {
+            manipulation_index = 0.0;
+        }
+    }
+}
+
+// holding a note while the same hand does something else is harder than tapping
+fn compute_for_ln_multiplier(data: &mut [StrainSolverData]) {
+    for index in 0..data.len() {
+        let next = data[index].next_on_hand.map(|next| (data[next].start_time, data[next].end_time));
+        let current = &mut data[index];
+
+        for (note, multiplier) in current.notes.iter().zip(current.ln_multipliers.iter_mut()) {
+            let (start_time, end_time) = *note;
+            if end_time <= start_time {
+                continue;
+            }
+
+            let duration_value = 1.0
+                - ((LN_LAYER_THRESHOLD_MS + LN_LAYER_TOLERANCE_MS - (end_time - start_time)) / LN_LAYER_TOLERANCE_MS)
+                    .clamp(0.0, 1.0);
+            *multiplier = 1.0 + duration_value * LN_BASE_MULTIPLIER;
+
+            let Some((next_start, next_end)) = next else {
+                continue;
+            };
+
+            if next_start < end_time - LN_END_THRESHOLD_MS && next_start >= start_time + LN_END_THRESHOLD_MS {
+                if next_end > end_time + LN_END_THRESHOLD_MS {
+                    *multiplier *= LN_RELEASE_AFTER_MULTIPLIER;
+                } else if next_end > next_start && next_end < end_time - LN_END_THRESHOLD_MS {
+                    *multiplier *= LN_RELEASE_BEFORE_MULTIPLIER;
+                } else {
+                    *multiplier *= LN_TAP_MULTIPLIER;
+                }
+            }
+        }
+    }
+}
+
+fn calculate_overall_difficulty(data: &mut [StrainSolverData]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    for point in data.iter_mut() {
+        point.calculate_strain_value();
+    }
+
+    let mut calculated_difficulty = data.iter().map(|point| point.total_strain).sum::<f64>() / data.len() as f64;
+
+    let map_start = data.iter().map(|point| point.start_time).fold(f64::MAX, f64::min);
+    let map_end = data.iter().map(|point| point.start_time.max(point.end_time)).fold(f64::MIN, f64::max);
+
+    // data is sorted by start time so every bin is a contiguous slice
+    let mut bins: Vec<f64> = Vec::new();
+    let mut bin_start = map_start;
+    let mut first = 0;
+    while bin_start < map_end {
+        let count = data[first..].iter().take_while(|point| point.start_time < bin_start + BIN_SIZE_MS).count();
+        let values = &data[first..first + count];
+        bins.push(match values.len() {
+            0 => 0.0,
+            len => values.iter().map(|point| point.total_strain).sum::<f64>() / len as f64,
+        });
+
+        first += count;
+        bin_start += BIN_SIZE_MS;
+    }
+
+    if !bins.iter().any(|bin| *bin > 0.0) {
+        return 0.0;
+    }
+
+    // how close the rest of the map is to its hardest 40%
+    let mut sorted = bins.clone();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let cutoff = (bins.len() as f64 * 0.4).floor() as usize;
+    let easy_rating_cutoff = match cutoff {
+        0 => sorted[0],
+        cutoff => sorted[..cutoff].iter().sum::<f64>() / cutoff as f64,
+    };
+
+    let played: Vec<f64> = bins.iter().copied().filter(|bin| *bin > 0.0).collect();
+    let continuity = played.iter().map(|bin| (bin / easy_rating_cutoff).sqrt()).sum::<f64>() / played.len() as f64;
+
+    const MAX_CONTINUITY: f64 = 1.00;
+    const AVG_CONTINUITY: f64 = 0.85;
+    const MIN_CONTINUITY: f64 = 0.60;
+    const MAX_ADJUSTMENT: f64 = 1.05;
+    const AVG_ADJUSTMENT: f64 = 1.00;
+    const MIN_ADJUSTMENT: f64 = 0.90;
+
+    let continuity_adjustment = if continuity > AVG_CONTINUITY {
+        let ratio = (continuity - AVG_CONTINUITY) / (MAX_CONTINUITY - AVG_CONTINUITY);
+        (AVG_ADJUSTMENT + ratio * (MAX_ADJUSTMENT - AVG_ADJUSTMENT)).min(MAX_ADJUSTMENT)
+    } else {
+        let ratio = (continuity - AVG_CONTINUITY) / (AVG_CONTINUITY - MIN_CONTINUITY);
+        (AVG_ADJUSTMENT + ratio * (AVG_ADJUSTMENT - MIN_ADJUSTMENT)).max(MIN_ADJUSTMENT)
+    };
+    calculated_difficulty *= continuity_adjustment;
+
+    // short maps don't get the full rating until a minute of actual playing
+    let true_drain_time = bins.len() as f64 * continuity * BIN_SIZE_MS;
+    let short_map_adjustment = (0.25 * (true_drain_time / 60_000.0).sqrt() + 0.75).clamp(0.75, 1.0);
+
+    calculated_difficulty * short_map_adjustment
+}
+
+fn overall_difficulty(chart: &GenericManiaChart, rate: f64, assume_hand: Hand) -> f64 {
+    let data = base_strain_states(chart, rate, assume_hand);
+    let mut data = compute_for_chords(data);
+
+    compute_for_finger_actions(&mut data);
+    compute_for_roll_manipulation(&mut data);
+    compute_for_jack_manipulation(&mut data);
+    compute_for_ln_multiplier(&mut data);
+
+    calculate_overall_difficulty(&mut data)
+}
+
+// Quaver's strain solver, 7k doesn't know which hand plays the middle lane so it takes the average of both
+pub(crate) fn difficulty(chart: &GenericManiaChart, rate: f32) -> Result<f32, Box<dyn std::error::Error>> {
+    if !matches!(chart.chartinfo.key_count, 4 | 7) {
+        return Err(Box::new(errors::AnalysisError::InvalidKeyCount(
+            chart.chartinfo.key_count,
+            "4k and 7k".to_string(),
+            "Quaver's difficulty".to_string(),
+        )));
+    }
+
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err(Box::new(errors::AnalysisError::InvalidRate(rate)));
+    }
+
+    let rate = rate as f64;
+    let difficulty = match chart.chartinfo.key_count {
+        7 => (overall_difficulty(chart, rate, Hand::Left) + overall_difficulty(chart, rate, Hand::Right)) / 2.0,
+        _ => overall_difficulty(chart, rate, Hand::Right),
+    };
+
+    Ok(difficulty as f32)
+}
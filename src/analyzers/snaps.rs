@@ -0,0 +1,91 @@
+use crate::wasm_bindgen;
+use crate::models::common::KeyType;
+use crate::models::generic::GenericManiaChart;
+use crate::utils::rhythm::{NOTE_TYPES, SNAP_TOLERANCE_MS};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteSnap {
+    // index in the hitobjects
+    pub index: usize,
+    pub time: f64,
+    // 4 for a 1/4 note counted from the bpm point the note is under, taken from the closest snap so it's 48 at most
+    pub divisor: u32,
+    // milliseconds from the snap, positive when late
+    pub error: f64,
+    // whether the note is within the snap tolerance of its snap
+    pub snapped: bool,
+}
+
+// osu! and Quaver store whole milliseconds, so a note less than one off is already on its snap
+const ROUNDING_MS: f64 = 1.0;
+
+#[inline]
+fn is_snappable(key_type: KeyType) -> bool {
+    matches!(key_type, KeyType::Normal | KeyType::SliderStart | KeyType::SliderEnd | KeyType::Mine)
+}
+
+// one entry per note, hold end and mine, charts without a bpm have none
+pub(crate) fn snaps(chart: &GenericManiaChart) -> Vec<NoteSnap> {
+    let timing_map = chart.timing_map();
+
+    chart
+        .hitobjects
+        .iter()
+        .enumerate()
+        .filter(|(_, obj)| is_snappable(obj.key.key_type))
+        .filter_map(|(index, obj)| {
+            let snap = timing_map.snap_from_bpm(obj.time, &NOTE_TYPES)?;
+            Some(NoteSnap {
+                index,
+                time: obj.time,
+                divisor: snap.divisor(),
+                error: snap.error,
+                snapped: snap.error.abs() <= SNAP_TOLERANCE_MS,
+            })
+        })
+        .collect()
+}
+
+// moves notes, hold heads and hold ends onto the closest allowed snap when it's within tolerance_ms
+// notes further away are left alone, returns how many objects had their time or end time moved
+pub(crate) fn quantize(chart: &mut GenericManiaChart, allowed_divisors: &[u32], tolerance_ms: f64) -> u32 {
+    let timing_map = chart.timing_map();
+    let snap_time = |time: f64| {
+        timing_map
+            .snap_from_bpm(time, allowed_divisors)
+            .filter(|snap| snap.error.abs() >= ROUNDING_MS && snap.error.abs() <= tolerance_ms)
+    };
+
+    let mut moved = 0;
+    for obj in chart.hitobjects.objects.iter_mut() {
+        if !is_snappable(obj.key.key_type) {
+            continue;
+        }
+
+        let mut changed = false;
+
+        // heads keep their own end time, it snaps the same way as the slider end object
+        if let Some(end) = obj.key.slider_end_time.and_then(snap_time) {
+            obj.key.slider_end_time = Some(end.time);
+            changed = true;
+        }
+
+        // the snap's beat is counted from its bpm point, the note's beat is counted from the first one
+        if let Some(snap) = snap_time(obj.time) {
+            obj.time = snap.time;
+            obj.beat = timing_map.snapped_beat_at(snap.time);
+            changed = true;
+        }
+
+        if changed {
+            moved += 1;
+        }
+    }
+
+    if moved > 0 {
+        chart.hitobjects.objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    moved
+}
@@ -13,9 +13,17 @@ pub enum ParseError<GameMode: fmt::Display + 'static> {
 #[derive(Debug)]
 pub enum WriteError<GameMode: fmt::Display + 'static> {
     InvalidKeyCount(u8, String, String),
+    InvalidVersion(u8, String, String),
+    EmptyChartData(String),
     Unimpl(GameMode),
 }
 
+#[derive(Debug)]
+pub enum AnalysisError {
+    InvalidKeyCount(u8, String, String),
+    InvalidRate(f32),
+}
+
 impl<GameMode: fmt::Display + 'static> fmt::Display for ParseError<GameMode> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -31,12 +39,22 @@ impl<GameMode: fmt::Display + 'static> fmt::Display for WriteError<GameMode> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::InvalidKeyCount(key_count, avaibable_key_counts, format) => write!(f, "Failed to write because {key_count}k is not supported, {format} only supports {avaibable_key_counts}"),
+            Self::InvalidVersion(version, avaibable_versions, format) => write!(f, "Failed to write because v{version} is not supported, {format} only supports {avaibable_versions}"),
+            Self::EmptyChartData(format) => write!(f, "Failed to write because no charts were provided for {format}"),
             Self::Unimpl(_gamemode) => {unimplemented!()},
         }
     }
 }
 
-
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidKeyCount(key_count, available_key_counts, analyzer) => write!(f, "Failed to analyze because {key_count}k is not supported, {analyzer} only supports {available_key_counts}"),
+            Self::InvalidRate(rate) => write!(f, "Failed to analyze because {rate}x is not a valid music rate"),
+        }
+    }
+}
 
 impl<GameMode: fmt::Debug + fmt::Display + 'static> Error for ParseError<GameMode> {}
 impl<GameMode: fmt::Debug + fmt::Display + 'static> Error for WriteError<GameMode> {}
+impl Error for AnalysisError {}
@@ -9,6 +9,7 @@ pub(crate) mod macros;
 
 mod parsers;
 mod writers;
+mod analyzers;
 
 pub use models::common;
 pub use models::generic;
@@ -17,15 +18,17 @@ pub use models::osu;
 pub use models::quaver;
 
 pub use generic::GenericManiaChart;
+pub use generic::GenericTaikoChart;
+pub use generic::GenericCatchChart;
 pub use fluxis::FscFile;
 pub use osu::OsuFile;
 pub use quaver::QuaFile;
-pub use common::{GameMode, KeyType, Key};
+pub use common::{GameMode, KeyType, Key, Beat};
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod parse {
     use crate::parsers;
-    use crate::GenericManiaChart;
+    use crate::{GenericManiaChart, GenericTaikoChart, GenericCatchChart};
     use std::error::Error;
 
     #[inline]
@@ -33,6 +36,11 @@ pub mod parse {
         parsers::osu::from_osu_generic(raw_chart)
     }
 
+    #[inline]
+    pub fn from_osu_convert_to_mania(raw_chart: &str, key_count: Option<u8>) -> Result<GenericManiaChart, Box<dyn Error>> {
+        parsers::osu::from_osu_convert_to_mania(raw_chart, key_count)
+    }
+
     #[inline]
     pub fn from_sm_generic(raw_chart: &str) -> Result<GenericManiaChart, Box<dyn Error>> {
         parsers::stepmania::from_sm_generic(raw_chart)
@@ -47,12 +55,32 @@ pub mod parse {
     pub fn from_fsc_generic(raw_chart: &str) -> Result<GenericManiaChart, Box<dyn Error>> {
         parsers::fluxis::from_fsc_generic(raw_chart)
     }
+
+    #[inline]
+    pub fn from_osu_taiko(raw_chart: &str) -> Result<GenericTaikoChart, Box<dyn Error>> {
+        parsers::osu::from_osu_taiko(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_tja_taiko(raw_chart: &str) -> Result<Vec<GenericTaikoChart>, Box<dyn Error>> {
+        parsers::tja::from_tja_taiko(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_osu_catch(raw_chart: &str) -> Result<GenericCatchChart, Box<dyn Error>> {
+        parsers::osu::from_osu_catch(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_osb(raw_storyboard: &str) -> Result<crate::osu::Storyboard, Box<dyn Error>> {
+        parsers::osu::from_osb(raw_storyboard)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod write {
     use crate::writers;
-    use crate::GenericManiaChart;
+    use crate::{GenericManiaChart, GenericTaikoChart};
     use std::error::Error;
 
     #[inline]
@@ -60,6 +88,11 @@ pub mod write {
         writers::osu::to_osu_generic(chart)
     }
 
+    #[inline]
+    pub fn to_osu_generic_with_version(chart: &GenericManiaChart, version: u8) -> Result<String, Box<dyn Error>> {
+        writers::osu::to_osu_generic_with_version(chart, version)
+    }
+
     #[inline]
     pub fn to_sm_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn Error>> {
         writers::stepmania::to_sm_generic(chart)
@@ -74,13 +107,102 @@ pub mod write {
     pub fn to_fsc_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn Error>> {
         writers::fluxis::to_fsc_generic(chart)
     }
+
+    #[inline]
+    pub fn to_osu_taiko(chart: &GenericTaikoChart) -> Result<String, Box<dyn Error>> {
+        writers::osu::to_osu_taiko(chart)
+    }
+
+    #[inline]
+    pub fn to_tja_taiko(chart: &GenericTaikoChart) -> Result<String, Box<dyn Error>> {
+        writers::tja::to_tja_taiko(chart)
+    }
+
+    #[inline]
+    pub fn to_tja_taiko_courses(charts: &[GenericTaikoChart]) -> Result<String, Box<dyn Error>> {
+        writers::tja::to_tja_taiko_courses(charts)
+    }
+
+    #[inline]
+    pub fn to_osb(storyboard: &crate::osu::Storyboard) -> Result<String, Box<dyn Error>> {
+        writers::osu::to_osb(storyboard)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod analysis {
+    use std::error::Error;
+    use crate::analyzers;
+    use crate::GenericManiaChart;
+
+    pub use crate::analyzers::OsuMods;
+    pub use crate::analyzers::density::DensityFilter;
+    pub use crate::analyzers::diff::{ChartDiff, DiffKind, FieldChange, LnChange, MovedNote, NoteDiff, TimingPointDiff};
+    pub use crate::analyzers::lints::{Lint, LintKind};
+    pub use crate::analyzers::patterns::{PatternSection, PatternType, Patterns};
+    pub use crate::analyzers::snaps::NoteSnap;
+
+    #[inline]
+    pub fn osu_mania_star_rating(chart: &GenericManiaChart, mods: u32) -> f64 {
+        analyzers::osu_mania::star_rating(chart, mods)
+    }
+
+    #[inline]
+    pub fn quaver_difficulty(chart: &GenericManiaChart, rate: f32) -> Result<f32, Box<dyn Error>> {
+        analyzers::quaver::difficulty(chart, rate)
+    }
+
+    #[inline]
+    pub fn density(chart: &GenericManiaChart, window_ms: f64, step_ms: f64) -> Vec<f32> {
+        analyzers::density::density(chart, window_ms, step_ms, DensityFilter::ALL)
+    }
+
+    #[inline]
+    pub fn density_filtered(chart: &GenericManiaChart, window_ms: f64, step_ms: f64, filter: u32) -> Vec<f32> {
+        analyzers::density::density(chart, window_ms, step_ms, filter)
+    }
+
+    #[inline]
+    pub fn patterns(chart: &GenericManiaChart) -> Patterns {
+        analyzers::patterns::patterns(chart)
+    }
+
+    #[inline]
+    pub fn validate(chart: &GenericManiaChart) -> Vec<Lint> {
+        analyzers::lints::validate(chart)
+    }
+
+    #[inline]
+    pub fn fix(chart: &mut GenericManiaChart) -> Vec<Lint> {
+        analyzers::lints::fix(chart)
+    }
+
+    #[inline]
+    pub fn snaps(chart: &GenericManiaChart) -> Vec<NoteSnap> {
+        analyzers::snaps::snaps(chart)
+    }
+
+    #[inline]
+    pub fn quantize(chart: &mut GenericManiaChart, allowed_divisors: &[u32], tolerance_ms: f64) -> u32 {
+        analyzers::snaps::quantize(chart, allowed_divisors, tolerance_ms)
+    }
+
+    #[inline]
+    pub fn diff(old: &GenericManiaChart, new: &GenericManiaChart) -> ChartDiff {
+        analyzers::diff::diff(old, new, analyzers::diff::MATCH_TOLERANCE_MS)
+    }
+
+    #[inline]
+    pub fn diff_with_tolerance(old: &GenericManiaChart, new: &GenericManiaChart, tolerance_ms: f64) -> ChartDiff {
+        analyzers::diff::diff(old, new, tolerance_ms)
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
 pub mod parse {
     use wasm_bindgen::prelude::*;
     use crate::parsers;
-    use crate::GenericManiaChart;
+    use crate::{GenericManiaChart, GenericTaikoChart, GenericCatchChart};
 
     #[wasm_bindgen(js_name = parseFromOsuGeneric)]
     pub fn parse_from_osu_generic(raw_chart: &str) -> Result<GenericManiaChart, JsError> {
@@ -88,6 +210,12 @@ pub mod parse {
             .map_err(|e| JsError::new(&e.to_string()))
     }
 
+    #[wasm_bindgen(js_name = parseFromOsuConvertToMania)]
+    pub fn parse_from_osu_convert_to_mania(raw_chart: &str, key_count: Option<u8>) -> Result<GenericManiaChart, JsError> {
+        parsers::osu::from_osu_convert_to_mania(raw_chart, key_count)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
     #[wasm_bindgen(js_name = parseFromSmGeneric)]
     pub fn parse_from_sm_generic(raw_chart: &str) -> Result<GenericManiaChart, JsError> {
         parsers::stepmania::from_sm_generic(raw_chart)
@@ -105,13 +233,31 @@ pub mod parse {
         parsers::fluxis::from_fsc_generic(raw_chart)
             .map_err(|e| JsError::new(&e.to_string()))
     }
+
+    #[wasm_bindgen(js_name = parseFromOsuTaiko)]
+    pub fn parse_from_osu_taiko(raw_chart: &str) -> Result<GenericTaikoChart, JsError> {
+        parsers::osu::from_osu_taiko(raw_chart)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = parseFromTjaTaiko)]
+    pub fn parse_from_tja_taiko(raw_chart: &str) -> Result<Vec<GenericTaikoChart>, JsError> {
+        parsers::tja::from_tja_taiko(raw_chart)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = parseFromOsuCatch)]
+    pub fn parse_from_osu_catch(raw_chart: &str) -> Result<GenericCatchChart, JsError> {
+        parsers::osu::from_osu_catch(raw_chart)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
 pub mod write {
     use wasm_bindgen::prelude::*;
     use crate::writers;
-    use crate::GenericManiaChart;
+    use crate::{GenericManiaChart, GenericTaikoChart};
 
     #[wasm_bindgen(js_name = writeToOsuGeneric)]
     pub fn write_to_osu_generic(chart: &GenericManiaChart) -> Result<String, JsError> {
@@ -119,6 +265,12 @@ pub mod write {
             .map_err(|e| JsError::new(&e.to_string()))
     }
 
+    #[wasm_bindgen(js_name = writeToOsuGenericWithVersion)]
+    pub fn write_to_osu_generic_with_version(chart: &GenericManiaChart, version: u8) -> Result<String, JsError> {
+        writers::osu::to_osu_generic_with_version(chart, version)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
     #[wasm_bindgen(js_name = writeToSmGeneric)]
     pub fn write_to_sm_generic(chart: &GenericManiaChart) -> Result<String, JsError> {
         writers::stepmania::to_sm_generic(chart)
@@ -136,4 +288,92 @@ pub mod write {
         writers::fluxis::to_fsc_generic(chart)
             .map_err(|e| JsError::new(&e.to_string()))
     }
+
+    #[wasm_bindgen(js_name = writeToOsuTaiko)]
+    pub fn write_to_osu_taiko(chart: &GenericTaikoChart) -> Result<String, JsError> {
+        writers::osu::to_osu_taiko(chart)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = writeToTjaTaiko)]
+    pub fn write_to_tja_taiko(chart: &GenericTaikoChart) -> Result<String, JsError> {
+        writers::tja::to_tja_taiko(chart)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = writeToTjaTaikoCourses)]
+    pub fn write_to_tja_taiko_courses(charts: Vec<GenericTaikoChart>) -> Result<String, JsError> {
+        writers::tja::to_tja_taiko_courses(&charts)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub mod analysis {
+    use wasm_bindgen::prelude::*;
+    use crate::analyzers;
+    use crate::GenericManiaChart;
+
+    pub use crate::analyzers::OsuMods;
+    pub use crate::analyzers::density::DensityFilter;
+    pub use crate::analyzers::diff::{ChartDiff, DiffKind, FieldChange, LnChange, MovedNote, NoteDiff, TimingPointDiff};
+    pub use crate::analyzers::lints::{Lint, LintKind};
+    pub use crate::analyzers::patterns::{PatternSection, PatternType, Patterns};
+    pub use crate::analyzers::snaps::NoteSnap;
+
+    #[wasm_bindgen(js_name = osuManiaStarRating)]
+    pub fn osu_mania_star_rating(chart: &GenericManiaChart, mods: u32) -> f64 {
+        analyzers::osu_mania::star_rating(chart, mods)
+    }
+
+    #[wasm_bindgen(js_name = quaverDifficulty)]
+    pub fn quaver_difficulty(chart: &GenericManiaChart, rate: f32) -> Result<f32, JsError> {
+        analyzers::quaver::difficulty(chart, rate)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = density)]
+    pub fn density(chart: &GenericManiaChart, window_ms: f64, step_ms: f64) -> Vec<f32> {
+        analyzers::density::density(chart, window_ms, step_ms, DensityFilter::ALL)
+    }
+
+    #[wasm_bindgen(js_name = densityFiltered)]
+    pub fn density_filtered(chart: &GenericManiaChart, window_ms: f64, step_ms: f64, filter: u32) -> Vec<f32> {
+        analyzers::density::density(chart, window_ms, step_ms, filter)
+    }
+
+    #[wasm_bindgen(js_name = patterns)]
+    pub fn patterns(chart: &GenericManiaChart) -> Patterns {
+        analyzers::patterns::patterns(chart)
+    }
+
+    #[wasm_bindgen(js_name = validate)]
+    pub fn validate(chart: &GenericManiaChart) -> Vec<Lint> {
+        analyzers::lints::validate(chart)
+    }
+
+    #[wasm_bindgen(js_name = fix)]
+    pub fn fix(chart: &mut GenericManiaChart) -> Vec<Lint> {
+        analyzers::lints::fix(chart)
+    }
+
+    #[wasm_bindgen(js_name = snaps)]
+    pub fn snaps(chart: &GenericManiaChart) -> Vec<NoteSnap> {
+        analyzers::snaps::snaps(chart)
+    }
+
+    #[wasm_bindgen(js_name = quantize)]
+    pub fn quantize(chart: &mut GenericManiaChart, allowed_divisors: &[u32], tolerance_ms: f64) -> u32 {
+        analyzers::snaps::quantize(chart, allowed_divisors, tolerance_ms)
+    }
+
+    #[wasm_bindgen(js_name = diff)]
+    pub fn diff(old: &GenericManiaChart, new: &GenericManiaChart) -> ChartDiff {
+        analyzers::diff::diff(old, new, analyzers::diff::MATCH_TOLERANCE_MS)
+    }
+
+    #[wasm_bindgen(js_name = diffWithTolerance)]
+    pub fn diff_with_tolerance(old: &GenericManiaChart, new: &GenericManiaChart, tolerance_ms: f64) -> ChartDiff {
+        analyzers::diff::diff(old, new, tolerance_ms)
+    }
 }
@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+
+// float beats get snapped to 48 rows per beat (192nd notes), same grid StepMania uses
+pub const ROWS_PER_BEAT: u32 = 48;
+
+// exact beat position, always kept reduced so derived equality and hashing hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Beat {
+    numerator: i64,
+    denominator: u32,
+}
+
+#[inline]
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+impl Beat {
+    pub const ZERO: Beat = Beat { numerator: 0, denominator: 1 };
+
+    pub fn new(numerator: i64, denominator: u32) -> Self {
+        assert!(denominator != 0, "beat denominator can't be zero");
+        Self::reduced(numerator as i128, denominator as i128)
+    }
+
+    fn reduced(numerator: i128, denominator: i128) -> Self {
+        let divisor = gcd(numerator, denominator).max(1);
+        Self {
+            numerator: (numerator / divisor).try_into().expect("beat numerator out of range"),
+            denominator: (denominator / divisor).try_into().expect("beat denominator out of range"),
+        }
+    }
+
+    pub fn whole(beats: i64) -> Self {
+        Self { numerator: beats, denominator: 1 }
+    }
+
+    pub fn from_row(row: i64, rows_per_beat: u32) -> Self {
+        Self::new(row, rows_per_beat)
+    }
+
+    pub fn from_f64(beats: f64) -> Self {
+        if !beats.is_finite() {
+            return Self::ZERO;
+        }
+        Self::new((beats * ROWS_PER_BEAT as f64).round() as i64, ROWS_PER_BEAT)
+    }
+
+    #[inline]
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    #[inline]
+    pub fn denominator(&self) -> u32 {
+        self.denominator
+    }
+
+    #[inline]
+    pub fn as_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    #[inline]
+    pub fn as_f32(&self) -> f32 {
+        self.as_f64() as f32
+    }
+
+    #[inline]
+    pub fn is_whole(&self) -> bool {
+        self.denominator == 1
+    }
+
+    pub fn floor(&self) -> i64 {
+        self.numerator.div_euclid(self.denominator as i64)
+    }
+
+    pub fn fract(&self) -> Beat {
+        *self - Beat::whole(self.floor())
+    }
+
+    // nearest position on a grid with the given rows per beat
+    pub fn snap(&self, rows_per_beat: u32) -> Beat {
+        Self::from_row(self.nearest_row(rows_per_beat), rows_per_beat)
+    }
+
+    pub fn nearest_row(&self, rows_per_beat: u32) -> i64 {
+        let scaled = self.numerator as i128 * rows_per_beat as i128;
+        let denominator = self.denominator as i128;
+        ((2 * scaled + denominator).div_euclid(2 * denominator)) as i64
+    }
+
+    // only some if the beat lands exactly on the grid
+    pub fn row(&self, rows_per_beat: u32) -> Option<i64> {
+        let scaled = self.numerator as i128 * rows_per_beat as i128;
+        let denominator = self.denominator as i128;
+        (scaled % denominator == 0).then(|| (scaled / denominator) as i64)
+    }
+
+    pub fn abs(&self) -> Beat {
+        Self { numerator: self.numerator.abs(), denominator: self.denominator }
+    }
+}
+
+impl Default for Beat {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl fmt::Display for Beat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_whole() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl PartialOrd for Beat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Beat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = self.numerator as i128 * other.denominator as i128;
+        let rhs = other.numerator as i128 * self.denominator as i128;
+        lhs.cmp(&rhs)
+    }
+}
+
+impl Add for Beat {
+    type Output = Beat;
+
+    fn add(self, other: Beat) -> Beat {
+        let denominator = self.denominator as i128 * other.denominator as i128;
+        let numerator = self.numerator as i128 * other.denominator as i128
+            + other.numerator as i128 * self.denominator as i128;
+        Self::reduced(numerator, denominator)
+    }
+}
+
+impl Sub for Beat {
+    type Output = Beat;
+
+    fn sub(self, other: Beat) -> Beat {
+        self + Beat { numerator: -other.numerator, denominator: other.denominator }
+    }
+}
+
+impl AddAssign for Beat {
+    fn add_assign(&mut self, other: Beat) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Beat {
+    fn sub_assign(&mut self, other: Beat) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Beat {
+    type Output = Beat;
+
+    fn mul(self, factor: i64) -> Beat {
+        Self::reduced(self.numerator as i128 * factor as i128, self.denominator as i128)
+    }
+}
+
+impl Div<u32> for Beat {
+    type Output = Beat;
+
+    fn div(self, divisor: u32) -> Beat {
+        assert!(divisor != 0, "can't divide a beat by zero");
+        Self::reduced(self.numerator as i128, self.denominator as i128 * divisor as i128)
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 use crate::{def_varied_type_enum, wasm_bindgen};
 
+pub use crate::models::beat::Beat;
+
 def_varied_type_enum!(pub ChartDefaults {
     TITLE: &'static str => "Unknown Title",
     ALT_TITLE: &'static str => "Unknown Title",
@@ -30,13 +32,13 @@ def_varied_type_enum!(pub ChartDefaults {
 
 #[derive(Clone)]
 pub struct HitObjectRow {
-    pub time: i32,
-    pub beat: f32,
+    pub time: f64,
+    pub beat: Beat,
     pub keys: Vec<Key>
 }
 
 impl HitObjectRow {
-    pub fn empty(time: i32, beat: f32, key_count: usize) -> Self {
+    pub fn empty(time: f64, beat: Beat, key_count: usize) -> Self {
         HitObjectRow {
             time,
             beat,
@@ -48,11 +50,12 @@ impl HitObjectRow {
 pub type Measure = Vec<HitObjectRow>;
 
 #[wasm_bindgen]
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TimingChangeType {
     Bpm,
     Sv,
-    Stop
+    Stop,
+    TimeSignature
 }
 
 #[allow(unused)]
@@ -94,12 +97,12 @@ pub enum KeyType {
 }
 
 #[wasm_bindgen]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Key {
     #[wasm_bindgen(getter_with_clone)]
     pub key_type: KeyType,
     #[wasm_bindgen(getter_with_clone)]
-    pub slider_end_time: Option<i32>,
+    pub slider_end_time: Option<f64>,
 }
 
 impl HitObject for Key { 
@@ -127,7 +130,7 @@ impl Key {
     }
 
     #[wasm_bindgen]
-    pub fn slider_start(value: Option<i32>) -> Self {
+    pub fn slider_start(value: Option<f64>) -> Self {
         Self {
             key_type: KeyType::SliderStart,
             slider_end_time: value,
@@ -143,7 +146,7 @@ impl Key {
     }
 
     #[wasm_bindgen]
-    pub fn mine(value: Option<i32>) -> Self {
+    pub fn mine(value: Option<f64>) -> Self {
         Self {
             key_type: KeyType::Mine,
             slider_end_time: value,
@@ -175,7 +178,7 @@ impl Key {
     }
 
     #[wasm_bindgen]
-    pub fn slider_end_time(&self) -> Option<i32> {
+    pub fn slider_end_time(&self) -> Option<f64> {
         self.slider_end_time
     }
 }
@@ -195,12 +198,12 @@ pub enum TaikoHitobjectType {
 }
 
 #[wasm_bindgen]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TaikoHitobject {
     #[wasm_bindgen(getter_with_clone)]
     pub note_type: TaikoHitobjectType,
     #[wasm_bindgen(getter_with_clone)]
-    pub end_time: Option<i32>,
+    pub end_time: Option<f64>,
 }
 
 impl HitObject for TaikoHitobject {
@@ -252,7 +255,7 @@ impl TaikoHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn drum_roll(end_time: i32) -> Self {
+    pub fn drum_roll(end_time: f64) -> Self {
         Self {
             note_type: TaikoHitobjectType::DrumRoll,
             end_time: Some(end_time),
@@ -260,7 +263,7 @@ impl TaikoHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn bonus_drum_roll(end_time: i32) -> Self {
+    pub fn bonus_drum_roll(end_time: f64) -> Self {
         Self {
             note_type: TaikoHitobjectType::BonusDrumRoll,
             end_time: Some(end_time),
@@ -268,7 +271,7 @@ impl TaikoHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn balloon(end_time: i32) -> Self {
+    pub fn balloon(end_time: f64) -> Self {
         Self {
             note_type: TaikoHitobjectType::Balloon,
             end_time: Some(end_time),
@@ -284,7 +287,7 @@ impl TaikoHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn end_time(&self) -> Option<i32> {
+    pub fn end_time(&self) -> Option<f64> {
         self.end_time
     }
 }
@@ -295,6 +298,7 @@ pub enum CatchHitobjectType {
     Empty,
     Fruit,
     Juice,
+    TinyDroplet,
     Banana,
     Hyperfruit,
     Unknown,
@@ -308,7 +312,7 @@ pub struct CatchHitobject {
     #[wasm_bindgen(getter_with_clone)]
     pub x_position: i32,
     #[wasm_bindgen(getter_with_clone)]
-    pub end_time: Option<i32>,
+    pub end_time: Option<f64>,
     #[wasm_bindgen(getter_with_clone)]
     pub hyperdash: bool,
 }
@@ -352,7 +356,17 @@ impl CatchHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn banana(x_position: i32, end_time: i32) -> Self {
+    pub fn tiny_droplet(x_position: i32) -> Self {
+        Self {
+            object_type: CatchHitobjectType::TinyDroplet,
+            x_position,
+            end_time: None,
+            hyperdash: false,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn banana(x_position: i32, end_time: f64) -> Self {
         Self {
             object_type: CatchHitobjectType::Banana,
             x_position,
@@ -382,7 +396,7 @@ impl CatchHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn end_time(&self) -> Option<i32> {
+    pub fn end_time(&self) -> Option<f64> {
         self.end_time
     }
 
@@ -414,7 +428,7 @@ pub struct OsuHitobject {
     #[wasm_bindgen(getter_with_clone)]
     pub y: i32,
      #[wasm_bindgen(getter_with_clone)]
-    pub end_time: Option<i32>,
+    pub end_time: Option<f64>,
     #[wasm_bindgen(getter_with_clone)]
     pub new_combo: bool,
     #[wasm_bindgen(getter_with_clone)]
@@ -466,7 +480,7 @@ impl OsuHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn spinner(end_time: i32) -> Self {
+    pub fn spinner(end_time: f64) -> Self {
         Self {
             object_type: OsuHitobjectType::Spinner,
             x: 256,
@@ -502,7 +516,7 @@ impl OsuHitobject {
     }
 
     #[wasm_bindgen]
-    pub fn end_time(&self) -> Option<i32> {
+    pub fn end_time(&self) -> Option<f64> {
         self.end_time
     }
 
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::models::generic::{ChartColors, Color};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Metadata {
@@ -98,4 +99,29 @@ impl Default for Colors {
             middle: String::new(),
         }
     }
-}
\ No newline at end of file
+}
+
+impl Colors {
+    pub fn to_generic(&self) -> ChartColors {
+        let accent = Color::from_hex(&self.accent);
+        let primary = Color::from_hex(&self.primary);
+        let secondary = Color::from_hex(&self.secondary);
+        let middle = Color::from_hex(&self.middle);
+
+        ChartColors {
+            // games with only combo colors still get the ones that are set
+            combo_colors: [accent, primary, secondary, middle].into_iter().flatten().collect(),
+            accent,
+            primary,
+            secondary,
+            middle,
+            ..ChartColors::default()
+        }
+    }
+
+    pub fn from_generic(colors: &ChartColors) -> Self {
+        let [accent, primary, secondary, middle] = colors.theme_colors().map(|color| color.map(|c| c.to_hex()).unwrap_or_default());
+
+        Colors { accent, primary, secondary, middle }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::wasm_bindgen;
+use crate::models::common::{Beat, CatchHitobject, CatchHitobjectType};
+use crate::models::generic::{
+    chartinfo::ChartInfo, metadata::Metadata, sound::{KeySound, SoundBank},
+    timing_map::TimingMap, timing_points::TimingPoints,
+};
+
+pub const CATCH_PLAYFIELD_WIDTH: f32 = 512.0;
+const CATCHER_BASE_SIZE: f32 = 106.75;
+const CATCHER_BASE_DASH_SPEED: f32 = 1.0;
+
+#[derive(Debug, Clone)]
+pub struct CatchHitObject {
+    pub time: f64,
+    pub beat: Beat,
+    // exact playfield position, note.x_position is the rounded one
+    pub x: f32,
+    pub keysound: KeySound,
+    pub note: CatchHitobject,
+    pub distance_to_hyperdash: f32,
+    pub hyperdash_target: Option<usize>,
+}
+
+impl CatchHitObject {
+    pub fn new(time: f64, beat: Beat, x: f32, keysound: KeySound, note: CatchHitobject) -> Self {
+        Self {
+            time,
+            beat,
+            x,
+            keysound,
+            note: CatchHitobject {
+                x_position: x.round() as i32,
+                ..note
+            },
+            distance_to_hyperdash: 0.0,
+            hyperdash_target: None,
+        }
+    }
+
+    pub fn is_fruit(&self) -> bool {
+        matches!(self.note.object_type, CatchHitobjectType::Fruit | CatchHitobjectType::Hyperfruit)
+    }
+
+    pub fn is_droplet(&self) -> bool {
+        self.note.object_type == CatchHitobjectType::Juice
+    }
+
+    pub fn is_tiny_droplet(&self) -> bool {
+        self.note.object_type == CatchHitobjectType::TinyDroplet
+    }
+
+    pub fn is_banana(&self) -> bool {
+        self.note.object_type == CatchHitobjectType::Banana
+    }
+
+    // objects the catcher has to move to, tiny droplets and bananas don't count
+    pub fn is_palpable(&self) -> bool {
+        self.is_fruit() || self.is_droplet()
+    }
+
+    pub fn is_hyperdash(&self) -> bool {
+        self.note.hyperdash
+    }
+}
+
+#[wasm_bindgen]
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct CatchHitObjects {
+    #[wasm_bindgen(skip)]
+    pub objects: Vec<CatchHitObject>,
+}
+
+impl From<Vec<CatchHitObject>> for CatchHitObjects {
+    fn from(objects: Vec<CatchHitObject>) -> Self {
+        Self { objects }
+    }
+}
+
+impl CatchHitObjects {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            objects: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn new(objects: Vec<CatchHitObject>) -> Self {
+        Self { objects }
+    }
+
+    #[inline]
+    pub fn add_hitobject_sorted(&mut self, object: CatchHitObject) {
+        let len = self.objects.len();
+
+        if len == 0 || object.time >= self.objects[len - 1].time {
+            self.objects.push(object);
+            return;
+        }
+
+        let pos = self.objects.partition_point(|obj| obj.time <= object.time);
+        self.objects.insert(pos, object);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CatchHitObject> {
+        self.objects.iter()
+    }
+
+    pub fn iter_palpable(&self) -> impl Iterator<Item = &CatchHitObject> {
+        self.objects.iter().filter(|obj| obj.is_palpable())
+    }
+
+    pub fn hyperdash_count(&self) -> usize {
+        self.objects.iter().filter(|obj| obj.is_hyperdash()).count()
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct GenericCatchChart {
+    #[wasm_bindgen(getter_with_clone)]
+    pub metadata: Metadata,
+    #[wasm_bindgen(getter_with_clone)]
+    pub chartinfo: ChartInfo,
+    #[wasm_bindgen(getter_with_clone)]
+    pub timing_points: TimingPoints,
+    #[wasm_bindgen(getter_with_clone)]
+    pub hitobjects: CatchHitObjects,
+    #[wasm_bindgen(getter_with_clone)]
+    pub soundbank: Option<SoundBank>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub circle_size: f32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub approach_rate: f32,
+}
+
+impl GenericCatchChart {
+    pub(crate) fn new(
+        metadata: Metadata,
+        chartinfo: ChartInfo,
+        timing_points: TimingPoints,
+        hitobjects: CatchHitObjects,
+        soundbank: Option<SoundBank>,
+        circle_size: f32,
+        approach_rate: f32,
+    ) -> Self {
+        let mut chart = Self {
+            metadata,
+            chartinfo,
+            timing_points,
+            hitobjects,
+            soundbank,
+            circle_size,
+            approach_rate,
+        };
+        chart.compute_hyperdashes();
+        chart
+    }
+
+    pub fn catcher_width(&self) -> f32 {
+        let scale = 1.0 - 0.7 * (self.circle_size - 5.0) / 5.0;
+        CATCHER_BASE_SIZE * scale.abs()
+    }
+
+    // same rules as osu!stable, the catcher has to be able to reach the next object while dashing
+    // or it will get a hyperdash to it
+    pub fn compute_hyperdashes(&mut self) {
+        let half_catcher_width = self.catcher_width() / 2.0;
+        let objects = &mut self.hitobjects.objects;
+
+        let mut palpable: Vec<usize> = (0..objects.len())
+            .filter(|&i| objects[i].is_palpable())
+            .collect();
+        palpable.sort_by(|&a, &b| objects[a].time.total_cmp(&objects[b].time));
+
+        for object in objects.iter_mut() {
+            object.distance_to_hyperdash = 0.0;
+            object.hyperdash_target = None;
+            object.note.hyperdash = false;
+            if object.note.object_type == CatchHitobjectType::Hyperfruit {
+                object.note.object_type = CatchHitobjectType::Fruit;
+            }
+        }
+
+        let mut last_direction = 0;
+        let mut last_excess = half_catcher_width;
+
+        for pair in palpable.windows(2) {
+            let (current, next) = (&objects[pair[0]], &objects[pair[1]]);
+
+            let direction = if next.x > current.x { 1 } else { -1 };
+            // a quarter of a frame of grace time
+            let time_to_next = (next.time - current.time) as f32 - 1000.0 / 60.0 / 4.0;
+            let distance_to_next = (next.x - current.x).abs()
+                - if last_direction == direction { last_excess } else { half_catcher_width };
+            let distance_to_hyperdash = time_to_next * CATCHER_BASE_DASH_SPEED - distance_to_next;
+
+            let current = &mut objects[pair[0]];
+            if distance_to_hyperdash < 0.0 {
+                current.hyperdash_target = Some(pair[1]);
+                current.note.hyperdash = true;
+                if current.note.object_type == CatchHitobjectType::Fruit {
+                    current.note.object_type = CatchHitobjectType::Hyperfruit;
+                }
+                last_excess = half_catcher_width;
+            } else {
+                current.distance_to_hyperdash = distance_to_hyperdash;
+                last_excess = distance_to_hyperdash.clamp(0.0, half_catcher_width);
+            }
+
+            last_direction = direction;
+        }
+    }
+
+    // the time of the last object, bananas and droplets included
+    pub(crate) fn end_time(&self) -> f64 {
+        self.hitobjects.iter().map(|obj| obj.time).fold(0.0, f64::max)
+    }
+}
+
+#[wasm_bindgen]
+impl GenericCatchChart {
+    pub fn timing_map(&self) -> TimingMap {
+        TimingMap::new(&self.timing_points, self.chartinfo.bpm_affects_sv, self.end_time())
+    }
+}
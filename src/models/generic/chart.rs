@@ -1,6 +1,7 @@
 use crate::models::generic::{
-    chartinfo::ChartInfo, hitobjects::HitObjects, metadata::Metadata, sound::SoundBank,
-    timing_points::TimingPoints,
+    chartinfo::ChartInfo, colors::ChartColors, hitobjects::HitObjects, metadata::Metadata,
+    sound::SoundBank, scroll_positions::{ScrollPositions, ScrollRules}, snap_divisors::SnapDivisors, stats::ChartStats,
+    timing_map::TimingMap, timing_points::TimingPoints,
 };
 use crate::wasm_bindgen;
 
@@ -18,6 +19,8 @@ pub struct GenericManiaChart {
     pub hitobjects: HitObjects,
     #[wasm_bindgen(getter_with_clone)]
     pub soundbank: Option<SoundBank>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub colors: Option<ChartColors>,
 }
 
 impl GenericManiaChart {
@@ -27,6 +30,7 @@ impl GenericManiaChart {
         timing_points: TimingPoints,
         hitobjects: HitObjects,
         soundbank: Option<SoundBank>,
+        colors: Option<ChartColors>,
     ) -> Self {
         Self {
             metadata,
@@ -34,6 +38,37 @@ impl GenericManiaChart {
             timing_points,
             hitobjects,
             soundbank,
+            colors,
         }
     }
+
+    // the time of the last object, hold ends included
+    pub(crate) fn end_time(&self) -> f64 {
+        self.hitobjects.iter().map(|obj| obj.time).fold(0.0, f64::max)
+    }
+}
+
+#[wasm_bindgen]
+impl GenericManiaChart {
+    pub fn timing_map(&self) -> TimingMap {
+        TimingMap::new(&self.timing_points, self.chartinfo.bpm_affects_sv, self.end_time())
+    }
+
+    pub fn scroll_positions(&self, rules: ScrollRules) -> ScrollPositions {
+        ScrollPositions::new(self, rules)
+    }
+
+    // computed once, keep it around instead of asking for every note
+    pub fn snap_divisors(&self) -> SnapDivisors {
+        SnapDivisors::new(self)
+    }
+
+    // visible measure line times up to the last object
+    pub fn barlines(&self) -> Vec<f64> {
+        self.timing_points.barlines(self.end_time())
+    }
+
+    pub fn stats(&self) -> ChartStats {
+        ChartStats::new(self)
+    }
 }
@@ -16,7 +16,7 @@ pub struct ChartInfo {
     #[wasm_bindgen(getter_with_clone)]
     pub song_path: String,
     #[wasm_bindgen(getter_with_clone)]
-    pub audio_offset: i32,
+    pub audio_offset: f64,
     #[wasm_bindgen(getter_with_clone)]
     pub preview_time: i32,
     #[wasm_bindgen(getter_with_clone)]
@@ -35,7 +35,7 @@ impl ChartInfo {
         bg_path: String,
         video_path: String,
         song_path: String,
-        audio_offset: i32,
+        audio_offset: f64,
         preview_time: i32,
         key_count: u8,
         bpm_affects_sv: bool,
@@ -62,7 +62,7 @@ impl ChartInfo {
             bg_path: String::with_capacity(20),
             video_path: String::with_capacity(20),
             song_path: String::with_capacity(10),
-            audio_offset: 0,
+            audio_offset: 0.0,
             preview_time: 0,
             key_count: 4,
             bpm_affects_sv: false,
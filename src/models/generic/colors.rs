@@ -0,0 +1,87 @@
+use crate::wasm_bindgen;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    #[wasm_bindgen(getter_with_clone)]
+    pub red: u8,
+    #[wasm_bindgen(getter_with_clone)]
+    pub green: u8,
+    #[wasm_bindgen(getter_with_clone)]
+    pub blue: u8,
+}
+
+#[wasm_bindgen]
+impl Color {
+    #[wasm_bindgen(constructor)]
+    pub fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() < 6 {
+            return None;
+        }
+
+        let red = u8::from_str_radix(hex.get(0..2)?, 16).ok()?;
+        let green = u8::from_str_radix(hex.get(2..4)?, 16).ok()?;
+        let blue = u8::from_str_radix(hex.get(4..6)?, 16).ok()?;
+
+        Some(Self { red, green, blue })
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct ChartColors {
+    #[wasm_bindgen(getter_with_clone)]
+    pub combo_colors: Vec<Color>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub slider_track: Option<Color>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub slider_border: Option<Color>,
+    // fluXis' theme colors, each one keeps its role even when another is missing
+    #[wasm_bindgen(getter_with_clone)]
+    pub accent: Option<Color>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub primary: Option<Color>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub secondary: Option<Color>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub middle: Option<Color>,
+}
+
+#[wasm_bindgen]
+impl ChartColors {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combo_colors.is_empty()
+            && self.slider_track.is_none()
+            && self.slider_border.is_none()
+            && !self.has_theme_colors()
+    }
+
+    pub fn has_theme_colors(&self) -> bool {
+        self.accent.is_some() || self.primary.is_some() || self.secondary.is_some() || self.middle.is_some()
+    }
+}
+
+impl ChartColors {
+    // accent, primary, secondary and middle, charts without them use their first 4 combo colors in that order
+    pub fn theme_colors(&self) -> [Option<Color>; 4] {
+        if self.has_theme_colors() {
+            [self.accent, self.primary, self.secondary, self.middle]
+        } else {
+            std::array::from_fn(|i| self.combo_colors.get(i).copied())
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use crate::wasm_bindgen;
-use crate::models::common::{Key, KeyType};
+use crate::models::common::{Beat, Key, KeyType};
 use crate::models::generic::sound::{KeySound};
 
 #[derive(Debug, Clone)]
 pub struct HitObject {
-    pub time: i32,
-    pub beat: f32,
+    pub time: f64,
+    pub beat: Beat,
     pub keysound: KeySound,
     pub key: Key,
     pub lane: u8,
@@ -13,7 +13,6 @@ pub struct HitObject {
 }
 
 // TODO: add wasm bindings for HitObject
-// TODO: add row and object count
 #[wasm_bindgen]
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -42,8 +41,8 @@ impl HitObjects {
     #[inline]
     pub fn add_hitobject(
         &mut self,
-        time: i32,
-        beat: f32,
+        time: f64,
+        beat: Beat,
         keysound: KeySound,
         key: Key,
         lane: u8,
@@ -71,10 +70,8 @@ impl HitObjects {
             return;
         }
 
-        let pos = self.objects.binary_search_by(|obj| 
-            obj.time.cmp(&object.time)
-        ).unwrap_or_else(|pos| pos);
-        
+        // objects at the same time stay in the order they were added
+        let pos = self.objects.partition_point(|obj| obj.time <= object.time);
         self.objects.insert(pos, object);
     }
 
@@ -3,6 +3,13 @@ mod chartinfo;
 mod metadata;
 mod hitobjects;
 mod timing_points;
+mod timing_map;
+mod scroll_positions;
+mod snap_divisors;
+mod stats;
+mod colors;
+mod taiko;
+mod catch;
 pub mod sound;
 
 pub use chart::GenericManiaChart;
@@ -10,4 +17,11 @@ pub use chartinfo::ChartInfo;
 pub use metadata::Metadata;
 pub use hitobjects::*;
 pub use timing_points::*;
+pub use timing_map::*;
+pub use scroll_positions::*;
+pub use snap_divisors::*;
+pub use stats::*;
+pub use colors::*;
+pub use taiko::*;
+pub use catch::*;
 pub use sound::*;
\ No newline at end of file
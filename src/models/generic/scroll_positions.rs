@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use crate::wasm_bindgen;
+use crate::models::common::TimingChangeType;
+use crate::models::generic::chart::GenericManiaChart;
+use crate::models::generic::timing_map::TimingMap;
+use crate::models::generic::timing_points::{TimingChange, TimingPoint, TimingPoints};
+
+// osu! keeps inherited point multipliers in this range
+const OSU_SV_RANGE: (f32, f32) = (0.1, 10.0);
+
+// every game integrates sv a bit differently, pick the one the chart is going to be shown as
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollRules {
+    // sv is relative to the bpm and resets on every uninherited point
+    Osu,
+    // the bpm only counts when BPMDoesNotAffectScrollVelocity is off
+    Quaver,
+    // sv is per group and the bpm never affects it
+    FluXis,
+    // notes are spaced by beats so the bpm always counts, stops freeze the playfield
+    StepMania,
+}
+
+// track positions of every note in the chart, in milliseconds at 1x
+// a note is on the receptor when the position of its group reaches its own
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct ScrollPositions {
+    // same order as the chart's hitobjects
+    #[wasm_bindgen(getter_with_clone)]
+    pub positions: Vec<f64>,
+    pub rules: ScrollRules,
+    timing_map: TimingMap,
+}
+
+#[wasm_bindgen]
+impl ScrollPositions {
+    #[wasm_bindgen(constructor)]
+    pub fn new(chart: &GenericManiaChart, rules: ScrollRules) -> Self {
+        let bpm_affects_sv = match rules {
+            ScrollRules::Osu | ScrollRules::StepMania => true,
+            ScrollRules::Quaver => chart.chartinfo.bpm_affects_sv,
+            ScrollRules::FluXis => false,
+        };
+
+        let timing_map = match rules {
+            ScrollRules::Osu => TimingMap::new(&osu_timing_points(&chart.timing_points), bpm_affects_sv, chart.end_time()),
+            _ => TimingMap::new(&chart.timing_points, bpm_affects_sv, chart.end_time()),
+        };
+
+        let positions = chart
+            .hitobjects
+            .iter()
+            .map(|obj| timing_map.position_at(obj.time, obj.group.as_deref().unwrap_or_default()))
+            .collect();
+
+        Self { positions, rules, timing_map }
+    }
+
+    // where the playfield of a group is at, subtract it from a note's position to get its distance from the receptor
+    pub fn position_at(&self, time: f64, group: &str) -> f64 {
+        self.timing_map.position_at(time, group)
+    }
+
+    pub fn position_of(&self, index: usize) -> Option<f64> {
+        self.positions.get(index).copied()
+    }
+}
+
+// osu! has no groups, so only the svs of the default group get reset and clamped
+fn osu_timing_points(timing_points: &TimingPoints) -> TimingPoints {
+    let is_default_sv = |p: &TimingPoint| p.change.change_type == TimingChangeType::Sv && p.group.is_empty();
+    let sv_times: HashSet<u64> = timing_points
+        .iter()
+        .filter(|p| is_default_sv(p))
+        .map(|p| p.time.to_bits())
+        .collect();
+
+    let mut points: Vec<TimingPoint> = Vec::with_capacity(timing_points.points.len() * 2);
+    for point in timing_points.iter() {
+        let mut point = point.clone();
+        if is_default_sv(&point) {
+            point.change.value = point.change.value.clamp(OSU_SV_RANGE.0, OSU_SV_RANGE.1);
+        }
+
+        // a green line on the same time as the red one overrides the reset
+        let resets_sv = point.change.change_type == TimingChangeType::Bpm && !sv_times.contains(&point.time.to_bits());
+        let (time, beat) = (point.time, point.beat);
+        points.push(point);
+
+        if resets_sv {
+            points.push(TimingPoint::new(
+                time,
+                beat,
+                String::new(),
+                TimingChange { value: 1.0, change_type: TimingChangeType::Sv },
+            ));
+        }
+    }
+
+    points.into()
+}
@@ -0,0 +1,52 @@
+use crate::wasm_bindgen;
+use crate::models::generic::chart::GenericManiaChart;
+use crate::models::generic::colors::Color;
+use crate::utils::rhythm::NOTE_TYPES;
+
+// snap divisor of every note counted from the bpm point it's under, 4 is a 1/4 note (a 16th in StepMania)
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapDivisors {
+    // same order as the chart's hitobjects, 0 when the chart has no bpm
+    #[wasm_bindgen(getter_with_clone)]
+    pub divisors: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl SnapDivisors {
+    #[wasm_bindgen(constructor)]
+    pub fn new(chart: &GenericManiaChart) -> Self {
+        let timing_map = chart.timing_map();
+
+        let divisors = chart
+            .hitobjects
+            .iter()
+            .map(|obj| timing_map.snap_from_bpm(obj.time, &NOTE_TYPES).map_or(0, |snap| snap.divisor()))
+            .collect();
+
+        Self { divisors }
+    }
+
+    pub fn divisor_of(&self, index: usize) -> Option<u32> {
+        self.divisors.get(index).copied()
+    }
+
+    pub fn color_of(&self, index: usize) -> Option<Color> {
+        self.divisor_of(index).map(Self::color)
+    }
+
+    // StepMania's note colors, red 4ths (1 per beat), blue 8ths, purple 12ths, yellow 16ths and so on
+    pub fn color(divisor: u32) -> Color {
+        match divisor {
+            1 => Color::new(255, 0, 0),
+            2 => Color::new(0, 0, 255),
+            3 => Color::new(128, 0, 255),
+            4 => Color::new(255, 255, 0),
+            6 => Color::new(255, 0, 255),
+            8 => Color::new(255, 128, 0),
+            12 => Color::new(0, 255, 255),
+            16 => Color::new(0, 255, 0),
+            _ => Color::new(128, 128, 128),
+        }
+    }
+}
@@ -14,7 +14,7 @@ pub enum HitSoundType {
 #[derive(Debug, Clone)]
 pub struct SoundEffect {
     #[wasm_bindgen(getter_with_clone)]
-    pub time: i32,
+    pub time: f64,
     #[wasm_bindgen(getter_with_clone)]
     pub volume: u8,
     #[wasm_bindgen(getter_with_clone)]
@@ -25,7 +25,7 @@ pub struct SoundEffect {
 impl SoundEffect {
     #[inline]
     #[wasm_bindgen(constructor)]
-    pub fn new(time: i32, volume: u8, sample: usize) -> Self {
+    pub fn new(time: f64, volume: u8, sample: usize) -> Self {
         Self { time, volume, sample }
     }
 }
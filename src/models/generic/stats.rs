@@ -0,0 +1,147 @@
+use crate::wasm_bindgen;
+use crate::models::common::{KeyType, TimingChangeType};
+use crate::models::generic::chart::GenericManiaChart;
+
+const NPS_WINDOW_MS: f64 = 1000.0;
+
+// basic numbers about a chart, times are in milliseconds
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartStats {
+    pub note_count: u32,
+    pub ln_count: u32,
+    pub mine_count: u32,
+    pub tick_count: u32,
+    // rows with a tap or a hold head
+    pub row_count: u32,
+    // index 0 is the amount of single notes, 1 is jumps and so on
+    #[wasm_bindgen(getter_with_clone)]
+    pub chord_histogram: Vec<u32>,
+    // from the first object to the end of the last one
+    pub drain_time: f64,
+    // from the start of the audio to the end of the last object
+    pub total_length: f64,
+    // holds count their head and their release
+    pub max_combo_osu: u32,
+    pub max_combo_quaver: u32,
+    // holds only count their head
+    pub max_combo_etterna: u32,
+    // over a one second window
+    pub peak_nps: f64,
+    pub average_nps: f64,
+    pub min_bpm: f32,
+    pub max_bpm: f32,
+    // the bpm that lasts the longest until the end of the chart
+    pub dominant_bpm: f32,
+}
+
+impl ChartStats {
+    pub fn new(chart: &GenericManiaChart) -> Self {
+        let mut stats = Self::default();
+
+        for obj in chart.hitobjects.iter() {
+            match obj.key.key_type {
+                KeyType::Normal => stats.note_count += 1,
+                KeyType::SliderStart => stats.ln_count += 1,
+                KeyType::Mine => stats.mine_count += 1,
+                KeyType::Tick => stats.tick_count += 1,
+                _ => {}
+            }
+        }
+
+        let mut heads: Vec<f64> = chart
+            .hitobjects
+            .iter()
+            .filter(|obj| matches!(obj.key.key_type, KeyType::Normal | KeyType::SliderStart))
+            .map(|obj| obj.time)
+            .collect();
+        heads.sort_by(|a, b| a.total_cmp(b));
+
+        for row in heads.chunk_by(|a, b| a == b) {
+            if stats.chord_histogram.len() < row.len() {
+                stats.chord_histogram.resize(row.len(), 0);
+            }
+            stats.chord_histogram[row.len() - 1] += 1;
+            stats.row_count += 1;
+        }
+
+        let start_time = chart
+            .hitobjects
+            .iter()
+            .filter(|obj| obj.key.key_type != KeyType::Fake)
+            .map(|obj| obj.time)
+            .fold(f64::MAX, f64::min);
+        let end_time = chart
+            .hitobjects
+            .iter()
+            .map(|obj| obj.key.slider_end_time().unwrap_or(obj.time).max(obj.time))
+            .fold(0.0, f64::max);
+
+        if start_time <= end_time {
+            stats.drain_time = end_time - start_time;
+            stats.total_length = end_time;
+        }
+
+        stats.max_combo_osu = stats.head_and_release_combo();
+        stats.max_combo_quaver = stats.head_and_release_combo();
+        stats.max_combo_etterna = stats.note_count + stats.ln_count;
+
+        let mut window_start = 0;
+        for (index, time) in heads.iter().enumerate() {
+            while time - heads[window_start] >= NPS_WINDOW_MS {
+                window_start += 1;
+            }
+            stats.peak_nps = stats.peak_nps.max((index + 1 - window_start) as f64 * 1000.0 / NPS_WINDOW_MS);
+        }
+        if stats.drain_time > 0.0 {
+            stats.average_nps = heads.len() as f64 / (stats.drain_time / 1000.0);
+        }
+
+        stats.set_bpms(chart, end_time);
+        stats
+    }
+
+    // osu!lazer's HoldNote judges a head and a tail (osu.Game.Rulesets.Mania/Objects/HoldNote.cs) and Quaver
+    // counts two judgements per long note (Quaver.API ScoreProcessorKeys.GetTotalJudgementCount)
+    // osu!stable gave combo for hold ticks too, that isn't counted here
+    fn head_and_release_combo(&self) -> u32 {
+        self.note_count + self.ln_count * 2
+    }
+
+    fn set_bpms(&mut self, chart: &GenericManiaChart, end_time: f64) {
+        let mut bpms: Vec<(f64, f32)> = chart
+            .timing_points
+            .iter()
+            .filter(|p| p.change.change_type == TimingChangeType::Bpm && p.change.value > 0.0)
+            .map(|p| (p.time, p.change.value))
+            .collect();
+        bpms.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let Some(first) = bpms.first() else {
+            return;
+        };
+
+        self.min_bpm = bpms.iter().map(|(_, bpm)| *bpm).fold(f32::MAX, f32::min);
+        self.max_bpm = bpms.iter().map(|(_, bpm)| *bpm).fold(0.0, f32::max);
+        self.dominant_bpm = first.1;
+
+        let mut durations: Vec<(f32, f64)> = Vec::new();
+        for (index, (time, bpm)) in bpms.iter().enumerate() {
+            let next_time = bpms.get(index + 1).map_or(end_time, |(next, _)| *next).min(end_time);
+            let duration = (next_time - time).max(0.0);
+            match durations.iter_mut().find(|(other, _)| other == bpm) {
+                Some((_, total)) => *total += duration,
+                None => durations.push((*bpm, duration)),
+            }
+        }
+
+        if let Some((bpm, total)) = durations.iter().fold(None, |best: Option<(f32, f64)>, &(bpm, total)| match best {
+            Some((_, best_total)) if best_total >= total => best,
+            _ => Some((bpm, total)),
+        }) {
+            if total > 0.0 {
+                self.dominant_bpm = bpm;
+            }
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use crate::wasm_bindgen;
+use crate::models::common::{Beat, TaikoHitobject, TaikoHitobjectType};
+use crate::models::generic::{
+    chartinfo::ChartInfo, metadata::Metadata, sound::{KeySound, SoundBank},
+    timing_map::TimingMap, timing_points::TimingPoints,
+};
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaikoCourse {
+    Easy,
+    Normal,
+    Hard,
+    Oni,
+    Edit,
+    Tower,
+    Dan,
+}
+
+impl TaikoCourse {
+    pub fn from_tja(s: &str) -> Option<TaikoCourse> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" | "0" => Some(TaikoCourse::Easy),
+            "normal" | "1" => Some(TaikoCourse::Normal),
+            "hard" | "2" => Some(TaikoCourse::Hard),
+            "oni" | "3" => Some(TaikoCourse::Oni),
+            "edit" | "ura" | "4" => Some(TaikoCourse::Edit),
+            "tower" | "5" => Some(TaikoCourse::Tower),
+            "dan" | "6" => Some(TaikoCourse::Dan),
+            _ => None,
+        }
+    }
+
+    pub fn to_tja(&self) -> &'static str {
+        match self {
+            TaikoCourse::Easy => "Easy",
+            TaikoCourse::Normal => "Normal",
+            TaikoCourse::Hard => "Hard",
+            TaikoCourse::Oni => "Oni",
+            TaikoCourse::Edit => "Edit",
+            TaikoCourse::Tower => "Tower",
+            TaikoCourse::Dan => "Dan",
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchType {
+    Normal,
+    Expert,
+    Master,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchCondition {
+    Roll,
+    Accuracy,
+    Score,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BranchSection {
+    #[wasm_bindgen(getter_with_clone)]
+    pub start_time: f64,
+    #[wasm_bindgen(getter_with_clone)]
+    pub end_time: f64,
+    #[wasm_bindgen(getter_with_clone)]
+    pub condition: BranchCondition,
+    #[wasm_bindgen(getter_with_clone)]
+    pub expert_requirement: f32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub master_requirement: f32,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GogoSection {
+    #[wasm_bindgen(getter_with_clone)]
+    pub start_time: f64,
+    #[wasm_bindgen(getter_with_clone)]
+    pub end_time: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaikoHitObject {
+    pub time: f64,
+    pub beat: Beat,
+    pub keysound: KeySound,
+    pub note: TaikoHitobject,
+    pub end_beat: Option<Beat>,
+    pub balloon_hits: Option<u32>,
+    pub branch: Option<BranchType>,
+}
+
+impl TaikoHitObject {
+    pub fn is_don(&self) -> bool {
+        matches!(self.note.note_type, TaikoHitobjectType::Don | TaikoHitobjectType::BonusDon)
+    }
+
+    pub fn is_kat(&self) -> bool {
+        matches!(self.note.note_type, TaikoHitobjectType::Kat | TaikoHitobjectType::BonusKat)
+    }
+
+    pub fn is_big(&self) -> bool {
+        matches!(
+            self.note.note_type,
+            TaikoHitobjectType::BonusDon | TaikoHitobjectType::BonusKat | TaikoHitobjectType::BonusDrumRoll
+        )
+    }
+
+    pub fn is_roll(&self) -> bool {
+        matches!(
+            self.note.note_type,
+            TaikoHitobjectType::DrumRoll | TaikoHitobjectType::BonusDrumRoll | TaikoHitobjectType::Balloon
+        )
+    }
+
+    // objects outside of a branch section are played on every branch
+    pub fn is_on_branch(&self, branch: BranchType) -> bool {
+        self.branch.is_none_or(|b| b == branch)
+    }
+}
+
+#[wasm_bindgen]
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct TaikoHitObjects {
+    #[wasm_bindgen(skip)]
+    pub objects: Vec<TaikoHitObject>,
+}
+
+impl From<Vec<TaikoHitObject>> for TaikoHitObjects {
+    fn from(objects: Vec<TaikoHitObject>) -> Self {
+        Self { objects }
+    }
+}
+
+impl TaikoHitObjects {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            objects: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn new(objects: Vec<TaikoHitObject>) -> Self {
+        Self { objects }
+    }
+
+    #[inline]
+    pub fn add_hitobject_sorted(&mut self, object: TaikoHitObject) {
+        let len = self.objects.len();
+
+        if len == 0 || object.time >= self.objects[len - 1].time {
+            self.objects.push(object);
+            return;
+        }
+
+        let pos = self.objects.binary_search_by(|obj|
+            obj.time.total_cmp(&object.time)
+        ).unwrap_or_else(|pos| pos);
+
+        self.objects.insert(pos, object);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TaikoHitObject> {
+        self.objects.iter()
+    }
+
+    pub fn iter_branch(&self, branch: BranchType) -> impl Iterator<Item = &TaikoHitObject> {
+        self.objects.iter().filter(move |obj| obj.is_on_branch(branch))
+    }
+
+    pub fn has_branches(&self) -> bool {
+        self.objects.iter().any(|obj| obj.branch.is_some())
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct GenericTaikoChart {
+    #[wasm_bindgen(getter_with_clone)]
+    pub metadata: Metadata,
+    #[wasm_bindgen(getter_with_clone)]
+    pub chartinfo: ChartInfo,
+    #[wasm_bindgen(getter_with_clone)]
+    pub timing_points: TimingPoints,
+    #[wasm_bindgen(getter_with_clone)]
+    pub hitobjects: TaikoHitObjects,
+    #[wasm_bindgen(getter_with_clone)]
+    pub soundbank: Option<SoundBank>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub course: TaikoCourse,
+    #[wasm_bindgen(getter_with_clone)]
+    pub level: u8,
+    #[wasm_bindgen(getter_with_clone)]
+    pub gogo_sections: Vec<GogoSection>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub branches: Vec<BranchSection>,
+}
+
+impl GenericTaikoChart {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        metadata: Metadata,
+        chartinfo: ChartInfo,
+        timing_points: TimingPoints,
+        hitobjects: TaikoHitObjects,
+        soundbank: Option<SoundBank>,
+        course: TaikoCourse,
+        level: u8,
+        gogo_sections: Vec<GogoSection>,
+        branches: Vec<BranchSection>,
+    ) -> Self {
+        Self {
+            metadata,
+            chartinfo,
+            timing_points,
+            hitobjects,
+            soundbank,
+            course,
+            level,
+            gogo_sections,
+            branches,
+        }
+    }
+
+    pub fn is_gogo_at(&self, time: f64) -> bool {
+        self.gogo_sections
+            .iter()
+            .any(|s| time >= s.start_time && time < s.end_time)
+    }
+
+    // the time of the last object, roll ends included
+    pub(crate) fn end_time(&self) -> f64 {
+        self.hitobjects
+            .iter()
+            .map(|obj| obj.note.end_time.unwrap_or(obj.time).max(obj.time))
+            .fold(0.0, f64::max)
+    }
+}
+
+#[wasm_bindgen]
+impl GenericTaikoChart {
+    pub fn timing_map(&self) -> TimingMap {
+        TimingMap::new(&self.timing_points, self.chartinfo.bpm_affects_sv, self.end_time())
+    }
+
+    // visible measure line times up to the last object
+    pub fn barlines(&self) -> Vec<f64> {
+        self.timing_points.barlines(self.end_time())
+    }
+}
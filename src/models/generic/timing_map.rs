@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use crate::wasm_bindgen;
+use crate::models::common::{Beat, TimingChangeType};
+use crate::models::generic::timing_points::{TimingPoint, TimingPoints};
+use crate::utils::rhythm::NOTE_TYPES;
+
+const BEAT_EPSILON: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy)]
+struct TempoSegment {
+    time: f64,
+    beat: f64,
+    bpm: f64,
+    // beats don't move during a stop, the bpm is kept for bpm_at
+    stopped: bool,
+}
+
+impl TempoSegment {
+    #[inline]
+    fn beats_per_ms(&self) -> f64 {
+        if self.stopped {
+            0.0
+        } else {
+            self.bpm / 60_000.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScrollSegment {
+    time: f64,
+    position: f64,
+    speed: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ScrollTrack {
+    svs: Vec<(f64, f32)>,
+    segments: Vec<ScrollSegment>,
+    initial_speed: f64,
+}
+
+// quaver's default groups and osu!'s unnamed one all share the same track
+#[inline]
+pub(crate) fn track_key(group: &str) -> &str {
+    match group {
+        "$Default" | "$Global" => "",
+        _ => group,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeatSnap {
+    // counted from where the grid starts, which is the first bpm point unless it says otherwise
+    pub beat: Beat,
+    pub time: f64,
+    // how far the time that got snapped is from the grid line, positive when late
+    pub error: f64,
+}
+
+impl BeatSnap {
+    // 4 for a 1/4 note, 1 on whole beats
+    #[inline]
+    pub fn divisor(&self) -> u32 {
+        self.beat.fract().denominator()
+    }
+}
+
+// precomputed lookups over the timing points, build it once and query in O(log n)
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct TimingMap {
+    tempo: Vec<TempoSegment>,
+    tracks: HashMap<String, ScrollTrack>,
+    base_track: ScrollTrack,
+    base_bpm: f64,
+    bpm_affects_sv: bool,
+}
+
+#[wasm_bindgen]
+impl TimingMap {
+    // end_time is when the chart's last object ends, the last bpm lasts until then when picking the base bpm
+    #[wasm_bindgen(constructor)]
+    pub fn new(timing_points: &TimingPoints, bpm_affects_sv: bool, end_time: f64) -> Self {
+        let mut points: Vec<_> = timing_points.iter().collect();
+        points.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        let tempo = Self::build_tempo(&points);
+        let base_bpm = Self::dominant_bpm(&tempo, end_time);
+
+        let mut svs: HashMap<String, Vec<(f64, f32)>> = HashMap::new();
+        for point in points.iter().filter(|p| p.change.change_type == TimingChangeType::Sv) {
+            svs.entry(track_key(&point.group).to_string())
+                .or_default()
+                .push((point.time, point.change.value));
+        }
+
+        let mut map = Self {
+            tempo,
+            tracks: HashMap::with_capacity(svs.len()),
+            base_track: ScrollTrack::default(),
+            base_bpm,
+            bpm_affects_sv,
+        };
+
+        map.base_track = map.build_track(Vec::new());
+        for (group, group_svs) in svs {
+            let track = map.build_track(group_svs);
+            map.tracks.insert(group, track);
+        }
+
+        map
+    }
+
+    // continuous beat from the first bpm point, times before it count backwards
+    pub fn beat_at(&self, time: f64) -> f64 {
+        let Some(first) = self.tempo.first() else {
+            return 0.0;
+        };
+
+        match self.tempo.partition_point(|s| s.time <= time) {
+            0 => first.beat + (time - first.time) * first.bpm / 60_000.0,
+            index => {
+                let segment = &self.tempo[index - 1];
+                segment.beat + (time - segment.time) * segment.beats_per_ms()
+            }
+        }
+    }
+
+    // a beat that lands on a stop gets the time the stop starts at
+    pub fn time_at(&self, beat: f64) -> f64 {
+        let Some(first) = self.tempo.first() else {
+            return 0.0;
+        };
+
+        // segment beats are summed up from floats so they can land a hair off
+        let index = self.tempo.partition_point(|s| s.beat < beat - BEAT_EPSILON);
+        if let Some(segment) = self.tempo.get(index).filter(|s| (s.beat - beat).abs() <= BEAT_EPSILON) {
+            return segment.time;
+        }
+
+        let segment = match index {
+            0 => first,
+            index => &self.tempo[index - 1],
+        };
+
+        if segment.bpm <= 0.0 {
+            return segment.time;
+        }
+        segment.time + (beat - segment.beat) * 60_000.0 / segment.bpm
+    }
+
+    // falls back to the first bpm before the first bpm point
+    pub fn bpm_at(&self, time: f64) -> Option<f32> {
+        match self.tempo.partition_point(|s| s.time <= time) {
+            0 => self.tempo.first(),
+            index => self.tempo.get(index - 1),
+        }
+        .map(|s| s.bpm as f32)
+    }
+
+    pub fn ms_per_beat_at(&self, time: f64) -> Option<f32> {
+        self.bpm_at(time).filter(|bpm| *bpm > 0.0).map(|bpm| 60_000.0 / bpm)
+    }
+
+    pub fn sv_at(&self, time: f64, group: &str) -> f32 {
+        let Some(track) = self.tracks.get(track_key(group)) else {
+            return 1.0;
+        };
+
+        match track.svs.partition_point(|(sv_time, _)| *sv_time <= time) {
+            0 => 1.0,
+            index => track.svs[index - 1].1,
+        }
+    }
+
+    // cumulative scroll in milliseconds at 1x, groups without svs only follow the bpm
+    pub fn position_at(&self, time: f64, group: &str) -> f64 {
+        let track = self.tracks.get(track_key(group)).unwrap_or(&self.base_track);
+
+        match track.segments.partition_point(|s| s.time <= time) {
+            0 => time * track.initial_speed,
+            index => {
+                let segment = &track.segments[index - 1];
+                segment.position + (time - segment.time) * segment.speed
+            }
+        }
+    }
+
+    pub fn base_bpm(&self) -> f32 {
+        self.base_bpm as f32
+    }
+
+    pub fn bpm_affects_sv(&self) -> bool {
+        self.bpm_affects_sv
+    }
+}
+
+impl TimingMap {
+    pub fn snapped_beat_at(&self, time: f64) -> Beat {
+        match self.snap(time, &NOTE_TYPES) {
+            Some(snap) => snap.beat,
+            None => Beat::from_f64(self.beat_at(time)),
+        }
+    }
+
+    // the closest line over every divisor's grid, lines shared by several grids count as the coarsest one
+    pub fn snap(&self, time: f64, divisors: &[u32]) -> Option<BeatSnap> {
+        self.snap_from(time, 0.0, divisors)
+    }
+
+    // same as snap but the grid starts over on every bpm change, which is how osu! and editors count snaps
+    pub fn snap_from_bpm(&self, time: f64, divisors: &[u32]) -> Option<BeatSnap> {
+        let origin = match self.tempo.partition_point(|s| s.time <= time) {
+            0 => self.tempo.first()?.beat,
+            index => self.tempo[index - 1].beat,
+        };
+        self.snap_from(time, origin, divisors)
+    }
+
+    fn snap_from(&self, time: f64, origin: f64, divisors: &[u32]) -> Option<BeatSnap> {
+        let beat = self.beat_at(time) - origin;
+        if self.tempo.is_empty() || !beat.is_finite() {
+            return None;
+        }
+
+        let mut divisors: Vec<u32> = divisors.iter().copied().filter(|d| *d > 0).collect();
+        divisors.sort_unstable();
+
+        let mut closest: Option<BeatSnap> = None;
+        for divisor in divisors {
+            let snapped = Beat::from_row((beat * divisor as f64).round() as i64, divisor);
+            let snapped_time = self.time_at(origin + snapped.as_f64());
+            let snap = BeatSnap { beat: snapped, time: snapped_time, error: time - snapped_time };
+
+            // finer grids only win when they're actually closer, not by float noise
+            if closest.is_none_or(|closest| snap.error.abs() < closest.error.abs() - BEAT_EPSILON) {
+                closest = Some(snap);
+            }
+        }
+
+        closest
+    }
+
+    pub fn time_at_beat(&self, beat: Beat) -> f64 {
+        self.time_at(beat.as_f64())
+    }
+
+    fn build_tempo(points: &[&TimingPoint]) -> Vec<TempoSegment> {
+        let mut tempo: Vec<TempoSegment> = Vec::new();
+
+        for point in points {
+            let (bpm, stop) = match point.change.change_type {
+                TimingChangeType::Bpm if point.change.value > 0.0 => (point.change.value as f64, None),
+                TimingChangeType::Stop => match tempo.last() {
+                    Some(last) => (last.bpm, Some(point.change.value as f64)),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            // a bpm change on the time a stop starts only changes the tempo after the stop
+            let len = tempo.len();
+            if stop.is_none() && len >= 2 && tempo[len - 2].stopped && tempo[len - 2].time == point.time {
+                tempo[len - 2].bpm = bpm;
+                tempo[len - 1].bpm = bpm;
+                continue;
+            }
+
+            let beat = tempo.last().map_or(0.0, |last| last.beat + (point.time - last.time) * last.beats_per_ms());
+
+            // a later point at the same time replaces the earlier one
+            if tempo.last().is_some_and(|last| last.time == point.time && !last.stopped && stop.is_none()) {
+                tempo.pop();
+            }
+
+            match stop {
+                Some(duration) => {
+                    tempo.push(TempoSegment { time: point.time, beat, bpm, stopped: true });
+                    tempo.push(TempoSegment { time: point.time + duration.max(0.0), beat, bpm, stopped: false });
+                }
+                None => tempo.push(TempoSegment { time: point.time, beat, bpm, stopped: false }),
+            }
+        }
+
+        tempo
+    }
+
+    // the bpm that lasts the longest up to the end of the chart, same as what osu! normalizes sv against
+    fn dominant_bpm(tempo: &[TempoSegment], end_time: f64) -> f64 {
+        let mut durations: Vec<(f64, f64)> = Vec::new();
+
+        for (index, segment) in tempo.iter().enumerate() {
+            let next_time = tempo.get(index + 1).map_or(end_time, |next| next.time).min(end_time);
+            let duration = (next_time - segment.time).max(0.0);
+            match durations.iter_mut().find(|(bpm, _)| *bpm == segment.bpm) {
+                Some((_, total)) => *total += duration,
+                None => durations.push((segment.bpm, duration)),
+            }
+        }
+
+        durations
+            .iter()
+            .fold(None, |best: Option<(f64, f64)>, &(bpm, total)| match best {
+                Some((_, best_total)) if best_total >= total => best,
+                _ => Some((bpm, total)),
+            })
+            .map_or(0.0, |(bpm, _)| bpm)
+    }
+
+    fn tempo_speed(&self, segment: &TempoSegment) -> f64 {
+        if segment.stopped {
+            0.0
+        } else if self.bpm_affects_sv && self.base_bpm > 0.0 {
+            segment.bpm / self.base_bpm
+        } else {
+            1.0
+        }
+    }
+
+    fn build_track(&self, svs: Vec<(f64, f32)>) -> ScrollTrack {
+        let mut tempo_speed = self.tempo.first().map_or(1.0, |s| self.tempo_speed(s));
+        let mut sv = 1.0;
+        let initial_speed = tempo_speed;
+
+        let mut segments: Vec<ScrollSegment> = Vec::with_capacity(self.tempo.len() + svs.len());
+        let (mut tempo_index, mut sv_index) = (0, 0);
+
+        while tempo_index < self.tempo.len() || sv_index < svs.len() {
+            let tempo_time = self.tempo.get(tempo_index).map_or(f64::MAX, |s| s.time);
+            let sv_time = svs.get(sv_index).map_or(f64::MAX, |(time, _)| *time);
+            let time = tempo_time.min(sv_time);
+
+            let position = match segments.last() {
+                Some(last) => last.position + (time - last.time) * last.speed,
+                None => time * initial_speed,
+            };
+
+            if tempo_time <= sv_time {
+                tempo_speed = self.tempo_speed(&self.tempo[tempo_index]);
+                tempo_index += 1;
+            } else {
+                sv = svs[sv_index].1 as f64;
+                sv_index += 1;
+            }
+
+            let segment = ScrollSegment { time, position, speed: sv * tempo_speed };
+            match segments.last_mut() {
+                Some(last) if last.time == time => *last = segment,
+                _ => segments.push(segment),
+            }
+        }
+
+        ScrollTrack { svs, segments, initial_speed }
+    }
+}
@@ -1,5 +1,8 @@
+use std::fmt;
+use std::str::FromStr;
+
 use crate::wasm_bindgen;
-use crate::models::common::TimingChangeType;
+use crate::models::common::{Beat, TimingChangeType};
 
 #[derive(Debug, Clone, Copy)]
 pub struct TimingChange {
@@ -7,15 +10,178 @@ pub struct TimingChange {
     pub value: f32,
 }
 
+// hitsound defaults for the objects after a point, only osu! has these
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleContext {
+    pub sample_set: u8,
+    pub sample_index: u32,
+    pub volume: u8,
+}
+
+// hide_barlines hides every barline until the next bpm or signature change, like fluXis' hide-lines
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimingEffects {
+    pub kiai: bool,
+    pub omit_first_barline: bool,
+    pub hide_barlines: bool,
+}
+
+impl TimingEffects {
+    pub fn from_osu_bits(effects: i32) -> Self {
+        Self {
+            kiai: effects & 1 != 0,
+            omit_first_barline: effects & 8 != 0,
+            hide_barlines: false,
+        }
+    }
+
+    // osu! can only omit the first barline, the writer adds uninherited points for the rest of a hidden section
+    pub fn to_osu_bits(&self) -> i32 {
+        let omit_first_barline = self.omit_first_barline || self.hide_barlines;
+        (self.kiai as i32) | ((omit_first_barline as i32) << 3)
+    }
+}
+
+// the span between two bpm or signature changes, measures restart at its start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BarlineSection {
+    pub start: f64,
+    pub end: f64,
+    pub bpm: f32,
+    pub beats_per_measure: f32,
+    pub omit_first: bool,
+    pub hidden: bool,
+}
+
+impl BarlineSection {
+    pub fn measure_length(&self) -> f64 {
+        self.beats_per_measure as f64 * 60_000.0 / self.bpm as f64
+    }
+
+    // every measure start in the section, hidden or not
+    pub fn measure_times(&self) -> impl Iterator<Item = f64> + '_ {
+        // a measure that starts within a millisecond of the next section belongs to it
+        const LENIENCY: f64 = 1.0;
+        let length = self.measure_length();
+
+        (0..)
+            .map(move |measure| self.start + measure as f64 * length)
+            .take_while(move |time| length > 0.0 && self.end - time > LENIENCY)
+    }
+
+    pub fn visible_times(&self) -> impl Iterator<Item = f64> + '_ {
+        let skip = if self.omit_first { 1 } else { 0 };
+        self.measure_times().skip(skip).filter(|_| !self.hidden)
+    }
+}
+
+// numerator notes of length 1/denominator per measure, a beat is always a quarter note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl TimeSignature {
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        Self { numerator, denominator }
+    }
+
+    pub fn beats_per_measure(&self) -> f32 {
+        4.0 * self.numerator as f32 / self.denominator.max(1) as f32
+    }
+
+    // picks the smallest denominator that can hold the measure, 3.5 beats becomes 7/8
+    pub fn from_beats_per_measure(beats: f32) -> Self {
+        for denominator in [4, 8, 16, 32, 64] {
+            let numerator = beats * denominator as f32 / 4.0;
+            if (numerator - numerator.round()).abs() < 1e-3 {
+                return Self::new((numerator.round() as u32).max(1), denominator);
+            }
+        }
+
+        Self::new(((beats * 12.0).round() as u32).max(1), 48)
+    }
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        Self::new(4, 4)
+    }
+}
+
+impl fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl FromStr for TimeSignature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (numerator, denominator) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid time signature: {}", s))?;
+
+        let numerator = numerator.trim().parse::<u32>()
+            .map_err(|_| format!("Invalid time signature numerator: {}", numerator))?;
+        let denominator = denominator.trim().parse::<u32>()
+            .map_err(|_| format!("Invalid time signature denominator: {}", denominator))?;
+
+        if numerator == 0 || denominator == 0 {
+            return Err(format!("Invalid time signature: {}", s));
+        }
+
+        Ok(Self::new(numerator, denominator))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TimingPoint {
-    pub time: i32,
-    pub beat: f32,
+    pub time: f64,
+    pub beat: Beat,
     pub group: String,
     pub change: TimingChange,
+    pub sample: Option<SampleContext>,
+    pub effects: Option<TimingEffects>,
+    pub signature: Option<TimeSignature>,
 }
 
 impl TimingPoint {
+    pub fn new(time: f64, beat: Beat, group: String, change: TimingChange) -> Self {
+        Self {
+            time,
+            beat,
+            group,
+            change,
+            sample: None,
+            effects: None,
+            signature: None,
+        }
+    }
+
+    // the change value holds the measure length in beats, the signature keeps the exact fraction
+    pub fn time_signature(time: f64, beat: Beat, signature: TimeSignature) -> Self {
+        let change = TimingChange {
+            change_type: TimingChangeType::TimeSignature,
+            value: signature.beats_per_measure(),
+        };
+
+        Self {
+            signature: Some(signature),
+            ..Self::new(time, beat, String::new(), change)
+        }
+    }
+
+    pub fn with_sample(self, sample: SampleContext) -> Self {
+        Self { sample: Some(sample), ..self }
+    }
+
+    pub fn with_effects(self, effects: TimingEffects) -> Self {
+        Self { effects: Some(effects), ..self }
+    }
+
     pub fn has_default_group_qua(&self) -> bool {
         return self.group.is_empty() || self.group == "$Default" || self.group == "$Global";
     }
@@ -47,13 +213,18 @@ impl TimingPoints {
         Self { points }
     }
 
-    pub fn add(&mut self, time: i32, beat: f32, group: String, change: TimingChange) {
-        self.points.push(TimingPoint {
-            time,
-            beat,
-            group: group,
-            change,
-        });
+    pub fn add(&mut self, time: f64, beat: Beat, group: String, change: TimingChange) {
+        self.points.push(TimingPoint::new(time, beat, group, change));
+    }
+
+    pub fn push(&mut self, point: TimingPoint) {
+        self.points.push(point);
+    }
+
+    // keeps the points sorted, the signature goes after anything else at the same time
+    pub fn insert_time_signature(&mut self, time: f64, beat: Beat, signature: TimeSignature) {
+        let index = self.points.partition_point(|p| p.time <= time);
+        self.points.insert(index, TimingPoint::time_signature(time, beat, signature));
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &TimingPoint> {
@@ -76,6 +247,12 @@ impl TimingPoints {
             .filter(|p| matches!(p.change.change_type, TimingChangeType::Sv))
     }
 
+    pub fn time_signature_changes(&self) -> impl Iterator<Item = &TimingPoint> + '_ {
+        self.points
+            .iter()
+            .filter(|p| matches!(p.change.change_type, TimingChangeType::TimeSignature))
+    }
+
     pub fn is_bpms_empty(&self) -> bool {
         !self.points
             .iter()
@@ -96,7 +273,7 @@ impl TimingPoints {
             .collect()
     }
 
-    pub fn bpms_times(&self) -> Vec<i32> {
+    pub fn bpms_times(&self) -> Vec<f64> {
         self.points
             .iter()
             .filter(|p| matches!(p.change.change_type, TimingChangeType::Bpm))
@@ -111,4 +288,144 @@ impl TimingPoints {
             .map(|p| p.change.value)
             .collect()
     }
+
+    // falls back to the first bpm before the first bpm point
+    pub fn bpm_at(&self, time: f64) -> Option<f32> {
+        self.bpm_changes()
+            .take_while(|p| p.time <= time)
+            .last()
+            .or_else(|| self.bpm_changes().next())
+            .map(|p| p.change.value)
+    }
+
+    // formats that keep the signature on their bpm points need an extra one where only the signature changes
+    pub fn bpm_for_signature_change(&self, time: f64) -> Option<f32> {
+        if self.bpm_changes().any(|p| p.time == time) {
+            return None;
+        }
+
+        self.bpm_at(time)
+    }
+
+    pub fn signature_at(&self, time: f64) -> TimeSignature {
+        self.time_signature_changes()
+            .take_while(|p| p.time <= time)
+            .filter_map(|p| p.signature)
+            .last()
+            .unwrap_or_default()
+    }
+
+    pub fn signature_at_beat(&self, beat: Beat) -> TimeSignature {
+        self.time_signature_changes()
+            .take_while(|p| p.beat <= beat)
+            .filter_map(|p| p.signature)
+            .last()
+            .unwrap_or_default()
+    }
+
+    // the last point at or before the time that has hitsound context
+    pub fn sample_at(&self, time: f64) -> Option<SampleContext> {
+        self.points
+            .iter()
+            .take_while(|p| p.time <= time)
+            .filter_map(|p| p.sample)
+            .last()
+    }
+
+    pub fn is_kiai_at(&self, time: f64) -> bool {
+        self.points
+            .iter()
+            .take_while(|p| p.time <= time)
+            .filter_map(|p| p.effects)
+            .last()
+            .is_some_and(|effects| effects.kiai)
+    }
+
+    pub fn kiai_sections(&self) -> Vec<(f64, f64)> {
+        let mut sections = Vec::new();
+        let mut kiai_start = None;
+
+        for point in &self.points {
+            let Some(effects) = point.effects else {
+                continue;
+            };
+
+            match (effects.kiai, kiai_start) {
+                (true, None) => kiai_start = Some(point.time),
+                (false, Some(start)) => {
+                    sections.push((start, point.time));
+                    kiai_start = None;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(start) = kiai_start {
+            sections.push((start, f64::MAX));
+        }
+
+        sections
+    }
+
+    // measures restart on every bpm or signature change, same as osu! and fluXis draw them
+    pub fn barline_sections(&self, end_time: f64) -> Vec<BarlineSection> {
+        let mut changes: Vec<&TimingPoint> = self.points
+            .iter()
+            .filter(|p| match p.change.change_type {
+                TimingChangeType::Bpm => p.change.value > 0.0,
+                TimingChangeType::TimeSignature => true,
+                _ => false,
+            })
+            .collect();
+        changes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        let mut sections: Vec<BarlineSection> = Vec::new();
+        let mut bpm = None;
+        let mut beats_per_measure = 4.0;
+        let mut hidden = false;
+
+        // a bpm and a signature on the same time only start one section
+        for same_time in changes.chunk_by(|a, b| a.time == b.time) {
+            let time = same_time[0].time;
+            let mut omit_first = false;
+
+            for point in same_time {
+                match point.change.change_type {
+                    TimingChangeType::Bpm => {
+                        let effects = point.effects.unwrap_or_default();
+                        bpm = Some(point.change.value);
+                        hidden = effects.hide_barlines;
+                        omit_first |= effects.omit_first_barline;
+                    }
+                    _ => beats_per_measure = point.signature.map_or(point.change.value, |s| s.beats_per_measure()),
+                }
+            }
+
+            let Some(bpm) = bpm else {
+                continue;
+            };
+
+            if let Some(last) = sections.last_mut() {
+                last.end = time;
+            }
+
+            sections.push(BarlineSection {
+                start: time,
+                end: end_time.max(time),
+                bpm,
+                beats_per_measure,
+                omit_first,
+                hidden,
+            });
+        }
+
+        sections
+    }
+
+    pub fn barlines(&self, end_time: f64) -> Vec<f64> {
+        self.barline_sections(end_time)
+            .iter()
+            .flat_map(|section| section.visible_times().collect::<Vec<_>>())
+            .collect()
+    }
 }
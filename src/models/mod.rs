@@ -1,3 +1,4 @@
+pub mod beat;
 pub mod common;
 pub mod timeline;
 pub mod generic;
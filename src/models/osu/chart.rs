@@ -23,25 +23,48 @@ impl Display for OsuMode {
     }
 }
 
+pub const LATEST_FORMAT_VERSION: u8 = 14;
+
+// files older than v5 are played 24ms later than their times say
+pub const EARLY_VERSION_TIMING_OFFSET: f32 = 24.0;
+
+pub fn format_version_from_str(raw_chart: &str) -> Option<u8> {
+    raw_chart
+        .lines()
+        .map(|line| line.trim_start_matches('\u{feff}').trim())
+        .find(|line| !line.is_empty())?
+        .strip_prefix("osu file format v")?
+        .parse::<u8>()
+        .ok()
+}
+
+pub fn timing_offset_for_version(version: u8) -> f32 {
+    if version < 5 { EARLY_VERSION_TIMING_OFFSET } else { 0.0 }
+}
+
 pub struct OsuFile {
+    pub version: u8,
     pub general: general::General,
     pub editor: Option<editor::Editor>,
     pub metadata: metadata::Metadata,
     pub difficulty: difficulty::Difficulty,
     pub events: events::Events,
     pub timing_points: timing_points::TimingPoints,
+    pub colours: Option<colours::Colours>,
     pub hitobjects: hitobjects::HitObjects,
 }
 
 impl Default for OsuFile {
     fn default() -> Self {
         OsuFile {
+            version: LATEST_FORMAT_VERSION,
             general: Default::default(),
             editor: None,
             metadata: Default::default(),
             difficulty: Default::default(),
             events: Default::default(),
             timing_points: Default::default(),
+            colours: None,
             hitobjects: Default::default(),
         }
     }
@@ -51,12 +74,48 @@ impl OsuFile {
     pub fn from_str(str: &str) -> Result<Self, Box<dyn std::error::Error>> {
         from_osu(str)
     }
+
+    // changes the version the file is written as, times are moved when the early version offset changes
+    pub fn set_format_version(&mut self, version: u8) {
+        let offset = timing_offset_for_version(self.version) - timing_offset_for_version(version);
+        if offset != 0.0 {
+            self.shift_times(offset);
+        }
+        self.version = version;
+    }
+
+    pub fn shift_times(&mut self, offset: f32) {
+        let offset_i32 = offset.round() as i32;
+
+        if self.general.preview_time >= 0 {
+            self.general.preview_time += offset_i32;
+        }
+
+        for timing_point in &mut self.timing_points.timing_points {
+            timing_point.time += offset;
+        }
+
+        for hit_object in self.hitobjects.iter_mut() {
+            hit_object.shift_time(offset);
+        }
+
+        self.events.shift_times(offset_i32);
+    }
 }
 
 impl OsuFile {
+    // the section is left out when the chart has no colours
+    fn push_colours(&self, result: &mut String) {
+        if let Some(ref colours) = self.colours {
+            result.push_str("[Colours]\n");
+            result.push_str(&colours.to_str());
+            result.push_str("\n\n");
+        }
+    }
+
     pub fn to_str_taiko(&self) -> String {
         let mut result = String::new();
-        result.push_str("osu file format v14\n\n");
+        result.push_str(&format!("osu file format v{}\n\n", self.version));
         
         result.push_str("[General]\n");
         result.push_str(&self.general.to_str());
@@ -81,9 +140,11 @@ impl OsuFile {
         result.push_str("\n");
         
         result.push_str("[TimingPoints]\n");
-        result.push_str(&self.timing_points.to_str());
+        result.push_str(&self.timing_points.to_str_version(self.version));
         result.push_str("\n\n");
         
+        self.push_colours(&mut result);
+        
         result.push_str("[HitObjects]\n");
         result.push_str(&self.hitobjects.to_str_taiko());
         
@@ -92,7 +153,7 @@ impl OsuFile {
 
     pub fn to_str_catch(&self) -> String {
         let mut result = String::new();
-        result.push_str("osu file format v14\n\n");
+        result.push_str(&format!("osu file format v{}\n\n", self.version));
         
         result.push_str("[General]\n");
         result.push_str(&self.general.to_str());
@@ -117,9 +178,11 @@ impl OsuFile {
         result.push_str("\n");
         
         result.push_str("[TimingPoints]\n");
-        result.push_str(&self.timing_points.to_str());
+        result.push_str(&self.timing_points.to_str_version(self.version));
         result.push_str("\n\n");
         
+        self.push_colours(&mut result);
+        
         result.push_str("[HitObjects]\n");
         result.push_str(&self.hitobjects.to_str_catch());
         
@@ -128,7 +191,7 @@ impl OsuFile {
 
     pub fn to_str_mania(&self, soundbank: &mut SoundBank) -> String {
         let mut result = String::new();
-        result.push_str("osu file format v14\n\n");
+        result.push_str(&format!("osu file format v{}\n\n", self.version));
         
         result.push_str("[General]\n");
         result.push_str(&self.general.to_str());
@@ -153,9 +216,11 @@ impl OsuFile {
         result.push_str("\n");
         
         result.push_str("[TimingPoints]\n");
-        result.push_str(&self.timing_points.to_str());
+        result.push_str(&self.timing_points.to_str_version(self.version));
         result.push_str("\n\n");
         
+        self.push_colours(&mut result);
+        
         result.push_str("[HitObjects]\n");
         result.push_str(&self.hitobjects.to_str_mania(soundbank));
         
@@ -164,7 +229,7 @@ impl OsuFile {
 
     pub fn to_str_mania_no_soundbank(&self) -> String {
         let mut result = String::new();
-        result.push_str("osu file format v14\n\n");
+        result.push_str(&format!("osu file format v{}\n\n", self.version));
         
         result.push_str("[General]\n");
         result.push_str(&self.general.to_str());
@@ -189,9 +254,11 @@ impl OsuFile {
         result.push_str("\n");
         
         result.push_str("[TimingPoints]\n");
-        result.push_str(&self.timing_points.to_str());
+        result.push_str(&self.timing_points.to_str_version(self.version));
         result.push_str("\n\n");
         
+        self.push_colours(&mut result);
+        
         result.push_str("[HitObjects]\n");
         result.push_str(&self.hitobjects.to_str_no_soundbank());
         
@@ -204,7 +271,7 @@ impl OsuFile {
 
     pub fn to_str(&self) -> String {
         let mut result = String::new();
-        result.push_str("osu file format v14\n\n");
+        result.push_str(&format!("osu file format v{}\n\n", self.version));
         
         result.push_str("[General]\n");
         result.push_str(&self.general.to_str());
@@ -229,9 +296,11 @@ impl OsuFile {
         result.push_str("\n");
         
         result.push_str("[TimingPoints]\n");
-        result.push_str(&self.timing_points.to_str());
+        result.push_str(&self.timing_points.to_str_version(self.version));
         result.push_str("\n\n");
         
+        self.push_colours(&mut result);
+        
         result.push_str("[HitObjects]\n");
         result.push_str(&self.hitobjects.to_str());
         
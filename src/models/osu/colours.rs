@@ -0,0 +1,159 @@
+use std::str::FromStr;
+use std::collections::BTreeMap;
+
+use crate::models::generic::{self, ChartColors};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colour {
+    pub red: u8,
+
+    pub green: u8,
+
+    pub blue: u8,
+}
+
+impl FromStr for Colour {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(',').map(|p| p.trim()).collect();
+
+        if parts.len() < 3 {
+            return Err(format!("Expected at least 3 comma-separated values, found {}", parts.len()));
+        }
+
+        let red = parts[0].parse::<u8>()
+            .map_err(|_| format!("Invalid red value: {}", parts[0]))?;
+
+        let green = parts[1].parse::<u8>()
+            .map_err(|_| format!("Invalid green value: {}", parts[1]))?;
+
+        let blue = parts[2].parse::<u8>()
+            .map_err(|_| format!("Invalid blue value: {}", parts[2]))?;
+
+        Ok(Colour { red, green, blue })
+    }
+}
+
+impl Colour {
+    pub fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    pub fn to_generic(&self) -> generic::Color {
+        generic::Color::new(self.red, self.green, self.blue)
+    }
+
+    pub fn from_generic(color: &generic::Color) -> Self {
+        Self::new(color.red, color.green, color.blue)
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("{},{},{}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Colours {
+    pub combos: Vec<Colour>,
+
+    pub slider_track_override: Option<Colour>,
+
+    pub slider_border: Option<Colour>,
+}
+
+impl FromStr for Colours {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut colours = Colours::default();
+        let mut combos = BTreeMap::new();
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim();
+
+            // osu! skips lines it can't read instead of failing the whole beatmap
+            let Ok(colour) = Colour::from_str(value) else {
+                continue;
+            };
+
+            if let Some(index) = key.strip_prefix("Combo") {
+                if let Ok(index) = index.parse::<u32>() {
+                    combos.insert(index, colour);
+                }
+                continue;
+            }
+
+            match key {
+                "SliderTrackOverride" => colours.slider_track_override = Some(colour),
+                "SliderBorder" => colours.slider_border = Some(colour),
+                _ => {}
+            }
+        }
+
+        colours.combos = combos.into_values().collect();
+
+        Ok(colours)
+    }
+}
+
+impl Colours {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_combo(&mut self, colour: Colour) {
+        self.combos.push(colour);
+    }
+
+    pub fn combo(&self, index: usize) -> Option<&Colour> {
+        self.combos.get(index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty() && self.slider_track_override.is_none() && self.slider_border.is_none()
+    }
+
+    pub fn to_generic(&self) -> ChartColors {
+        ChartColors {
+            combo_colors: self.combos.iter().map(|c| c.to_generic()).collect(),
+            slider_track: self.slider_track_override.map(|c| c.to_generic()),
+            slider_border: self.slider_border.map(|c| c.to_generic()),
+            ..ChartColors::default()
+        }
+    }
+
+    pub fn from_generic(colors: &ChartColors) -> Self {
+        Self {
+            combos: colors.combo_colors.iter().map(Colour::from_generic).collect(),
+            slider_track_override: colors.slider_track.as_ref().map(Colour::from_generic),
+            slider_border: colors.slider_border.as_ref().map(Colour::from_generic),
+        }
+    }
+
+    pub fn to_str(&self) -> String {
+        let mut lines = Vec::new();
+
+        for (i, combo) in self.combos.iter().enumerate() {
+            lines.push(format!("Combo{} : {}", i + 1, combo.to_str()));
+        }
+
+        if let Some(ref track) = self.slider_track_override {
+            lines.push(format!("SliderTrackOverride : {}", track.to_str()));
+        }
+
+        if let Some(ref border) = self.slider_border {
+            lines.push(format!("SliderBorder : {}", border.to_str()));
+        }
+
+        lines.join("\n")
+    }
+}
@@ -64,9 +64,12 @@ impl FromStr for Difficulty {
                 .map_err(|_| format!("Invalid OverallDifficulty value: {}", value))?;
         }
         
+        // files older than v8 don't have ApproachRate, osu! uses OverallDifficulty instead
         if let Some(value) = key_values.get("ApproachRate") {
             difficulty.approach_rate = value.parse::<f32>()
                 .map_err(|_| format!("Invalid ApproachRate value: {}", value))?;
+        } else {
+            difficulty.approach_rate = difficulty.overall_difficulty;
         }
         
         if let Some(value) = key_values.get("SliderMultiplier") {
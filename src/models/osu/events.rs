@@ -1,5 +1,7 @@
 use std::str::FromStr;
 
+use crate::models::osu::storyboard::{apply_variables, Layer, Storyboard, StoryboardReader};
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Event {
     pub event_type: String,
@@ -57,6 +59,8 @@ pub struct Events {
     
     pub samples: Vec<Sample>,
     
+    pub storyboard: Storyboard,
+    
     pub raw_events: String,
 }
 
@@ -67,6 +71,7 @@ impl Default for Events {
             video: None,
             breaks: Vec::new(),
             samples: Vec::new(),
+            storyboard: Storyboard::default(),
             raw_events: String::new(),
         }
     }
@@ -103,15 +108,29 @@ impl FromStr for Events {
     type Err = String;
     
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Events::from_str_with_variables(s, &[])
+    }
+}
+
+impl Events {
+    // variables come from the [Variables] section and are only used by storyboards
+    pub fn from_str_with_variables(s: &str, variables: &[(String, String)]) -> Result<Self, String> {
         let mut events_section = Events::default();
         events_section.raw_events = s.to_string();
+        let mut storyboard_reader = StoryboardReader::default();
         
         for line in s.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with("//") {
+            let line = apply_variables(line.trim_end(), variables);
+            if line.trim().is_empty() || line.trim_start().starts_with("//") {
                 continue;
             }
             
+            // osu! skips storyboard lines it can't read instead of failing the whole beatmap
+            if storyboard_reader.read_line(&line).unwrap_or(true) {
+                continue;
+            }
+            
+            let line = line.trim();
             let event = Event::from_str(line)?;
             
             match event.event_type.as_str() {
@@ -177,7 +196,7 @@ impl FromStr for Events {
                         });
                     }
                 }
-                "Sample" => {
+                "Sample" | "5" => {
                     if event.event_params.len() >= 2 {
                         let layer = event.event_params[0].parse::<i32>()
                             .map_err(|_| format!("Invalid sample layer: {}", event.event_params[0]))?;
@@ -204,6 +223,8 @@ impl FromStr for Events {
             }
         }
         
+        events_section.storyboard = storyboard_reader.finish();
+        
         Ok(events_section)
     }
 }
@@ -230,7 +251,7 @@ impl Event {
     }
     
     pub fn is_sample(&self) -> bool {
-        self.event_type == "Sample"
+        self.event_type == "Sample" || self.event_type == "5"
     }
     
     pub fn to_str(&self) -> String {
@@ -368,6 +389,35 @@ impl Events {
         self.samples.iter().filter(|s| s.layer == layer).collect()
     }
     
+    pub fn shift_times(&mut self, offset: i32) {
+        if let Some(ref mut video) = self.video {
+            video.start_time += offset;
+        }
+        
+        for break_event in &mut self.breaks {
+            break_event.start_time += offset;
+            break_event.end_time += offset;
+        }
+        
+        for sample in &mut self.samples {
+            sample.start_time += offset;
+        }
+        
+        self.storyboard.shift_times(offset);
+    }
+    
+    pub fn has_storyboard(&self) -> bool {
+        !self.storyboard.objects.is_empty()
+    }
+    
+    fn push_storyboard_layer(&self, result: &mut String, layer: Layer) {
+        let objects = self.storyboard.layer_to_str(layer);
+        if !objects.is_empty() {
+            result.push_str(&objects);
+            result.push('\n');
+        }
+    }
+    
     pub fn to_str(&self) -> String {
         let mut result = String::new();
         
@@ -390,30 +440,35 @@ impl Events {
         }
         
         result.push_str("//Storyboard Layer 0 (Background)\n");
+        self.push_storyboard_layer(&mut result, Layer::Background);
         for sample in self.samples_by_layer(0) {
             result.push_str(&sample.to_str());
             result.push('\n');
         }
         
         result.push_str("//Storyboard Layer 1 (Fail)\n");
+        self.push_storyboard_layer(&mut result, Layer::Fail);
         for sample in self.samples_by_layer(1) {
             result.push_str(&sample.to_str());
             result.push('\n');
         }
         
         result.push_str("//Storyboard Layer 2 (Pass)\n");
+        self.push_storyboard_layer(&mut result, Layer::Pass);
         for sample in self.samples_by_layer(2) {
             result.push_str(&sample.to_str());
             result.push('\n');
         }
         
         result.push_str("//Storyboard Layer 3 (Foreground)\n");
+        self.push_storyboard_layer(&mut result, Layer::Foreground);
         for sample in self.samples_by_layer(3) {
             result.push_str(&sample.to_str());
             result.push('\n');
         }
         
         result.push_str("//Storyboard Layer 4 (Overlay)\n");
+        self.push_storyboard_layer(&mut result, Layer::Overlay);
         for sample in &self.samples {
             if sample.layer >= 4 {
                 result.push_str(&sample.to_str());
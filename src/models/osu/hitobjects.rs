@@ -1,7 +1,9 @@
 use std::str::FromStr;
 use std::ops::{Index, IndexMut};
 use std::slice::SliceIndex;
-use crate::models::osu::sound::HitSample;
+use crate::models::osu::sound::{self as osu_sound, HitSample, ResolvedSample, SampleSet};
+use crate::models::osu::slider_path::{CurveType, SliderPath};
+use crate::models::osu::{Difficulty, TimingPoint, TimingPoints};
 use crate::models::common::{self, Key};
 use crate::models::generic::sound::{self, SoundBank};
 use crate::osu::OsuMode;
@@ -84,16 +86,17 @@ impl HitObject {
                     }
                 }
             } else {
-                let last_part = parts[parts.len() - 1];
-                if last_part.contains(':') {
-                    hit_sample = HitSample::from_str(last_part)?;
-                    object_params = parts[5..parts.len()-1].iter()
-                        .map(|s| s.to_string())
-                        .collect();
-                } else {
-                    object_params = parts[5..].iter()
-                        .map(|s| s.to_string())
-                        .collect();
+                // the hit sample comes after a fixed number of params, older files often leave it out
+                // and slider edge sets have colons too so it can't be found by looking for one
+                let param_count = Self::param_count(object_type);
+                let params_end = (5 + param_count).min(parts.len());
+                
+                object_params = parts[5..params_end].iter()
+                    .map(|s| s.to_string())
+                    .collect();
+                
+                if let Some(hit_sample_part) = parts.get(5 + param_count) {
+                    hit_sample = HitSample::from_str(hit_sample_part)?;
                 }
             }
         }
@@ -121,6 +124,24 @@ impl HitObject {
         }
     }
     
+    // params between hitSound and hitSample, holds keep the hit sample inside their only param
+    fn param_count(object_type: u8) -> usize {
+        if object_type & 2 != 0 {
+            5
+        } else if object_type & 8 != 0 {
+            1
+        } else {
+            0
+        }
+    }
+
+    // writing the hit sample after missing params would shift it into their place
+    fn push_hit_sample(&self, parts: &mut Vec<String>, object_type: u8) {
+        if parts.len() >= 5 + Self::param_count(object_type) {
+            parts.push(self.hit_sample.to_str());
+        }
+    }
+    
     pub fn is_hit_circle(&self) -> bool {
         (self.object_type & 1) != 0
     }
@@ -165,6 +186,7 @@ impl HitObject {
         (self.object_type >> 4) & 7
     }
     
+    // spinners and holds store their end time, sliders need the timing points so use slider_end_time for them
     pub fn end_time(&self) -> Option<i32> {
         if self.is_spinner() || self.is_hold() {
             if !self.object_params.is_empty() {
@@ -174,6 +196,13 @@ impl HitObject {
         None
     }
     
+    pub fn shift_time(&mut self, offset: f32) {
+        self.time += offset;
+        if let Some(end_time) = self.end_time() {
+            self.object_params[0] = (end_time + offset.round() as i32).to_string();
+        }
+    }
+    
     pub fn slider_params(&self) -> Option<(String, Vec<String>, i32, f32)> {
         if self.is_slider() && self.object_params.len() >= 3 {
             let curve_info = &self.object_params[0];
@@ -197,6 +226,39 @@ impl HitObject {
         }
     }
     
+    pub fn slider_path(&self) -> Option<SliderPath> {
+        let (curve_type, curve_points, _, length) = self.slider_params()?;
+        // osu! falls back to catmull for unknown curve types
+        let curve_type = CurveType::from_str(&curve_type).unwrap_or(CurveType::Catmull);
+
+        let mut control_points = vec![(self.x as f32, self.y as f32)];
+        control_points.extend(curve_points.iter().filter_map(|point| {
+            let (x, y) = point.split_once(':')?;
+            Some((x.trim().parse::<f32>().ok()?, y.trim().parse::<f32>().ok()?))
+        }));
+
+        let expected_distance = if length > 0.0 { Some(length as f64) } else { None };
+        Some(SliderPath::new(curve_type, control_points, expected_distance))
+    }
+
+    // osu!pixels per millisecond
+    pub fn slider_velocity(&self, timing_points: &TimingPoints, difficulty: &Difficulty) -> f32 {
+        let beat_length = timing_points.beat_length_at(self.time);
+        let sv = timing_points.slider_velocity_at(self.time).clamp(0.1, 10.0);
+        difficulty.slider_multiplier * 100.0 * sv / beat_length
+    }
+
+    pub fn slider_span_duration(&self, timing_points: &TimingPoints, difficulty: &Difficulty) -> Option<f64> {
+        let path = self.slider_path()?;
+        Some(path.distance() / self.slider_velocity(timing_points, difficulty) as f64)
+    }
+
+    pub fn slider_end_time(&self, timing_points: &TimingPoints, difficulty: &Difficulty) -> Option<i32> {
+        let (_, _, slides, _) = self.slider_params()?;
+        let span_duration = self.slider_span_duration(timing_points, difficulty)?;
+        Some((self.time as f64 + span_duration * slides.max(1) as f64) as i32)
+    }
+
     pub fn slider_edge_sounds(&self) -> Vec<i32> {
         if self.is_slider() && self.object_params.len() >= 4 {
             self.object_params[3]
@@ -232,6 +294,11 @@ impl HitObject {
         let column = (self.x * (column_count as i32) / 512).max(0).min((column_count as i32) - 1);
         column as u8
     }
+
+    // osu! columns start at 0, generic lanes start at 1
+    pub fn mania_lane(&self, column_count: u8) -> u8 {
+        self.mania_column(column_count) + 1
+    }
     
     pub fn to_taiko(&self) -> common::TaikoHitobject {
         use common::TaikoHitobject;
@@ -242,9 +309,9 @@ impl HitObject {
         let is_spinner = self.is_spinner();
         
         match (is_slider, is_spinner, is_bonus, is_kat) {
-            (true, _, true, _) => TaikoHitobject::bonus_drum_roll(self.end_time().unwrap_or(0)),
-            (true, _, false, _) => TaikoHitobject::drum_roll(self.end_time().unwrap_or(0)),
-            (_, true, _, _) => TaikoHitobject::balloon(self.end_time().unwrap_or(0)),
+            (true, _, true, _) => TaikoHitobject::bonus_drum_roll(self.end_time().unwrap_or(0) as f64),
+            (true, _, false, _) => TaikoHitobject::drum_roll(self.end_time().unwrap_or(0) as f64),
+            (_, true, _, _) => TaikoHitobject::balloon(self.end_time().unwrap_or(0) as f64),
             (false, false, true, false) => TaikoHitobject::bonus_don(),
             (false, false, true, true) => TaikoHitobject::bonus_kat(),
             (false, false, false, false) => TaikoHitobject::don(),
@@ -260,7 +327,7 @@ impl HitObject {
         } else if self.is_slider() {
             CatchHitobject::juice(self.x)
         } else if self.is_spinner() {
-            CatchHitobject::banana(self.x, self.end_time().unwrap_or(0))
+            CatchHitobject::banana(self.x, self.end_time().unwrap_or(0) as f64)
         } else {
             CatchHitobject::empty()
         }
@@ -271,7 +338,7 @@ impl HitObject {
         let object_type = if self.is_normal() {
             Key::normal()
         } else if self.is_hold() {
-            Key::slider_start(Some(self.end_time().unwrap_or(0)))
+            Key::slider_start(Some(self.end_time().unwrap_or(0) as f64))
         } else {
             Key::empty()
         };
@@ -279,20 +346,56 @@ impl HitObject {
         (object_type, column)
     }
 
+    // generic keysounds only have one type, clap wins over finish and finish over whistle
+    pub fn hitsound_type(&self) -> sound::HitSoundType {
+        if self.hit_sound & 8 != 0 {
+            sound::HitSoundType::Clap
+        } else if self.hit_sound & 4 != 0 {
+            sound::HitSoundType::Finish
+        } else if self.hit_sound & 2 != 0 {
+            sound::HitSoundType::Whistle
+        } else {
+            sound::HitSoundType::Normal
+        }
+    }
+
+    pub fn resolve_samples(&self, timing_points: &TimingPoints, default_set: &SampleSet) -> Vec<ResolvedSample> {
+        let timing_point = timing_points.sample_point_at(self.time);
+        osu_sound::resolve_samples(self.hit_sound, &self.hit_sample, timing_point, default_set)
+    }
+
     pub fn get_generic_keysound(&self, soundbank: &mut SoundBank) -> sound::KeySound {
-        let hitsound_type = match self.hit_sound {
-            3 => sound::HitSoundType::Clap,
-            1 => sound::HitSoundType::Whistle,
-            2 => sound::HitSoundType::Finish,
-            _ => sound::HitSoundType::Normal,
+        self.resolve_keysound(soundbank, None, &SampleSet::Normal)
+    }
+
+    // skin samples stay as hitsound types, files from the map go into the soundbank
+    pub fn resolve_keysound(
+        &self,
+        soundbank: &mut SoundBank,
+        timing_point: Option<&TimingPoint>,
+        default_set: &SampleSet,
+    ) -> sound::KeySound {
+        let hitsound_type = self.hitsound_type();
+        let samples = osu_sound::resolve_samples(self.hit_sound, &self.hit_sample, timing_point, default_set);
+        let volume = samples.first().map_or(100, |sample| sample.volume);
+
+        let mut map_samples = samples.iter().filter(|sample| !sample.from_skin);
+        let Some(primary) = map_samples.next() else {
+            return sound::KeySound::of_type(volume, hitsound_type);
         };
 
-        if self.hit_sample.filename.trim().is_empty() {
-            sound::KeySound::of_type(100, hitsound_type)
-        } else {
-            let keysound_idx = soundbank.add_sound_sample(self.hit_sample.filename.clone());
-            sound::KeySound::with_custom(self.hit_sample.volume.clamp(0, 100), keysound_idx, Some(hitsound_type))
+        let keysound_idx = soundbank.add_sound_sample(primary.filename.clone());
+        if !self.hit_sample.filename.trim().is_empty() {
+            return sound::KeySound::with_custom(volume, keysound_idx, Some(hitsound_type));
         }
+
+        // custom index additions are files too, they play as sound effects so the skin's don't play on top
+        for addition in map_samples {
+            let sample = soundbank.add_sound_sample(addition.filename.clone());
+            soundbank.add_sound_effect(sound::SoundEffect::new(self.time as f64, addition.volume, sample));
+        }
+
+        sound::KeySound::with_custom(volume, keysound_idx, Some(sound::HitSoundType::Normal))
     }
     
     pub fn to_str(&self) -> String {
@@ -306,8 +409,7 @@ impl HitObject {
         
         parts.extend(self.object_params.iter().cloned());
         
-        let hit_sample_str = self.hit_sample.to_str();
-        parts.push(hit_sample_str);
+        self.push_hit_sample(&mut parts, self.object_type);
         
         parts.join(",")
     }
@@ -347,8 +449,7 @@ impl HitObject {
             }
         }
         
-        let hit_sample_str = self.hit_sample.to_str();
-        parts.push(hit_sample_str);
+        self.push_hit_sample(&mut parts, self.object_type);
         
         parts.join(",")
     }
@@ -381,28 +482,28 @@ impl HitObject {
             common::TaikoHitobjectType::DrumRoll => {
                 parts.push("2".to_string());
                 parts.push("0".to_string());
-                let end_time = taiko_obj.end_time.unwrap_or(self.time as i32);
-                parts.push(format!("L|256:192,1,{}", end_time - self.time as i32));
+                let end_time = taiko_obj.end_time.unwrap_or(self.time as f64);
+                parts.push(format!("L|256:192,1,{}", (end_time - self.time as f64) as i32));
             },
             common::TaikoHitobjectType::BonusDrumRoll => {
                 parts.push("2".to_string());
                 parts.push("4".to_string());
-                let end_time = taiko_obj.end_time.unwrap_or(self.time as i32);
-                parts.push(format!("L|256:192,1,{}", end_time - self.time as i32));
+                let end_time = taiko_obj.end_time.unwrap_or(self.time as f64);
+                parts.push(format!("L|256:192,1,{}", (end_time - self.time as f64) as i32));
             },
             common::TaikoHitobjectType::Balloon => {
                 parts.push("8".to_string());
                 parts.push("0".to_string());
-                let end_time = taiko_obj.end_time.unwrap_or(self.time as i32);
-                parts.push(end_time.to_string());
+                let end_time = taiko_obj.end_time.unwrap_or(self.time as f64);
+                parts.push((end_time as i32).to_string());
             },
             common::TaikoHitobjectType::Empty | common::TaikoHitobjectType::Unknown => {
                 return String::new();
             }
         }
 
-        let hit_sample_str = self.hit_sample.to_str();
-        parts.push(hit_sample_str);
+        let object_type = parts[3].parse::<u8>().unwrap_or(0);
+        self.push_hit_sample(&mut parts, object_type);
 
         return parts.join(",");
     }
@@ -430,15 +531,17 @@ impl HitObject {
             common::CatchHitobjectType::Banana => {
                 parts.push("8".to_string());
                 parts.push(self.hit_sound.to_string());
-                parts.push(catch_obj.end_time().unwrap_or(0).to_string());
+                parts.push((catch_obj.end_time().unwrap_or(0.0) as i32).to_string());
             },
-            common::CatchHitobjectType::Empty | common::CatchHitobjectType::Unknown => {
+            common::CatchHitobjectType::TinyDroplet
+            | common::CatchHitobjectType::Empty
+            | common::CatchHitobjectType::Unknown => {
                 return String::new();
             },
         }
 
-        let hit_sample_str = self.hit_sample.to_str();
-        parts.push(hit_sample_str);
+        let object_type = parts[3].parse::<u8>().unwrap_or(0);
+        self.push_hit_sample(&mut parts, object_type);
 
         parts.join(",")
     }
@@ -6,6 +6,9 @@ mod editor;
 mod general;
 mod metadata;
 mod difficulty;
+mod colours;
+mod slider_path;
+pub(crate) mod storyboard;
 pub mod sound;
 
 pub use chart::*;
@@ -16,4 +19,10 @@ pub use editor::Editor;
 pub use general::General;
 pub use metadata::Metadata;
 pub use difficulty::Difficulty;
+pub use colours::{Colour, Colours};
+pub use slider_path::{CurveType, SliderPath};
+pub use storyboard::{
+    Command, CommandLoop, CommandTrigger, CommandValues, Easing, Layer, LoopType, ObjectKind, ObjectState,
+    Origin, ParameterType, Storyboard, StoryboardObject,
+};
 pub use sound::*;
\ No newline at end of file
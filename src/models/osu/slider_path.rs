@@ -0,0 +1,370 @@
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+const BEZIER_TOLERANCE: f32 = 0.25;
+const CIRCULAR_ARC_TOLERANCE: f32 = 0.1;
+const CATMULL_DETAIL: usize = 50;
+
+type Point = (f32, f32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveType {
+    Linear,
+    PerfectCurve,
+    Bezier,
+    Catmull,
+}
+
+impl FromStr for CurveType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "L" => Ok(CurveType::Linear),
+            "P" => Ok(CurveType::PerfectCurve),
+            "B" => Ok(CurveType::Bezier),
+            "C" => Ok(CurveType::Catmull),
+            _ => Err(format!("Invalid curve type: {}", s)),
+        }
+    }
+}
+
+impl CurveType {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            CurveType::Linear => "L",
+            CurveType::PerfectCurve => "P",
+            CurveType::Bezier => "B",
+            CurveType::Catmull => "C",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliderPath {
+    pub curve_type: CurveType,
+
+    // absolute positions, the first one is the slider head
+    pub control_points: Vec<Point>,
+
+    pub expected_distance: Option<f64>,
+
+    calculated_path: Vec<Point>,
+
+    cumulative_length: Vec<f64>,
+}
+
+impl SliderPath {
+    pub fn new(curve_type: CurveType, control_points: Vec<Point>, expected_distance: Option<f64>) -> Self {
+        let mut path = Self {
+            curve_type,
+            control_points,
+            expected_distance,
+            calculated_path: Vec::new(),
+            cumulative_length: Vec::new(),
+        };
+        path.calculate_path();
+        path.calculate_length();
+        path
+    }
+
+    pub fn distance(&self) -> f64 {
+        self.cumulative_length.last().copied().unwrap_or(0.0)
+    }
+
+    pub fn calculated_path(&self) -> &[Point] {
+        &self.calculated_path
+    }
+
+    // progress goes from 0 (head) to 1 (end of the path), repeats are not taken into account
+    pub fn position_at(&self, progress: f64) -> Point {
+        let distance = progress.clamp(0.0, 1.0) * self.distance();
+        self.position_at_distance(distance)
+    }
+
+    pub fn end_position(&self) -> Point {
+        self.position_at(1.0)
+    }
+
+    fn position_at_distance(&self, distance: f64) -> Point {
+        let path = &self.calculated_path;
+        if path.is_empty() {
+            return (0.0, 0.0);
+        }
+
+        let index = self.cumulative_length.partition_point(|&length| length < distance);
+        if index == 0 {
+            return path[0];
+        }
+        if index >= path.len() {
+            return path[path.len() - 1];
+        }
+
+        let (start, end) = (path[index - 1], path[index]);
+        let (start_distance, end_distance) = (self.cumulative_length[index - 1], self.cumulative_length[index]);
+        if end_distance - start_distance <= f64::EPSILON {
+            return start;
+        }
+
+        let t = ((distance - start_distance) / (end_distance - start_distance)) as f32;
+        (start.0 + (end.0 - start.0) * t, start.1 + (end.1 - start.1) * t)
+    }
+
+    fn calculate_path(&mut self) {
+        let points = &self.control_points;
+        let mut path: Vec<Point> = Vec::new();
+
+        let segments: Vec<&[Point]> = match self.curve_type {
+            CurveType::Bezier | CurveType::PerfectCurve => {
+                // a point repeated twice in a row starts a new segment
+                let mut segments = Vec::new();
+                let mut start = 0;
+                for i in 1..points.len() {
+                    if points[i] == points[i - 1] {
+                        segments.push(&points[start..i]);
+                        start = i;
+                    }
+                }
+                segments.push(&points[start..]);
+                segments
+            }
+            CurveType::Linear | CurveType::Catmull => vec![&points[..]],
+        };
+
+        for segment in segments {
+            let approximated = approximate_segment(self.curve_type, segment);
+            // the first point of a segment is the last point of the previous one
+            let skip = usize::from(!path.is_empty() && approximated.first() == path.last());
+            path.extend_from_slice(&approximated[skip.min(approximated.len())..]);
+        }
+
+        self.calculated_path = path;
+    }
+
+    // same as osu!, the last segment gets cut off or extended to match the length in the file
+    fn calculate_length(&mut self) {
+        let path = &mut self.calculated_path;
+        let mut cumulative_length = Vec::with_capacity(path.len());
+        let mut calculated_length = 0.0;
+        cumulative_length.push(0.0);
+
+        for segment in path.windows(2) {
+            calculated_length += distance(segment[0], segment[1]);
+            cumulative_length.push(calculated_length);
+        }
+
+        let Some(expected_distance) = self.expected_distance else {
+            self.cumulative_length = cumulative_length;
+            return;
+        };
+
+        if (calculated_length - expected_distance).abs() <= f64::EPSILON {
+            self.cumulative_length = cumulative_length;
+            return;
+        }
+
+        // osu!stable didn't extend the path if the last two points are the same
+        if path.len() >= 2 && path[path.len() - 1] == path[path.len() - 2] && expected_distance > calculated_length {
+            self.cumulative_length = cumulative_length;
+            return;
+        }
+
+        cumulative_length.pop();
+        let mut end_index = path.len().saturating_sub(1);
+
+        if calculated_length > expected_distance {
+            while cumulative_length.last().is_some_and(|&length| length >= expected_distance) {
+                cumulative_length.pop();
+                path.remove(end_index);
+                end_index = end_index.saturating_sub(1);
+            }
+        }
+
+        if end_index == 0 || cumulative_length.is_empty() {
+            path.truncate(1);
+            self.cumulative_length = vec![0.0; path.len()];
+            return;
+        }
+
+        let (previous, last) = (path[end_index - 1], path[end_index]);
+        let length = distance(previous, last) as f32;
+        let remaining = (expected_distance - cumulative_length[cumulative_length.len() - 1]) as f32;
+        if length > 0.0 {
+            let direction = ((last.0 - previous.0) / length, (last.1 - previous.1) / length);
+            path[end_index] = (previous.0 + direction.0 * remaining, previous.1 + direction.1 * remaining);
+        }
+
+        cumulative_length.push(expected_distance);
+        self.cumulative_length = cumulative_length;
+    }
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    (((b.0 - a.0) as f64).powi(2) + ((b.1 - a.1) as f64).powi(2)).sqrt()
+}
+
+fn approximate_segment(curve_type: CurveType, points: &[Point]) -> Vec<Point> {
+    match curve_type {
+        CurveType::Linear => points.to_vec(),
+        CurveType::PerfectCurve if points.len() == 3 => {
+            approximate_circular_arc(points).unwrap_or_else(|| approximate_bezier(points))
+        }
+        CurveType::Catmull => approximate_catmull(points),
+        CurveType::PerfectCurve | CurveType::Bezier => approximate_bezier(points),
+    }
+}
+
+fn is_flat_enough(points: &[Point]) -> bool {
+    points.windows(3).all(|p| {
+        let x = p[0].0 - 2.0 * p[1].0 + p[2].0;
+        let y = p[0].1 - 2.0 * p[1].1 + p[2].1;
+        x * x + y * y <= BEZIER_TOLERANCE * BEZIER_TOLERANCE * 4.0
+    })
+}
+
+// de Casteljau split at t = 0.5
+fn subdivide(points: &[Point]) -> (Vec<Point>, Vec<Point>) {
+    let count = points.len();
+    let mut midpoints = points.to_vec();
+    let mut left = vec![(0.0, 0.0); count];
+    let mut right = vec![(0.0, 0.0); count];
+
+    for i in 0..count {
+        left[i] = midpoints[0];
+        right[count - i - 1] = midpoints[count - i - 1];
+
+        for j in 0..count - i - 1 {
+            midpoints[j] = (
+                (midpoints[j].0 + midpoints[j + 1].0) / 2.0,
+                (midpoints[j].1 + midpoints[j + 1].1) / 2.0,
+            );
+        }
+    }
+
+    (left, right)
+}
+
+fn approximate_bezier(points: &[Point]) -> Vec<Point> {
+    let count = points.len();
+    if count < 2 {
+        return points.to_vec();
+    }
+
+    let mut output = Vec::new();
+    let mut to_flatten = vec![points.to_vec()];
+
+    while let Some(parent) = to_flatten.pop() {
+        if is_flat_enough(&parent) {
+            let (mut left, right) = subdivide(&parent);
+            left.extend_from_slice(&right[1..]);
+
+            output.push(parent[0]);
+            for i in 1..count - 1 {
+                let index = 2 * i;
+                output.push((
+                    0.25 * (left[index - 1].0 + 2.0 * left[index].0 + left[index + 1].0),
+                    0.25 * (left[index - 1].1 + 2.0 * left[index].1 + left[index + 1].1),
+                ));
+            }
+            continue;
+        }
+
+        let (left, right) = subdivide(&parent);
+        to_flatten.push(right);
+        to_flatten.push(left);
+    }
+
+    output.push(points[count - 1]);
+    output
+}
+
+fn approximate_circular_arc(points: &[Point]) -> Option<Vec<Point>> {
+    let (a, b, c) = (
+        (points[0].0 as f64, points[0].1 as f64),
+        (points[1].0 as f64, points[1].1 as f64),
+        (points[2].0 as f64, points[2].1 as f64),
+    );
+
+    // the points are on a straight line, so there's no circle going through them
+    let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+    if d.abs() < 1e-3 {
+        return None;
+    }
+
+    let a_sq = a.0 * a.0 + a.1 * a.1;
+    let b_sq = b.0 * b.0 + b.1 * b.1;
+    let c_sq = c.0 * c.0 + c.1 * c.1;
+    let centre = (
+        (a_sq * (b.1 - c.1) + b_sq * (c.1 - a.1) + c_sq * (a.1 - b.1)) / d,
+        (a_sq * (c.0 - b.0) + b_sq * (a.0 - c.0) + c_sq * (b.0 - a.0)) / d,
+    );
+
+    let radius = ((a.0 - centre.0).powi(2) + (a.1 - centre.1).powi(2)).sqrt();
+    let theta_start = (a.1 - centre.1).atan2(a.0 - centre.0);
+    let mut theta_end = (c.1 - centre.1).atan2(c.0 - centre.0);
+    while theta_end < theta_start {
+        theta_end += 2.0 * PI;
+    }
+
+    let mut direction = 1.0;
+    let mut theta_range = theta_end - theta_start;
+
+    // b has to be on the arc, if it isn't the arc goes the other way around
+    let ortho_a_to_c = (c.1 - a.1, -(c.0 - a.0));
+    if ortho_a_to_c.0 * (b.0 - a.0) + ortho_a_to_c.1 * (b.1 - a.1) < 0.0 {
+        direction = -1.0;
+        theta_range = 2.0 * PI - theta_range;
+    }
+
+    let tolerance = CIRCULAR_ARC_TOLERANCE as f64;
+    let amount_points = if 2.0 * radius <= tolerance {
+        2
+    } else {
+        ((theta_range / (2.0 * (1.0 - tolerance / radius).acos())).ceil() as usize).max(2)
+    };
+
+    Some(
+        (0..amount_points)
+            .map(|i| {
+                let fraction = i as f64 / (amount_points - 1) as f64;
+                let theta = theta_start + direction * fraction * theta_range;
+                (
+                    (centre.0 + radius * theta.cos()) as f32,
+                    (centre.1 + radius * theta.sin()) as f32,
+                )
+            })
+            .collect(),
+    )
+}
+
+fn catmull_point(v1: Point, v2: Point, v3: Point, v4: Point, t: f32) -> Point {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let component = |p1: f32, p2: f32, p3: f32, p4: f32| {
+        0.5 * (2.0 * p2
+            + (-p1 + p3) * t
+            + (2.0 * p1 - 5.0 * p2 + 4.0 * p3 - p4) * t2
+            + (-p1 + 3.0 * p2 - 3.0 * p3 + p4) * t3)
+    };
+
+    (component(v1.0, v2.0, v3.0, v4.0), component(v1.1, v2.1, v3.1, v4.1))
+}
+
+fn approximate_catmull(points: &[Point]) -> Vec<Point> {
+    let count = points.len();
+    let mut output = Vec::with_capacity(count.saturating_sub(1) * CATMULL_DETAIL * 2);
+
+    for i in 0..count.saturating_sub(1) {
+        let v1 = if i > 0 { points[i - 1] } else { points[i] };
+        let v2 = points[i];
+        let v3 = if i < count - 1 { points[i + 1] } else { (2.0 * v2.0 - v1.0, 2.0 * v2.1 - v1.1) };
+        let v4 = if i < count - 2 { points[i + 2] } else { (2.0 * v3.0 - v2.0, 2.0 * v3.1 - v2.1) };
+
+        for c in 0..CATMULL_DETAIL {
+            output.push(catmull_point(v1, v2, v3, v4, c as f32 / CATMULL_DETAIL as f32));
+            output.push(catmull_point(v1, v2, v3, v4, (c + 1) as f32 / CATMULL_DETAIL as f32));
+        }
+    }
+
+    output
+}
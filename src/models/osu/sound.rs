@@ -1,5 +1,8 @@
 use std::str::FromStr;
 
+use crate::models::generic::sound::HitSoundType;
+use crate::models::osu::TimingPoint;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum SampleSet {
     Normal,
@@ -12,7 +15,8 @@ impl FromStr for SampleSet {
     
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "Normal" => Ok(SampleSet::Normal),
+            // old files can have None, osu! plays those with the normal set
+            "Normal" | "None" => Ok(SampleSet::Normal),
             "Soft" => Ok(SampleSet::Soft),
             "Drum" => Ok(SampleSet::Drum),
             _ => Err(format!("Invalid SampleSet: {}", s)),
@@ -30,6 +34,26 @@ impl ToString for SampleSet {
     }
 }
 
+impl SampleSet {
+    // 0 means the set isn't overridden
+    pub fn from_index(index: i32) -> Option<Self> {
+        match index {
+            1 => Some(SampleSet::Normal),
+            2 => Some(SampleSet::Soft),
+            3 => Some(SampleSet::Drum),
+            _ => None,
+        }
+    }
+
+    pub fn file_prefix(&self) -> &'static str {
+        match self {
+            SampleSet::Normal => "normal",
+            SampleSet::Soft => "soft",
+            SampleSet::Drum => "drum",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct HitSample {
     pub normal_set: u8,
@@ -59,21 +83,15 @@ impl FromStr for HitSample {
     type Err = String;
     
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // older versions write fewer fields, missing ones keep their defaults
         let parts: Vec<&str> = s.split(':').collect();
+        let field = |i: usize| parts.get(i).map(|part| part.trim()).unwrap_or_default();
         
-        if parts.len() < 4 {
-            return Ok(HitSample::default());
-        }
-        
-        let normal_set = parts[0].parse::<u8>().unwrap_or(0);
-        let addition_set = parts[1].parse::<u8>().unwrap_or(0);
-        let index = parts[2].parse::<usize>().unwrap_or(0);
-        let volume = parts[3].parse::<u8>().unwrap_or(0);
-        let filename = if parts.len() > 4 {
-            parts[4].to_string()
-        } else {
-            String::new()
-        };
+        let normal_set = field(0).parse::<u8>().unwrap_or(0);
+        let addition_set = field(1).parse::<u8>().unwrap_or(0);
+        let index = field(2).parse::<usize>().unwrap_or(0);
+        let volume = field(3).parse::<u8>().unwrap_or(0);
+        let filename = field(4).to_string();
         
         Ok(HitSample {
             normal_set,
@@ -95,3 +113,84 @@ impl HitSample {
                 self.filename)
     }
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSample {
+    pub filename: String,
+    
+    pub volume: u8,
+    
+    pub hitsound_type: HitSoundType,
+    
+    // index 0 plays the skin's samples, those don't come with the map
+    pub from_skin: bool,
+}
+
+fn hitsound_file_name(hitsound_type: HitSoundType) -> &'static str {
+    match hitsound_type {
+        HitSoundType::Normal => "hitnormal",
+        HitSoundType::Whistle => "hitwhistle",
+        HitSoundType::Finish => "hitfinish",
+        HitSoundType::Clap => "hitclap",
+    }
+}
+
+// works out the files osu! plays for an object, hitnormal always plays and the additions
+// come from the hitSound bits, anything left at 0 falls back to the timing point then the map
+pub fn resolve_samples(
+    hit_sound: u8,
+    hit_sample: &HitSample,
+    timing_point: Option<&TimingPoint>,
+    default_set: &SampleSet,
+) -> Vec<ResolvedSample> {
+    let volume = match (hit_sample.volume, timing_point) {
+        (0, Some(timing_point)) => timing_point.volume.clamp(0, 100) as u8,
+        (0, None) => 100,
+        (volume, _) => volume.min(100),
+    };
+    
+    // a custom filename replaces every other sample
+    let filename = hit_sample.filename.trim();
+    if !filename.is_empty() {
+        return vec![ResolvedSample {
+            filename: filename.to_string(),
+            volume,
+            hitsound_type: HitSoundType::Normal,
+            from_skin: false,
+        }];
+    }
+    
+    let timing_set = timing_point
+        .and_then(|timing_point| SampleSet::from_index(timing_point.sample_set))
+        .unwrap_or_else(|| default_set.clone());
+    let normal_set = SampleSet::from_index(hit_sample.normal_set as i32).unwrap_or(timing_set);
+    let addition_set = SampleSet::from_index(hit_sample.addition_set as i32).unwrap_or_else(|| normal_set.clone());
+    
+    let index = match hit_sample.index {
+        0 => timing_point.map_or(0, |timing_point| timing_point.sample_index.max(0) as usize),
+        index => index,
+    };
+    
+    let sample = |set: &SampleSet, hitsound_type: HitSoundType| {
+        let suffix = if index > 1 { index.to_string() } else { String::new() };
+        ResolvedSample {
+            filename: format!("{}-{}{}.wav", set.file_prefix(), hitsound_file_name(hitsound_type), suffix),
+            volume,
+            hitsound_type,
+            from_skin: index == 0,
+        }
+    };
+    
+    let mut samples = vec![sample(&normal_set, HitSoundType::Normal)];
+    for (flag, hitsound_type) in [
+        (2, HitSoundType::Whistle),
+        (4, HitSoundType::Finish),
+        (8, HitSoundType::Clap),
+    ] {
+        if hit_sound & flag != 0 {
+            samples.push(sample(&addition_set, hitsound_type));
+        }
+    }
+    
+    samples
+}
@@ -0,0 +1,1159 @@
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+use crate::models::osu::events::{Events, Sample};
+use crate::utils::serde::process_bracket_sections;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    Background,
+    Fail,
+    Pass,
+    Foreground,
+    Overlay,
+}
+
+impl FromStr for Layer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "Background" | "0" => Ok(Layer::Background),
+            "Fail" | "1" => Ok(Layer::Fail),
+            "Pass" | "2" => Ok(Layer::Pass),
+            "Foreground" | "3" => Ok(Layer::Foreground),
+            "Overlay" | "4" => Ok(Layer::Overlay),
+            _ => Err(format!("Invalid storyboard layer: {}", s)),
+        }
+    }
+}
+
+impl Layer {
+    pub const ALL: [Layer; 5] = [Layer::Background, Layer::Fail, Layer::Pass, Layer::Foreground, Layer::Overlay];
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Layer::Background => "Background",
+            Layer::Fail => "Fail",
+            Layer::Pass => "Pass",
+            Layer::Foreground => "Foreground",
+            Layer::Overlay => "Overlay",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    TopLeft,
+    Centre,
+    CentreLeft,
+    TopRight,
+    BottomCentre,
+    TopCentre,
+    Custom,
+    CentreRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl FromStr for Origin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "TopLeft" | "0" => Ok(Origin::TopLeft),
+            "Centre" | "1" => Ok(Origin::Centre),
+            "CentreLeft" | "2" => Ok(Origin::CentreLeft),
+            "TopRight" | "3" => Ok(Origin::TopRight),
+            "BottomCentre" | "4" => Ok(Origin::BottomCentre),
+            "TopCentre" | "5" => Ok(Origin::TopCentre),
+            "Custom" | "6" => Ok(Origin::Custom),
+            "CentreRight" | "7" => Ok(Origin::CentreRight),
+            "BottomLeft" | "8" => Ok(Origin::BottomLeft),
+            "BottomRight" | "9" => Ok(Origin::BottomRight),
+            _ => Err(format!("Invalid storyboard origin: {}", s)),
+        }
+    }
+}
+
+impl Origin {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Origin::TopLeft => "TopLeft",
+            Origin::Centre => "Centre",
+            Origin::CentreLeft => "CentreLeft",
+            Origin::TopRight => "TopRight",
+            Origin::BottomCentre => "BottomCentre",
+            Origin::TopCentre => "TopCentre",
+            Origin::Custom => "Custom",
+            Origin::CentreRight => "CentreRight",
+            Origin::BottomLeft => "BottomLeft",
+            Origin::BottomRight => "BottomRight",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopType {
+    LoopForever,
+    LoopOnce,
+}
+
+impl FromStr for LoopType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "LoopForever" | "0" => Ok(LoopType::LoopForever),
+            "LoopOnce" | "1" => Ok(LoopType::LoopOnce),
+            _ => Err(format!("Invalid animation loop type: {}", s)),
+        }
+    }
+}
+
+impl LoopType {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            LoopType::LoopForever => "LoopForever",
+            LoopType::LoopOnce => "LoopOnce",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    Out,
+    In,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+    InQuart,
+    OutQuart,
+    InOutQuart,
+    InQuint,
+    OutQuint,
+    InOutQuint,
+    InSine,
+    OutSine,
+    InOutSine,
+    InExpo,
+    OutExpo,
+    InOutExpo,
+    InCirc,
+    OutCirc,
+    InOutCirc,
+    InElastic,
+    OutElastic,
+    OutElasticHalf,
+    OutElasticQuarter,
+    InOutElastic,
+    InBack,
+    OutBack,
+    InOutBack,
+    InBounce,
+    OutBounce,
+    InOutBounce,
+}
+
+impl Easing {
+    const ALL: [Easing; 35] = [
+        Easing::Linear, Easing::Out, Easing::In, Easing::InQuad, Easing::OutQuad, Easing::InOutQuad,
+        Easing::InCubic, Easing::OutCubic, Easing::InOutCubic, Easing::InQuart, Easing::OutQuart,
+        Easing::InOutQuart, Easing::InQuint, Easing::OutQuint, Easing::InOutQuint, Easing::InSine,
+        Easing::OutSine, Easing::InOutSine, Easing::InExpo, Easing::OutExpo, Easing::InOutExpo,
+        Easing::InCirc, Easing::OutCirc, Easing::InOutCirc, Easing::InElastic, Easing::OutElastic,
+        Easing::OutElasticHalf, Easing::OutElasticQuarter, Easing::InOutElastic, Easing::InBack,
+        Easing::OutBack, Easing::InOutBack, Easing::InBounce, Easing::OutBounce, Easing::InOutBounce,
+    ];
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        Self::ALL.iter().position(|easing| easing == self).unwrap_or(0) as u8
+    }
+
+    // same curves as osu!framework, t goes from 0 to 1
+    pub fn apply(&self, t: f64) -> f64 {
+        const ELASTIC_CONST: f64 = 2.0 * PI / 0.3;
+        const ELASTIC_CONST2: f64 = 0.3 / 4.0;
+        const BACK_CONST: f64 = 1.70158;
+        const BACK_CONST2: f64 = BACK_CONST * 1.525;
+
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::In | Easing::InQuad => t * t,
+            Easing::Out | Easing::OutQuad => t * (2.0 - t),
+            Easing::InOutQuad => {
+                if t < 0.5 { 2.0 * t * t } else { (4.0 - 2.0 * t) * t - 1.0 }
+            }
+            Easing::InCubic => t.powi(3),
+            Easing::OutCubic => (t - 1.0).powi(3) + 1.0,
+            Easing::InOutCubic => {
+                if t < 0.5 { 4.0 * t.powi(3) } else { 4.0 * (t - 1.0).powi(3) + 1.0 }
+            }
+            Easing::InQuart => t.powi(4),
+            Easing::OutQuart => 1.0 - (t - 1.0).powi(4),
+            Easing::InOutQuart => {
+                if t < 0.5 { 8.0 * t.powi(4) } else { 1.0 - 8.0 * (t - 1.0).powi(4) }
+            }
+            Easing::InQuint => t.powi(5),
+            Easing::OutQuint => (t - 1.0).powi(5) + 1.0,
+            Easing::InOutQuint => {
+                if t < 0.5 { 16.0 * t.powi(5) } else { 16.0 * (t - 1.0).powi(5) + 1.0 }
+            }
+            Easing::InSine => 1.0 - (t * PI / 2.0).cos(),
+            Easing::OutSine => (t * PI / 2.0).sin(),
+            Easing::InOutSine => 0.5 - 0.5 * (PI * t).cos(),
+            Easing::InExpo => {
+                if t == 0.0 { 0.0 } else { 2f64.powf(10.0 * (t - 1.0)) }
+            }
+            Easing::OutExpo => {
+                if t == 1.0 { 1.0 } else { 1.0 - 2f64.powf(-10.0 * t) }
+            }
+            Easing::InOutExpo => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else if t < 0.5 {
+                    0.5 * 2f64.powf(20.0 * t - 10.0)
+                } else {
+                    1.0 - 0.5 * 2f64.powf(-20.0 * t + 10.0)
+                }
+            }
+            Easing::InCirc => 1.0 - (1.0 - t * t).sqrt(),
+            Easing::OutCirc => (1.0 - (t - 1.0).powi(2)).sqrt(),
+            Easing::InOutCirc => {
+                if t < 0.5 {
+                    0.5 - 0.5 * (1.0 - 4.0 * t * t).sqrt()
+                } else {
+                    0.5 * (1.0 - (2.0 * t - 2.0).powi(2)).sqrt() + 0.5
+                }
+            }
+            Easing::InElastic => {
+                -(2f64.powf(-10.0 + 10.0 * t)) * ((1.0 - ELASTIC_CONST2 - t) * ELASTIC_CONST).sin()
+            }
+            Easing::OutElastic => 2f64.powf(-10.0 * t) * ((t - ELASTIC_CONST2) * ELASTIC_CONST).sin() + 1.0,
+            Easing::OutElasticHalf => 2f64.powf(-10.0 * t) * ((0.5 * t - ELASTIC_CONST2) * ELASTIC_CONST).sin() + 1.0,
+            Easing::OutElasticQuarter => 2f64.powf(-10.0 * t) * ((0.25 * t - ELASTIC_CONST2) * ELASTIC_CONST).sin() + 1.0,
+            Easing::InOutElastic => {
+                let t = t * 2.0;
+                if t < 1.0 {
+                    -0.5 * 2f64.powf(-10.0 + 10.0 * t)
+                        * ((1.0 - ELASTIC_CONST2 * 1.5 - t) * ELASTIC_CONST / 1.5).sin()
+                } else {
+                    let t = t - 1.0;
+                    0.5 * 2f64.powf(-10.0 * t) * ((t - ELASTIC_CONST2 * 1.5) * ELASTIC_CONST / 1.5).sin() + 1.0
+                }
+            }
+            Easing::InBack => t * t * ((BACK_CONST + 1.0) * t - BACK_CONST),
+            Easing::OutBack => {
+                let t = t - 1.0;
+                t * t * ((BACK_CONST + 1.0) * t + BACK_CONST) + 1.0
+            }
+            Easing::InOutBack => {
+                let t = t * 2.0;
+                if t < 1.0 {
+                    0.5 * t * t * ((BACK_CONST2 + 1.0) * t - BACK_CONST2)
+                } else {
+                    let t = t - 2.0;
+                    0.5 * (t * t * ((BACK_CONST2 + 1.0) * t + BACK_CONST2) + 2.0)
+                }
+            }
+            Easing::InBounce => 1.0 - out_bounce(1.0 - t),
+            Easing::OutBounce => out_bounce(t),
+            Easing::InOutBounce => {
+                if t < 0.5 {
+                    0.5 - 0.5 * out_bounce(1.0 - 2.0 * t)
+                } else {
+                    0.5 * out_bounce((t - 0.5) * 2.0) + 0.5
+                }
+            }
+        }
+    }
+}
+
+fn out_bounce(t: f64) -> f64 {
+    if t < 1.0 / 2.75 {
+        7.5625 * t * t
+    } else if t < 2.0 / 2.75 {
+        let t = t - 1.5 / 2.75;
+        7.5625 * t * t + 0.75
+    } else if t < 2.5 / 2.75 {
+        let t = t - 2.25 / 2.75;
+        7.5625 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / 2.75;
+        7.5625 * t * t + 0.984375
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterType {
+    FlipHorizontal,
+    FlipVertical,
+    AdditiveBlending,
+}
+
+impl ParameterType {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            ParameterType::FlipHorizontal => "H",
+            ParameterType::FlipVertical => "V",
+            ParameterType::AdditiveBlending => "A",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandValues {
+    Fade(f32, f32),
+    Move((f32, f32), (f32, f32)),
+    MoveX(f32, f32),
+    MoveY(f32, f32),
+    Scale(f32, f32),
+    VectorScale((f32, f32), (f32, f32)),
+    Rotate(f32, f32),
+    Colour((u8, u8, u8), (u8, u8, u8)),
+    Parameter(ParameterType),
+}
+
+impl CommandValues {
+    pub fn identifier(&self) -> &'static str {
+        match self {
+            CommandValues::Fade(..) => "F",
+            CommandValues::Move(..) => "M",
+            CommandValues::MoveX(..) => "MX",
+            CommandValues::MoveY(..) => "MY",
+            CommandValues::Scale(..) => "S",
+            CommandValues::VectorScale(..) => "V",
+            CommandValues::Rotate(..) => "R",
+            CommandValues::Colour(..) => "C",
+            CommandValues::Parameter(..) => "P",
+        }
+    }
+
+    fn value_count(identifier: &str) -> Option<usize> {
+        match identifier {
+            "F" | "MX" | "MY" | "S" | "R" => Some(1),
+            "M" | "V" => Some(2),
+            "C" => Some(3),
+            _ => None,
+        }
+    }
+
+    fn from_values(identifier: &str, start: &[f32], end: &[f32]) -> Option<Self> {
+        let colour = |values: &[f32]| {
+            (
+                values[0].clamp(0.0, 255.0) as u8,
+                values[1].clamp(0.0, 255.0) as u8,
+                values[2].clamp(0.0, 255.0) as u8,
+            )
+        };
+
+        Some(match identifier {
+            "F" => CommandValues::Fade(start[0], end[0]),
+            "M" => CommandValues::Move((start[0], start[1]), (end[0], end[1])),
+            "MX" => CommandValues::MoveX(start[0], end[0]),
+            "MY" => CommandValues::MoveY(start[0], end[0]),
+            "S" => CommandValues::Scale(start[0], end[0]),
+            "V" => CommandValues::VectorScale((start[0], start[1]), (end[0], end[1])),
+            "R" => CommandValues::Rotate(start[0], end[0]),
+            "C" => CommandValues::Colour(colour(start), colour(end)),
+            _ => return None,
+        })
+    }
+
+    fn values_to_str(&self) -> String {
+        let pair = |start: String, end: String| {
+            if start == end { start } else { format!("{},{}", start, end) }
+        };
+
+        match self {
+            CommandValues::Fade(start, end)
+            | CommandValues::MoveX(start, end)
+            | CommandValues::MoveY(start, end)
+            | CommandValues::Scale(start, end)
+            | CommandValues::Rotate(start, end) => pair(start.to_string(), end.to_string()),
+            CommandValues::Move(start, end) | CommandValues::VectorScale(start, end) => pair(
+                format!("{},{}", start.0, start.1),
+                format!("{},{}", end.0, end.1),
+            ),
+            CommandValues::Colour(start, end) => pair(
+                format!("{},{},{}", start.0, start.1, start.2),
+                format!("{},{},{}", end.0, end.1, end.2),
+            ),
+            CommandValues::Parameter(parameter) => parameter.to_str().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Command {
+    pub easing: Easing,
+
+    pub start_time: i32,
+
+    pub end_time: i32,
+
+    pub values: CommandValues,
+}
+
+impl Command {
+    pub fn new(easing: Easing, start_time: i32, end_time: i32, values: CommandValues) -> Self {
+        Self {
+            easing,
+            start_time,
+            end_time,
+            values,
+        }
+    }
+
+    pub fn duration(&self) -> i32 {
+        self.end_time - self.start_time
+    }
+
+    // eased progress of the command at the given time, clamped to 0..1
+    pub fn progress_at(&self, time: i32) -> f64 {
+        if self.end_time <= self.start_time {
+            return if time >= self.start_time { 1.0 } else { 0.0 };
+        }
+        let t = (time - self.start_time) as f64 / self.duration() as f64;
+        self.easing.apply(t)
+    }
+
+    fn offset(&self, offset: i32) -> Self {
+        Self {
+            start_time: self.start_time + offset,
+            end_time: self.end_time + offset,
+            ..*self
+        }
+    }
+
+    // a single line can hold several commands when more values than needed are given
+    pub fn parse_line(line: &str) -> Result<Vec<Command>, String> {
+        let parts: Vec<&str> = line.trim().split(',').map(str::trim).collect();
+        if parts.len() < 5 {
+            return Err(format!("Invalid storyboard command: {}", line));
+        }
+
+        let identifier = parts[0];
+        let easing = parts[1]
+            .parse::<u8>()
+            .ok()
+            .and_then(Easing::from_u8)
+            .ok_or_else(|| format!("Invalid easing: {}", parts[1]))?;
+        let start_time = parts[2]
+            .parse::<f32>()
+            .map_err(|_| format!("Invalid command start time: {}", parts[2]))? as i32;
+        let end_time = if parts[3].is_empty() {
+            start_time
+        } else {
+            parts[3]
+                .parse::<f32>()
+                .map_err(|_| format!("Invalid command end time: {}", parts[3]))? as i32
+        };
+
+        if identifier == "P" {
+            let parameter = match parts[4] {
+                "H" => ParameterType::FlipHorizontal,
+                "V" => ParameterType::FlipVertical,
+                "A" => ParameterType::AdditiveBlending,
+                _ => return Err(format!("Invalid parameter: {}", parts[4])),
+            };
+            return Ok(vec![Command::new(easing, start_time, end_time, CommandValues::Parameter(parameter))]);
+        }
+
+        let value_count = CommandValues::value_count(identifier)
+            .ok_or_else(|| format!("Unknown storyboard command: {}", identifier))?;
+        let values = parts[4..]
+            .iter()
+            .map(|value| value.parse::<f32>().map_err(|_| format!("Invalid command value: {}", value)))
+            .collect::<Result<Vec<f32>, String>>()?;
+
+        if values.len() < value_count {
+            return Err(format!("Not enough values for storyboard command: {}", line));
+        }
+
+        let groups: Vec<&[f32]> = values.chunks_exact(value_count).collect();
+        if groups.len() == 1 {
+            let values = CommandValues::from_values(identifier, groups[0], groups[0])
+                .ok_or_else(|| format!("Unknown storyboard command: {}", identifier))?;
+            return Ok(vec![Command::new(easing, start_time, end_time, values)]);
+        }
+
+        let duration = end_time - start_time;
+        let mut commands = Vec::with_capacity(groups.len() - 1);
+        for (i, pair) in groups.windows(2).enumerate() {
+            let offset = duration * i as i32;
+            let values = CommandValues::from_values(identifier, pair[0], pair[1])
+                .ok_or_else(|| format!("Unknown storyboard command: {}", identifier))?;
+            commands.push(Command::new(easing, start_time + offset, end_time + offset, values));
+        }
+
+        Ok(commands)
+    }
+
+    pub fn to_str(&self) -> String {
+        let end_time = if self.end_time == self.start_time {
+            String::new()
+        } else {
+            self.end_time.to_string()
+        };
+
+        format!(
+            "{},{},{},{},{}",
+            self.values.identifier(),
+            self.easing.to_u8(),
+            self.start_time,
+            end_time,
+            self.values.values_to_str()
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandLoop {
+    pub start_time: i32,
+
+    pub loop_count: i32,
+
+    // times are relative to the start of the loop
+    pub commands: Vec<Command>,
+}
+
+impl CommandLoop {
+    fn commands_start_time(&self) -> i32 {
+        self.commands.iter().map(|c| c.start_time).min().unwrap_or(0)
+    }
+
+    fn commands_end_time(&self) -> i32 {
+        self.commands.iter().map(|c| c.end_time).max().unwrap_or(0)
+    }
+
+    pub fn iteration_duration(&self) -> i32 {
+        self.commands_end_time() - self.commands_start_time()
+    }
+
+    pub fn iterations(&self) -> i32 {
+        self.loop_count.max(1)
+    }
+
+    pub fn start_time(&self) -> i32 {
+        self.start_time + self.commands_start_time()
+    }
+
+    pub fn end_time(&self) -> i32 {
+        self.start_time() + self.iteration_duration() * self.iterations()
+    }
+
+    // every iteration with absolute times
+    pub fn unrolled_commands(&self) -> Vec<Command> {
+        let duration = self.iteration_duration();
+        (0..self.iterations())
+            .flat_map(|iteration| {
+                let offset = self.start_time + duration * iteration;
+                self.commands.iter().map(move |command| command.offset(offset))
+            })
+            .collect()
+    }
+
+    pub fn to_str(&self) -> String {
+        format!("L,{},{}", self.start_time, self.loop_count)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandTrigger {
+    pub trigger_name: String,
+
+    pub start_time: i32,
+
+    pub end_time: i32,
+
+    pub group_number: Option<i32>,
+
+    // times are relative to when the trigger fires
+    pub commands: Vec<Command>,
+}
+
+impl CommandTrigger {
+    pub fn to_str(&self) -> String {
+        match self.group_number {
+            Some(group) => format!("T,{},{},{},{}", self.trigger_name, self.start_time, self.end_time, group),
+            None => format!("T,{},{},{}", self.trigger_name, self.start_time, self.end_time),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectKind {
+    Sprite,
+    Animation {
+        frame_count: u32,
+        frame_delay: f32,
+        loop_type: LoopType,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoryboardObject {
+    pub kind: ObjectKind,
+
+    pub layer: Layer,
+
+    pub origin: Origin,
+
+    pub filepath: String,
+
+    pub x: f32,
+
+    pub y: f32,
+
+    pub commands: Vec<Command>,
+
+    pub loops: Vec<CommandLoop>,
+
+    pub triggers: Vec<CommandTrigger>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectState {
+    pub position: (f32, f32),
+
+    pub scale: (f32, f32),
+
+    pub rotation: f32,
+
+    pub colour: (u8, u8, u8),
+
+    pub alpha: f32,
+
+    pub flip_horizontal: bool,
+
+    pub flip_vertical: bool,
+
+    pub additive: bool,
+
+    // only set for animations
+    pub frame: Option<u32>,
+}
+
+impl ObjectState {
+    pub fn is_visible(&self) -> bool {
+        self.alpha > 0.0 && self.scale.0 != 0.0 && self.scale.1 != 0.0
+    }
+}
+
+impl FromStr for StoryboardObject {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = line.trim().split(',').map(str::trim).collect();
+        let kind = parts.first().copied().unwrap_or_default();
+        let is_animation = kind == "Animation" || kind == "6";
+
+        if parts.len() < 6 || (is_animation && parts.len() < 8) {
+            return Err(format!("Invalid storyboard object: {}", line));
+        }
+
+        let layer = Layer::from_str(parts[1])?;
+        let origin = Origin::from_str(parts[2])?;
+        let filepath = parts[3].trim_matches('"').to_string();
+        let x = parts[4].parse::<f32>().map_err(|_| format!("Invalid x position: {}", parts[4]))?;
+        let y = parts[5].parse::<f32>().map_err(|_| format!("Invalid y position: {}", parts[5]))?;
+
+        let mut object = Self::sprite(layer, origin, filepath, x, y);
+
+        if is_animation {
+            let frame_count = parts[6].parse::<u32>().map_err(|_| format!("Invalid frame count: {}", parts[6]))?;
+            let frame_delay = parts[7].parse::<f32>().map_err(|_| format!("Invalid frame delay: {}", parts[7]))?;
+            let loop_type = parts.get(8).map_or(Ok(LoopType::LoopForever), |s| LoopType::from_str(s))?;
+            object.kind = ObjectKind::Animation {
+                frame_count,
+                frame_delay,
+                loop_type,
+            };
+        }
+
+        Ok(object)
+    }
+}
+
+impl StoryboardObject {
+    pub fn sprite(layer: Layer, origin: Origin, filepath: String, x: f32, y: f32) -> Self {
+        Self {
+            kind: ObjectKind::Sprite,
+            layer,
+            origin,
+            filepath,
+            x,
+            y,
+            commands: Vec::new(),
+            loops: Vec::new(),
+            triggers: Vec::new(),
+        }
+    }
+
+    pub fn is_animation(&self) -> bool {
+        matches!(self.kind, ObjectKind::Animation { .. })
+    }
+
+    // the regular commands and every loop iteration, triggers only run when something happens in game
+    pub fn all_commands(&self) -> Vec<Command> {
+        let mut commands = self.commands.clone();
+        for command_loop in &self.loops {
+            commands.extend(command_loop.unrolled_commands());
+        }
+        commands.sort_by_key(|command| command.start_time);
+        commands
+    }
+
+    pub fn start_time(&self) -> Option<i32> {
+        let commands = self.commands.iter().map(|c| c.start_time);
+        let loops = self.loops.iter().map(|l| l.start_time());
+        commands.chain(loops).min()
+    }
+
+    pub fn end_time(&self) -> Option<i32> {
+        let commands = self.commands.iter().map(|c| c.end_time);
+        let loops = self.loops.iter().map(|l| l.end_time());
+        commands.chain(loops).max()
+    }
+
+    pub fn is_active_at(&self, time: i32) -> bool {
+        match (self.start_time(), self.end_time()) {
+            (Some(start), Some(end)) => time >= start && time <= end,
+            _ => false,
+        }
+    }
+
+    pub fn frame_at(&self, time: i32) -> Option<u32> {
+        let ObjectKind::Animation { frame_count, frame_delay, loop_type } = self.kind else {
+            return None;
+        };
+
+        if frame_count == 0 {
+            return Some(0);
+        }
+
+        let elapsed = (time - self.start_time().unwrap_or(0)).max(0) as f32;
+        let frame = if frame_delay > 0.0 { (elapsed / frame_delay) as u32 } else { 0 };
+
+        Some(match loop_type {
+            LoopType::LoopForever => frame % frame_count,
+            LoopType::LoopOnce => frame.min(frame_count - 1),
+        })
+    }
+
+    // animations have their frame number before the extension, "sb/anim.png" becomes "sb/anim0.png"
+    pub fn file_at(&self, time: i32) -> String {
+        let Some(frame) = self.frame_at(time) else {
+            return self.filepath.clone();
+        };
+
+        match self.filepath.rfind('.') {
+            Some(dot) => format!("{}{}{}", &self.filepath[..dot], frame, &self.filepath[dot..]),
+            None => format!("{}{}", self.filepath, frame),
+        }
+    }
+
+    pub fn state_at(&self, time: i32) -> ObjectState {
+        let commands = self.all_commands();
+
+        let position_x = value_at(&commands, time, self.x, |values| match values {
+            CommandValues::Move(start, end) => Some((start.0, end.0)),
+            CommandValues::MoveX(start, end) => Some((start, end)),
+            _ => None,
+        });
+        let position_y = value_at(&commands, time, self.y, |values| match values {
+            CommandValues::Move(start, end) => Some((start.1, end.1)),
+            CommandValues::MoveY(start, end) => Some((start, end)),
+            _ => None,
+        });
+        let scale = value_at(&commands, time, 1.0, |values| match values {
+            CommandValues::Scale(start, end) => Some((start, end)),
+            _ => None,
+        });
+        let vector_scale_x = value_at(&commands, time, 1.0, |values| match values {
+            CommandValues::VectorScale(start, end) => Some((start.0, end.0)),
+            _ => None,
+        });
+        let vector_scale_y = value_at(&commands, time, 1.0, |values| match values {
+            CommandValues::VectorScale(start, end) => Some((start.1, end.1)),
+            _ => None,
+        });
+        let rotation = value_at(&commands, time, 0.0, |values| match values {
+            CommandValues::Rotate(start, end) => Some((start, end)),
+            _ => None,
+        });
+        let alpha = value_at(&commands, time, 1.0, |values| match values {
+            CommandValues::Fade(start, end) => Some((start, end)),
+            _ => None,
+        });
+        let colour_channel = |channel: usize| {
+            value_at(&commands, time, 255.0, |values| match values {
+                CommandValues::Colour(start, end) => {
+                    let start = [start.0, start.1, start.2][channel] as f32;
+                    let end = [end.0, end.1, end.2][channel] as f32;
+                    Some((start, end))
+                }
+                _ => None,
+            })
+            .round() as u8
+        };
+
+        // parameters stay on while they run, or forever if they have no duration
+        let parameter_active = |parameter: ParameterType| {
+            commands.iter().any(|command| {
+                command.values == CommandValues::Parameter(parameter)
+                    && time >= command.start_time
+                    && (command.start_time == command.end_time || time <= command.end_time)
+            })
+        };
+
+        ObjectState {
+            position: (position_x, position_y),
+            scale: (scale * vector_scale_x, scale * vector_scale_y),
+            rotation,
+            colour: (colour_channel(0), colour_channel(1), colour_channel(2)),
+            alpha: alpha.clamp(0.0, 1.0),
+            flip_horizontal: parameter_active(ParameterType::FlipHorizontal),
+            flip_vertical: parameter_active(ParameterType::FlipVertical),
+            additive: parameter_active(ParameterType::AdditiveBlending),
+            frame: self.frame_at(time),
+        }
+    }
+
+    pub fn to_str(&self) -> String {
+        let mut result = match self.kind {
+            ObjectKind::Sprite => format!(
+                "Sprite,{},{},\"{}\",{},{}",
+                self.layer.to_str(),
+                self.origin.to_str(),
+                self.filepath,
+                self.x,
+                self.y
+            ),
+            ObjectKind::Animation { frame_count, frame_delay, loop_type } => format!(
+                "Animation,{},{},\"{}\",{},{},{},{},{}",
+                self.layer.to_str(),
+                self.origin.to_str(),
+                self.filepath,
+                self.x,
+                self.y,
+                frame_count,
+                frame_delay,
+                loop_type.to_str()
+            ),
+        };
+
+        for command in &self.commands {
+            result.push_str("\n ");
+            result.push_str(&command.to_str());
+        }
+
+        for command_loop in &self.loops {
+            result.push_str("\n ");
+            result.push_str(&command_loop.to_str());
+            for command in &command_loop.commands {
+                result.push_str("\n  ");
+                result.push_str(&command.to_str());
+            }
+        }
+
+        for trigger in &self.triggers {
+            result.push_str("\n ");
+            result.push_str(&trigger.to_str());
+            for command in &trigger.commands {
+                result.push_str("\n  ");
+                result.push_str(&command.to_str());
+            }
+        }
+
+        result
+    }
+}
+
+// osu! uses the first command's start value before it starts and the last command's end value after it ends
+fn value_at<F>(commands: &[Command], time: i32, default: f32, select: F) -> f32
+where
+    F: Fn(CommandValues) -> Option<(f32, f32)>,
+{
+    let mut value = None;
+
+    for command in commands {
+        let Some((start, end)) = select(command.values) else {
+            continue;
+        };
+
+        if time < command.start_time {
+            if value.is_none() {
+                value = Some(start);
+            }
+            break;
+        }
+
+        let progress = command.progress_at(time) as f32;
+        value = Some(start + (end - start) * progress);
+    }
+
+    value.unwrap_or(default)
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Storyboard {
+    pub variables: Vec<(String, String)>,
+
+    pub objects: Vec<StoryboardObject>,
+
+    // only used for .osb files, samples in .osu files stay in Events
+    pub samples: Vec<Sample>,
+}
+
+impl FromStr for Storyboard {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut variables = Vec::new();
+        let mut events_content = String::new();
+
+        process_bracket_sections(s, |section, content| {
+            match section {
+                "Variables" => variables = parse_variables(content),
+                "Events" => events_content = content.to_string(),
+                _ => {}
+            }
+            Ok(())
+        })?;
+
+        let events = Events::from_str_with_variables(&events_content, &variables)?;
+
+        Ok(Storyboard {
+            variables,
+            objects: events.storyboard.objects,
+            samples: events.samples,
+        })
+    }
+}
+
+impl Storyboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.samples.is_empty()
+    }
+
+    pub fn objects_in_layer(&self, layer: Layer) -> impl Iterator<Item = &StoryboardObject> {
+        self.objects.iter().filter(move |object| object.layer == layer)
+    }
+
+    // objects on screen at the given time in draw order, back to front
+    pub fn visible_objects_at(&self, time: i32) -> Vec<(&StoryboardObject, ObjectState)> {
+        Layer::ALL
+            .iter()
+            .flat_map(|&layer| self.objects_in_layer(layer))
+            .filter(|object| object.is_active_at(time))
+            .map(|object| (object, object.state_at(time)))
+            .filter(|(_, state)| state.is_visible())
+            .collect()
+    }
+
+    pub fn start_time(&self) -> Option<i32> {
+        self.objects.iter().filter_map(|object| object.start_time()).min()
+    }
+
+    pub fn end_time(&self) -> Option<i32> {
+        self.objects.iter().filter_map(|object| object.end_time()).max()
+    }
+
+    // loop and trigger commands are relative so only their start moves
+    pub fn shift_times(&mut self, offset: i32) {
+        for object in &mut self.objects {
+            for command in &mut object.commands {
+                *command = command.offset(offset);
+            }
+            for command_loop in &mut object.loops {
+                command_loop.start_time += offset;
+            }
+            for trigger in &mut object.triggers {
+                trigger.start_time += offset;
+                trigger.end_time += offset;
+            }
+        }
+
+        for sample in &mut self.samples {
+            sample.start_time += offset;
+        }
+    }
+
+    pub fn layer_to_str(&self, layer: Layer) -> String {
+        self.objects_in_layer(layer)
+            .map(|object| object.to_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn to_str(&self) -> String {
+        let mut result = String::new();
+
+        if !self.variables.is_empty() {
+            result.push_str("[Variables]\n");
+            for (name, value) in &self.variables {
+                result.push_str(&format!("{}={}\n", name, value));
+            }
+            result.push('\n');
+        }
+
+        result.push_str("[Events]\n");
+        result.push_str("//Background and Video events\n");
+
+        for (index, layer) in Layer::ALL.iter().enumerate() {
+            result.push_str(&format!("//Storyboard Layer {} ({})\n", index, layer.to_str()));
+            let objects = self.layer_to_str(*layer);
+            if !objects.is_empty() {
+                result.push_str(&objects);
+                result.push('\n');
+            }
+        }
+
+        result.push_str("//Storyboard Sound Samples\n");
+        for sample in &self.samples {
+            result.push_str(&sample.to_str());
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+pub(crate) fn parse_variables(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.trim().split_once('=')?;
+            name.starts_with('$').then(|| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+// longer names go first so $ab isn't replaced by the value of $a
+pub(crate) fn apply_variables(line: &str, variables: &[(String, String)]) -> String {
+    if variables.is_empty() || !line.contains('$') {
+        return line.to_string();
+    }
+
+    let mut sorted: Vec<&(String, String)> = variables.iter().collect();
+    sorted.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+
+    sorted
+        .iter()
+        .fold(line.to_string(), |line, (name, value)| line.replace(name.as_str(), value))
+}
+
+enum CommandGroup {
+    Loop,
+    Trigger,
+}
+
+// reads [Events] line by line, commands are nested under objects with spaces or underscores
+#[derive(Default)]
+pub(crate) struct StoryboardReader {
+    storyboard: Storyboard,
+    in_object: bool,
+    group: Option<CommandGroup>,
+}
+
+impl StoryboardReader {
+    // returns false when the line isn't part of the storyboard
+    pub(crate) fn read_line(&mut self, line: &str) -> Result<bool, String> {
+        let depth = line.chars().take_while(|c| *c == ' ' || *c == '_').count();
+        let content = line[depth..].trim();
+
+        if depth == 0 {
+            self.group = None;
+            let kind = content.split(',').next().unwrap_or_default().trim();
+
+            if matches!(kind, "Sprite" | "Animation" | "4" | "6") {
+                // the commands under an object that can't be read go with it
+                self.in_object = false;
+                self.storyboard.objects.push(StoryboardObject::from_str(content)?);
+                self.in_object = true;
+                return Ok(true);
+            }
+
+            self.in_object = false;
+            return Ok(false);
+        }
+
+        let Some(object) = self.storyboard.objects.last_mut().filter(|_| self.in_object) else {
+            // commands without an object are ignored by osu! too
+            return Ok(true);
+        };
+
+        let parts: Vec<&str> = content.split(',').map(str::trim).collect();
+
+        if depth == 1 {
+            self.group = None;
+
+            match parts[0] {
+                "L" => {
+                    let start_time = parts.get(1).and_then(|s| s.parse::<f32>().ok()).unwrap_or(0.0) as i32;
+                    let loop_count = parts.get(2).and_then(|s| s.parse::<i32>().ok()).unwrap_or(1);
+                    object.loops.push(CommandLoop {
+                        start_time,
+                        loop_count,
+                        commands: Vec::new(),
+                    });
+                    self.group = Some(CommandGroup::Loop);
+                }
+                "T" => {
+                    let trigger_name = parts.get(1).copied().unwrap_or_default().to_string();
+                    let start_time = parts.get(2).and_then(|s| s.parse::<f32>().ok()).unwrap_or(0.0) as i32;
+                    let end_time = parts.get(3).and_then(|s| s.parse::<f32>().ok()).unwrap_or(0.0) as i32;
+                    let group_number = parts.get(4).and_then(|s| s.parse::<i32>().ok());
+                    object.triggers.push(CommandTrigger {
+                        trigger_name,
+                        start_time,
+                        end_time,
+                        group_number,
+                        commands: Vec::new(),
+                    });
+                    self.group = Some(CommandGroup::Trigger);
+                }
+                _ => object.commands.extend(Command::parse_line(content)?),
+            }
+
+            return Ok(true);
+        }
+
+        let commands = Command::parse_line(content)?;
+        match self.group {
+            Some(CommandGroup::Loop) => {
+                if let Some(command_loop) = object.loops.last_mut() {
+                    command_loop.commands.extend(commands);
+                }
+            }
+            Some(CommandGroup::Trigger) => {
+                if let Some(trigger) = object.triggers.last_mut() {
+                    trigger.commands.extend(commands);
+                }
+            }
+            None => object.commands.extend(commands),
+        }
+
+        Ok(true)
+    }
+
+    pub(crate) fn finish(self) -> Storyboard {
+        self.storyboard
+    }
+}
@@ -51,6 +51,10 @@ impl TimingPoint {
         }
     }
     
+    pub fn is_kiai(&self) -> bool {
+        (self.effects & 1) != 0
+    }
+    
     pub fn slider_velocity_multiplier(&self) -> Option<f32> {
         if self.is_inherited() && self.beat_length < 0.0 {
             Some(-100.0 / self.beat_length)
@@ -74,6 +78,25 @@ impl TimingPoint {
     }
 }
 
+impl TimingPoint {
+    // the uninherited flag and effects were added in v6
+    pub fn to_str_version(&self, version: u8) -> String {
+        if version >= 6 {
+            return self.to_str();
+        }
+        
+        format!(
+            "{},{},{},{},{},{}",
+            self.time,
+            self.beat_length,
+            self.meter,
+            self.sample_set,
+            self.sample_index,
+            self.volume
+        )
+    }
+}
+
 impl Default for TimingPoint {
     fn default() -> Self {
         TimingPoint { time: 0.0, beat_length: 500.0, meter: 4, sample_set: 0, sample_index: 0, volume: 100, uninherited: true, effects: 0 }
@@ -84,36 +107,36 @@ impl FromStr for TimingPoint {
     type Err = String;
     
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(',').collect();
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
         
-        if parts.len() != 8 {
-            return Err(format!("Expected 8 comma-separated values, found {}", parts.len()));
+        // v3 to v5 files only have some of the columns
+        if parts.len() < 2 {
+            return Err(format!("Expected at least 2 comma-separated values, found {}", parts.len()));
         }
         
+        let column = |i: usize, name: &str, default: i32| -> Result<i32, String> {
+            match parts.get(i) {
+                Some(value) if !value.is_empty() => value.parse::<i32>()
+                    .map_err(|_| format!("Invalid {} value: {}", name, value)),
+                _ => Ok(default),
+            }
+        };
+        
         let time = parts[0].parse::<f32>()
             .map_err(|_| format!("Invalid time value: {}", parts[0]))?;
             
         let beat_length = parts[1].parse::<f32>()
             .map_err(|_| format!("Invalid beatLength value: {}", parts[1]))?;
             
-        let meter = parts[2].parse::<i32>()
-            .map_err(|_| format!("Invalid meter value: {}", parts[2]))?;
-            
-        let sample_set = parts[3].parse::<i32>()
-            .map_err(|_| format!("Invalid sampleSet value: {}", parts[3]))?;
-            
-        let sample_index = parts[4].parse::<i32>()
-            .map_err(|_| format!("Invalid sampleIndex value: {}", parts[4]))?;
-            
-        let volume = parts[5].parse::<i32>()
-            .map_err(|_| format!("Invalid volume value: {}", parts[5]))?;
-            
-        let uninherited_val = parts[6].parse::<i32>()
-            .map_err(|_| format!("Invalid uninherited value: {}", parts[6]))?;
-        let uninherited = uninherited_val != 0;
+        let meter = column(2, "meter", 4)?;
+        let sample_set = column(3, "sampleSet", 0)?;
+        let sample_index = column(4, "sampleIndex", 0)?;
+        let volume = column(5, "volume", 100)?;
         
-        let effects = parts[7].parse::<i32>()
-            .map_err(|_| format!("Invalid effects value: {}", parts[7]))?;
+        // without the inherited flag osu! goes by the sign of the beat length
+        let uninherited = column(6, "uninherited", (beat_length >= 0.0) as i32)? != 0;
+        
+        let effects = column(7, "effects", 0)?;
         
         Ok(TimingPoint {
             time,
@@ -222,6 +245,72 @@ impl TimingPoints {
             .collect()
     }
     
+    pub fn beat_length_at(&self, time: f32) -> f32 {
+        self.timing_points
+            .iter()
+            .filter(|tp| tp.is_uninherited() && tp.beat_length > 0.0)
+            .take_while(|tp| tp.time <= time)
+            .last()
+            .or_else(|| self.timing_points.iter().find(|tp| tp.is_uninherited() && tp.beat_length > 0.0))
+            .map(|tp| tp.beat_length)
+            .unwrap_or(500.0)
+    }
+    
+    pub fn slider_velocity_at(&self, time: f32) -> f32 {
+        let mut sv = 1.0;
+        
+        for tp in self.timing_points.iter().take_while(|tp| tp.time <= time) {
+            if tp.is_uninherited() {
+                sv = 1.0;
+            } else if let Some(multiplier) = tp.slider_velocity_multiplier() {
+                sv = multiplier;
+            }
+        }
+        
+        sv
+    }
+    
+    // osu! looks a few ms ahead so a sample change placed right on an object applies to it
+    pub fn sample_point_at(&self, time: f32) -> Option<&TimingPoint> {
+        const SAMPLE_POINT_LENIENCY: f32 = 5.0;
+        
+        self.timing_points
+            .iter()
+            .take_while(|tp| tp.time <= time + SAMPLE_POINT_LENIENCY)
+            .last()
+            .or_else(|| self.timing_points.first())
+    }
+    
+    pub fn is_kiai_at(&self, time: f32) -> bool {
+        self.timing_points
+            .iter()
+            .take_while(|tp| tp.time <= time)
+            .last()
+            .is_some_and(|tp| tp.is_kiai())
+    }
+    
+    pub fn kiai_sections(&self) -> Vec<(f32, f32)> {
+        let mut sections = Vec::new();
+        let mut kiai_start = None;
+        
+        for tp in &self.timing_points {
+            match (tp.is_kiai(), kiai_start) {
+                (true, None) => kiai_start = Some(tp.time),
+                (false, Some(start)) => {
+                    sections.push((start, tp.time));
+                    kiai_start = None;
+                }
+                _ => {}
+            }
+        }
+        
+        if let Some(start) = kiai_start {
+            sections.push((start, f32::MAX));
+        }
+        
+        sections
+    }
+    
     pub fn to_str(&self) -> String {
         self.timing_points
             .iter()
@@ -229,4 +318,12 @@ impl TimingPoints {
             .collect::<Vec<_>>()
             .join("\n")
     }
+    
+    pub fn to_str_version(&self, version: u8) -> String {
+        self.timing_points
+            .iter()
+            .map(|tp| tp.to_str_version(version))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
\ No newline at end of file
@@ -8,6 +8,26 @@ pub struct TimingPoint {
     
     #[serde(rename = "Bpm")]
     pub bpm: f32,
+
+    #[serde(rename = "Signature", default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<TimeSignature>,
+}
+
+// quaver only knows 4/4 and 3/4, 4/4 is left out of the file
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+pub enum TimeSignature {
+    #[default]
+    Quadruple,
+    Triple,
+}
+
+impl TimeSignature {
+    pub fn beats_per_measure(&self) -> u32 {
+        match self {
+            TimeSignature::Quadruple => 4,
+            TimeSignature::Triple => 3,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -3,13 +3,12 @@ use std::ops::{Index, IndexMut};
 use crate::models::common::*;
 use crate::models::generic::HitObject;
 use crate::models::generic::KeySound;
-use crate::models::generic::{TimingPoints, TimingChange};
-use crate::utils::rhythm::calculate_beat_from_time;
+use crate::models::generic::{TimingMap, TimingPoint, TimingPoints, TimingChange};
 
 #[derive(Debug, Clone)]
 #[repr(C)]
 pub struct TimelineTimingPoint {
-    pub time: i32,
+    pub time: f64,
     pub value: f32,
     pub group: String,
     pub change_type: TimingChangeType,
@@ -20,7 +19,7 @@ pub trait TimelineOps<Item> {
     fn timeline_mut(&mut self) -> &mut Vec<Item>;
     fn is_sorted(&self) -> bool;
     fn set_sorted(&mut self, sorted: bool);
-    fn item_time(item: &Item) -> i32;
+    fn item_time(item: &Item) -> f64;
 
     fn with_capacity(capacity: usize) -> Self where Self: Sized;
 
@@ -67,7 +66,7 @@ pub trait TimelineOps<Item> {
         }
 
         let pos = self.timeline().binary_search_by(|obj| 
-            Self::item_time(obj).cmp(&Self::item_time(&timeline_object))
+            Self::item_time(obj).total_cmp(&Self::item_time(&timeline_object))
         ).unwrap_or_else(|pos| pos);
         
         self.timeline_mut().insert(pos, timeline_object);
@@ -76,7 +75,7 @@ pub trait TimelineOps<Item> {
     #[inline]
     fn sort(&mut self) {
         if !self.is_sorted() {
-            self.timeline_mut().sort_unstable_by(|a, b| Self::item_time(a).cmp(&Self::item_time(b)));
+            self.timeline_mut().sort_unstable_by(|a, b| Self::item_time(a).total_cmp(&Self::item_time(b)));
             self.set_sorted(true);
         }
     }
@@ -99,7 +98,8 @@ impl HitObjectTimeline {
         while i < hitobjects.len() {            
             while i < hitobjects.len() && hitobjects[i].time == current_time {
                 let obj = &hitobjects[i];
-                let lane = obj.lane as usize;
+                // generic lanes start at 1
+                let lane = (obj.lane as usize).wrapping_sub(1);
                 
                 if lane < key_count {
                     match obj.key.key_type {
@@ -252,7 +252,7 @@ impl TimelineOps<TimelineTimingPoint> for TimingPointTimeline {
     }
 
     #[inline]
-    fn item_time(item: &TimelineTimingPoint) -> i32 {
+    fn item_time(item: &TimelineTimingPoint) -> f64 {
         item.time
     }
 
@@ -274,41 +274,33 @@ impl TimelineOps<TimelineTimingPoint> for TimingPointTimeline {
 }
 
 impl TimingPointTimeline {
-    pub fn to_timing_points(&mut self, timing_points: &mut TimingPoints, offset: i32) {
+    pub fn to_timing_points(&mut self, timing_points: &mut TimingPoints) {
         if self.timeline.is_empty() {
             return;
         }
 
         self.sort();
-        
-        let mut bpm_times = Vec::new();
-        let mut bpms = Vec::new();
-        
-        for timing_point in &self.timeline {
-            match timing_point.change_type {
-                TimingChangeType::Bpm => {
-                    bpm_times.push(timing_point.time);
-                    bpms.push(timing_point.value);
-                }
-                _ => {}
-            }
-        }
-        
-        for timing_point in &self.timeline {
-            let time = timing_point.time;
-            let beat = calculate_beat_from_time(time, offset, (&bpm_times, &bpms));
-            let group = &timing_point.group;
-            
-            timing_points.add(
-                time,
-                beat,
-                group.clone(),
+
+        let points: TimingPoints = self.timeline
+            .iter()
+            .map(|timing_point| TimingPoint::new(
+                timing_point.time,
+                Beat::ZERO,
+                timing_point.group.clone(),
                 TimingChange {
                     value: timing_point.value,
                     change_type: timing_point.change_type,
-                }
-            );
-        }
+                },
+            ))
+            .collect::<Vec<_>>()
+            .into();
+
+        // beats only depend on the bpms, so scroll settings and the end time don't matter here
+        let timing_map = TimingMap::new(&points, false, 0.0);
+        timing_points.points.extend(points.points.into_iter().map(|mut point| {
+            point.beat = timing_map.snapped_beat_at(point.time);
+            point
+        }));
     }
 }
 
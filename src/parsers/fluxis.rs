@@ -2,19 +2,18 @@ use crate::models::common::*;
 use crate::models::fluxis::{self, FscFile};
 use crate::models::generic::{
     GenericManiaChart, ChartInfo, HitObject, HitObjects, 
-    KeySound, Metadata, TimingPoints
+    KeySound, Metadata, TimeSignature, TimingEffects, TimingMap, TimingPoints
 };
 use crate::models::timeline::{TimelineOps, TimelineTimingPoint, TimingPointTimeline};
-use crate::utils::rhythm::calculate_beat_from_time;
 
 fn process_timing_points(
-    timing_points: Vec<fluxis::TimingPoint>,
+    timing_points: &[fluxis::TimingPoint],
     chartinfo: &mut ChartInfo,
     timeline: &mut TimingPointTimeline,
 ) -> Result<(), Box<dyn std::error::Error>> {
     for timing_point in timing_points {
         timeline.add_sorted(TimelineTimingPoint {
-            time: timing_point.time as i32,
+            time: timing_point.time as f64,
             value: timing_point.bpm,
             group: String::new(),
             change_type: TimingChangeType::Bpm,
@@ -22,7 +21,7 @@ fn process_timing_points(
     }
 
     let start_time = if timeline.is_empty() {
-        0
+        0.0
     } else {
         timeline[0].time
     };
@@ -31,15 +30,51 @@ fn process_timing_points(
     Ok(())
 }
 
+// fluXis signatures count quarter notes, they go in after the bpms so their beats can be worked out
+fn process_signatures(
+    fsc_timing_points: &[fluxis::TimingPoint],
+    timing_points: &mut TimingPoints,
+    timing_map: &TimingMap,
+) {
+    let mut signature = 4;
+
+    for timing_point in fsc_timing_points {
+        if timing_point.signature == 0 || timing_point.signature == signature {
+            continue;
+        }
+        signature = timing_point.signature;
+
+        let time = timing_point.time as f64;
+        let beat = timing_map.snapped_beat_at(time);
+        timing_points.insert_time_signature(time, beat, TimeSignature::new(signature, 4));
+    }
+}
+
 fn add_sv(timeline: &mut TimingPointTimeline, sv: &fluxis::ScrollVelocity, group: &str) {
     timeline.add_sorted(TimelineTimingPoint {
-        time: sv.time as i32,
+        time: sv.time as f64,
         value: sv.multiplier,
         group: group.to_string(),
         change_type: TimingChangeType::Sv,
     });
 }
 
+fn process_hidden_lines(fsc_timing_points: &[fluxis::TimingPoint], timing_points: &mut TimingPoints) {
+    for timing_point in fsc_timing_points.iter().filter(|tp| tp.hide_lines) {
+        let time = timing_point.time as f64;
+        let bpm_point = timing_points
+            .iter_mut()
+            .find(|p| p.time == time && p.change.change_type == TimingChangeType::Bpm);
+
+        if let Some(point) = bpm_point {
+            point.effects = Some(TimingEffects {
+                hide_barlines: true,
+                ..point.effects.unwrap_or_default()
+            });
+        }
+    }
+}
+
 fn process_sv(
     slider_velocities: Vec<fluxis::ScrollVelocity>,
     timeline: &mut TimingPointTimeline,
@@ -101,19 +136,25 @@ fn process_sv(
     Ok(())
 }
 
+// the time the last note ends, picks how long the last bpm lasts
+fn last_object_time(fluxis_hitobjects: &[fluxis::HitObject]) -> f64 {
+    fluxis_hitobjects
+        .iter()
+        .map(|hitobject| (hitobject.time + hitobject.holdtime.max(0.0)) as f64)
+        .fold(0.0, f64::max)
+}
+
 fn process_notes(
     fluxis_hitobjects: Vec<fluxis::HitObject>,
     hitobjects: &mut HitObjects,
     chartinfo: &mut ChartInfo,
-    offset: i32,
-    bpms_times: &Vec<i32>,
-    bpms: &Vec<f32>,
+    timing_map: &TimingMap,
     use_column_as_group: bool
 ) -> Result<(), Box<dyn std::error::Error>> {
     let key_count = chartinfo.key_count as usize;
 
     for hitobject in fluxis_hitobjects {
-        let beat = calculate_beat_from_time(hitobject.time as i32, offset, (bpms_times, bpms));
+        let beat = timing_map.snapped_beat_at(hitobject.time as f64);
 
         let group = match &hitobject.group {
             Some(group) if !group.is_empty() => Some(group.clone()),
@@ -125,17 +166,19 @@ fn process_notes(
             fluxis::FluXisHitType::NormalOrHold => {
                 if hitobject.is_ln() {
                     let slider = HitObject {
-                        time: hitobject.time as i32,
+                        time: hitobject.time as f64,
                         beat,
                         lane: hitobject.lane as u8,
-                        key: Key::slider_start(Some(hitobject.end_time() as i32)),
+                        key: Key::slider_start(Some(hitobject.end_time() as f64)),
                         keysound: KeySound::default(),
                         group: group.clone(),
                     };
 
+                    let end_time_beat = timing_map.snapped_beat_at(hitobject.end_time() as f64);
+
                     let slider_end = HitObject {
-                        time: hitobject.end_time() as i32,
-                        beat,
+                        time: hitobject.end_time() as f64,
+                        beat: end_time_beat,
                         lane: hitobject.lane as u8,
                         key: Key::slider_end(),
                         keysound: KeySound::default(),
@@ -146,7 +189,7 @@ fn process_notes(
                     hitobjects.add_hitobject_sorted(slider_end);
                 } else {
                     hitobjects.add_hitobject_sorted(HitObject {
-                        time: hitobject.time as i32,
+                        time: hitobject.time as f64,
                         beat,
                         lane: hitobject.lane as u8,
                         key: Key::normal(),
@@ -156,10 +199,10 @@ fn process_notes(
                 }
             },
             fluxis::FluXisHitType::Landmine => {
-                let endtime = hitobject.is_ln().then(|| hitobject.end_time() as i32);
+                let endtime = hitobject.is_ln().then(|| hitobject.end_time() as f64);
 
                 hitobjects.add_hitobject_sorted(HitObject {
-                        time: hitobject.time as i32,
+                        time: hitobject.time as f64,
                         beat,
                         lane: hitobject.lane as u8,
                         key: Key::mine(endtime),
@@ -169,7 +212,7 @@ fn process_notes(
             },
             fluxis::FluXisHitType::Tick => {
                 hitobjects.add_hitobject_sorted(HitObject {
-                        time: hitobject.time as i32,
+                        time: hitobject.time as f64,
                         beat,
                         lane: hitobject.lane as u8,
                         key: Key::tick(),
@@ -194,6 +237,7 @@ pub(crate) fn from_fsc_generic(
     let fsc_file = FscFile::from_str(&raw_chart)?;
 
     let key_count = fsc_file.key_count();
+    let colors = Some(fsc_file.colors.to_generic()).filter(|c| !c.is_empty());
 
     let metadata = Metadata {
         title: fsc_file.metadata.title.clone(),
@@ -231,18 +275,17 @@ pub(crate) fn from_fsc_generic(
     let mut hitobjects = HitObjects::with_capacity(2048);
     let mut timeline = TimingPointTimeline::with_capacity(64);
 
-    let offset = fsc_file.timing_points[0].time as i32;
-
-    process_timing_points(fsc_file.timing_points, &mut chartinfo, &mut timeline)?;
+    process_timing_points(&fsc_file.timing_points, &mut chartinfo, &mut timeline)?;
     process_sv(fsc_file.scroll_velocities, &mut timeline, process_single_column_scroll)?;
-    timeline.to_timing_points(&mut timing_points, chartinfo.audio_offset);
+    timeline.to_timing_points(&mut timing_points);
+    let timing_map = TimingMap::new(&timing_points, chartinfo.bpm_affects_sv, last_object_time(&fsc_file.hit_objects));
+    process_signatures(&fsc_file.timing_points, &mut timing_points, &timing_map);
+    process_hidden_lines(&fsc_file.timing_points, &mut timing_points);
     process_notes(
         fsc_file.hit_objects,
         &mut hitobjects,
         &mut chartinfo,
-        offset,
-        &timing_points.bpms_times(),
-        &timing_points.bpms(),
+        &timing_map,
         process_single_column_scroll
     )?;
 
@@ -252,5 +295,6 @@ pub(crate) fn from_fsc_generic(
         timing_points,
         hitobjects,
         None,
+        colors,
     ))
 }
@@ -5,12 +5,26 @@ use crate::models::common::*;
 use crate::models::generic::{
     self,
     GenericManiaChart,
+    GenericTaikoChart,
+    GenericCatchChart,
+    CatchHitObject,
+    CatchHitObjects,
+    CATCH_PLAYFIELD_WIDTH,
     ChartInfo,
+    GogoSection,
     SoundBank,
-    TimingChange
+    TaikoCourse,
+    TaikoHitObject,
+    TaikoHitObjects,
+    TimingChange,
+    SampleContext,
+    TimingEffects,
+    TimingMap,
+    TimeSignature,
 };
-use crate::models::osu::{self, *};
-use crate::utils::rhythm::calculate_beat_from_time;
+use crate::models::osu::{self, storyboard, *};
+use crate::parsers::osu_convert;
+use crate::utils::random::LegacyRandom;
 use crate::utils::serde::process_bracket_sections;
 
 fn validate_mode_mania(mode: GameMode) -> Result<bool, Box<dyn std::error::Error>> {
@@ -23,13 +37,46 @@ fn validate_mode_mania(mode: GameMode) -> Result<bool, Box<dyn std::error::Error
     Ok(true)
 }
 
+fn validate_mode_taiko(mode: GameMode) -> Result<bool, Box<dyn std::error::Error>> {
+    if mode != GameMode::Taiko {
+        return Err(Box::new(errors::ParseError::InvalidMode(
+            mode.to_string(),
+            GameMode::Taiko,
+        )));
+    }
+    Ok(true)
+}
+
+// osu!catch can play standard maps as well
+fn validate_mode_standard(mode: GameMode) -> Result<bool, Box<dyn std::error::Error>> {
+    if mode != GameMode::OsuStandard {
+        return Err(Box::new(errors::ParseError::InvalidMode(
+            mode.to_string(),
+            GameMode::OsuStandard,
+        )));
+    }
+    Ok(true)
+}
+
+fn validate_mode_catch(mode: GameMode) -> Result<bool, Box<dyn std::error::Error>> {
+    if mode != GameMode::Catch && mode != GameMode::OsuStandard {
+        return Err(Box::new(errors::ParseError::InvalidMode(
+            mode.to_string(),
+            GameMode::Catch,
+        )));
+    }
+    Ok(true)
+}
+
 pub(crate) fn from_osu(raw_chart: &str) -> Result<OsuFile, Box<dyn std::error::Error>> {
     let mut general: osu::General = Default::default();
     let mut editor: osu::Editor = Default::default();
     let mut metadata: osu::Metadata = Default::default();
     let mut difficulty: osu::Difficulty = Default::default();
-    let mut events: osu::Events = Default::default();
+    let mut events_content = String::new();
+    let mut variables = Vec::new();
     let mut timing_points: osu::TimingPoints = Default::default();
+    let mut colours: Option<osu::Colours> = None;
     let mut hitobjects: osu::HitObjects = Default::default();
 
     process_bracket_sections(raw_chart, |section, content| {
@@ -42,10 +89,14 @@ pub(crate) fn from_osu(raw_chart: &str) -> Result<OsuFile, Box<dyn std::error::E
 
             "Difficulty" => difficulty = osu::Difficulty::from_str(content)?,
 
-            "Events" => events = osu::Events::from_str(content)?,
+            "Events" => events_content = content.to_string(),
+
+            "Variables" => variables = storyboard::parse_variables(content),
 
             "TimingPoints" => timing_points = osu::TimingPoints::from_str(content)?,
 
+            "Colours" => colours = Some(osu::Colours::from_str(content)?),
+
             "HitObjects" => {
                 hitobjects = osu::HitObjects::from_str_with_mode(content, &OsuMode::Mania)?
             }
@@ -55,41 +106,49 @@ pub(crate) fn from_osu(raw_chart: &str) -> Result<OsuFile, Box<dyn std::error::E
         Ok(())
     })?;
 
-    let osu_file = OsuFile {
+    // variables can come after the events that use them
+    let events = osu::Events::from_str_with_variables(&events_content, &variables)?;
+
+    let version = osu::format_version_from_str(raw_chart).unwrap_or(osu::LATEST_FORMAT_VERSION);
+
+    let mut osu_file = OsuFile {
+        version,
         general,
         editor: Some(editor),
         metadata,
         difficulty,
         events,
         timing_points,
+        colours,
         hitobjects,
     };
 
+    let offset = osu::timing_offset_for_version(version);
+    if offset != 0.0 {
+        osu_file.shift_times(offset);
+    }
+
     Ok(osu_file)
 }
 
-pub(crate) fn from_osu_generic(
-    raw_chart: &str,
-) -> Result<GenericManiaChart, Box<dyn std::error::Error>> {
-    let osu_file = from_osu(raw_chart)?;
-
-    validate_mode_mania(osu_file.general.get_mode())?;
-
-    let metadata = generic::Metadata {
+fn process_metadata(osu_file: &OsuFile) -> generic::Metadata {
+    generic::Metadata {
         title: osu_file.metadata.title.clone(),
         alt_title: osu_file.metadata.display_title().to_string(),
         artist: osu_file.metadata.artist.clone(),
         alt_artist: osu_file.metadata.display_artist().to_string(),
-        creator: osu_file.metadata.creator,
-        tags: osu_file.metadata.tags,
-        source: osu_file.metadata.source,
+        creator: osu_file.metadata.creator.clone(),
+        tags: osu_file.metadata.tags.clone(),
+        source: osu_file.metadata.source.clone(),
         ..generic::Metadata::empty()
-    };
+    }
+}
 
+fn process_chartinfo(osu_file: &OsuFile) -> ChartInfo {
     let mut chartinfo = ChartInfo {
-        song_path: osu_file.general.audio_filename,
+        song_path: osu_file.general.audio_filename.clone(),
         preview_time: osu_file.general.preview_time,
-        difficulty_name: osu_file.metadata.version,
+        difficulty_name: osu_file.metadata.version.clone(),
         od: osu_file.difficulty.overall_difficulty,
         hp: osu_file.difficulty.hp_drain_rate,
         key_count: osu_file.difficulty.circle_size as u8,
@@ -105,60 +164,146 @@ pub(crate) fn from_osu_generic(
         chartinfo.video_path = video.filename.clone();
     }
 
+    chartinfo
+}
+
+// the time the last object ends, picks how long the last bpm lasts
+fn last_object_time(osu_file: &OsuFile) -> f64 {
+    osu_file
+        .hitobjects
+        .iter()
+        .map(|hit_object| {
+            let end_time = hit_object
+                .slider_end_time(&osu_file.timing_points, &osu_file.difficulty)
+                .or_else(|| hit_object.end_time())
+                .map_or(hit_object.time as f64, |end_time| end_time as f64);
+            end_time.max(hit_object.time as f64)
+        })
+        .fold(0.0, f64::max)
+}
+
+fn process_timing_points(osu_file: &OsuFile, chartinfo: &mut ChartInfo) -> generic::TimingPoints {
     let mut timing_points = generic::TimingPoints::with_capacity(osu_file.timing_points.count());
+    let mut meter = 4;
 
     for tp in &osu_file.timing_points.timing_points {
-        if tp.is_uninherited() {
-            let bpm = tp.bpm().unwrap_or(120.0);
-            timing_points.add(
-                tp.time as i32,
-                0.0,
-                String::new(),
-                TimingChange {
-                    change_type: TimingChangeType::Bpm,
-                    value: bpm,
-                },
-            );
+        let change = if tp.is_uninherited() {
+            TimingChange {
+                change_type: TimingChangeType::Bpm,
+                value: tp.bpm().unwrap_or(120.0),
+            }
         } else {
-            let sv = tp.slider_velocity_multiplier().unwrap_or(1.0);
-            timing_points.add(
-                tp.time as i32,
-                0.0,
-                String::new(),
-                TimingChange {
-                    change_type: TimingChangeType::Sv,
-                    value: sv,
-                },
-            );
+            TimingChange {
+                change_type: TimingChangeType::Sv,
+                value: tp.slider_velocity_multiplier().unwrap_or(1.0),
+            }
+        };
+
+        let sample = SampleContext {
+            sample_set: tp.sample_set.clamp(0, 3) as u8,
+            sample_index: tp.sample_index.max(0) as u32,
+            volume: tp.volume.clamp(0, 100) as u8,
+        };
+
+        timing_points.push(
+            generic::TimingPoint::new(tp.time as f64, Beat::ZERO, String::new(), change)
+                .with_sample(sample)
+                .with_effects(TimingEffects::from_osu_bits(tp.effects)),
+        );
+
+        // the meter only counts on uninherited points and is always in quarter notes
+        if tp.is_uninherited() && tp.meter > 0 && tp.meter != meter {
+            meter = tp.meter;
+            timing_points.push(generic::TimingPoint::time_signature(
+                tp.time as f64,
+                Beat::ZERO,
+                TimeSignature::new(meter as u32, 4),
+            ));
         }
     }
 
-    let start_time = *timing_points.bpms_times().first().unwrap_or(&0);
+    let start_time = *timing_points.bpms_times().first().unwrap_or(&0.0);
     chartinfo.audio_offset = start_time;
 
-    let bpm_times: Vec<i32> = timing_points.bpms_times();
-    let bpms: Vec<f32> = timing_points.bpms();
-
+    let timing_map = TimingMap::new(&timing_points, chartinfo.bpm_affects_sv, last_object_time(osu_file));
     timing_points.iter_mut().for_each(|b| {
-        b.beat = calculate_beat_from_time(b.time, start_time, (&bpm_times, &bpms));
+        b.beat = timing_map.snapped_beat_at(b.time);
     });
 
+    timing_points
+}
+
+pub(crate) fn from_osb(raw_storyboard: &str) -> Result<Storyboard, Box<dyn std::error::Error>> {
+    Storyboard::from_str(raw_storyboard)
+}
+
+pub(crate) fn from_osu_generic(
+    raw_chart: &str,
+) -> Result<GenericManiaChart, Box<dyn std::error::Error>> {
+    let osu_file = from_osu(raw_chart)?;
+
+    validate_mode_mania(osu_file.general.get_mode())?;
+
+    Ok(process_mania_chart(&osu_file))
+}
+
+pub(crate) fn from_osu_convert_to_mania(
+    raw_chart: &str,
+    key_count: Option<u8>,
+) -> Result<GenericManiaChart, Box<dyn std::error::Error>> {
+    let osu_file = from_osu(raw_chart)?;
+
+    validate_mode_standard(osu_file.general.get_mode())?;
+
+    let key_count = match key_count {
+        Some(key_count) if !(1..=10).contains(&key_count) => {
+            return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart(
+                format!("Can't convert to {}K, only 1K to 10K are supported", key_count),
+            )));
+        }
+        Some(key_count) => key_count,
+        None => osu_convert::key_count_for(&osu_file),
+    };
+
+    let hitobjects = osu_convert::convert_to_mania(&osu_file, key_count);
+    let osu_file = OsuFile {
+        general: General { mode: 3, ..osu_file.general },
+        difficulty: Difficulty { circle_size: key_count as f32, ..osu_file.difficulty },
+        hitobjects,
+        ..osu_file
+    };
+
+    Ok(process_mania_chart(&osu_file))
+}
+
+fn process_mania_chart(osu_file: &OsuFile) -> GenericManiaChart {
+    let metadata = process_metadata(osu_file);
+    let mut chartinfo = process_chartinfo(osu_file);
+    let timing_points = process_timing_points(osu_file, &mut chartinfo);
+
+    let timing_map = TimingMap::new(&timing_points, chartinfo.bpm_affects_sv, last_object_time(osu_file));
+
     let mut hitobjects = generic::HitObjects::with_capacity(osu_file.hitobjects.count());
     let mut soundbank = SoundBank::new();
     soundbank.audio_tracks.push(chartinfo.song_path.clone());
 
     let key_count = chartinfo.key_count;
+    let colors = osu_file.colours.as_ref().map(|c| c.to_generic());
 
     for hit_object in osu_file.hitobjects.iter() {
-        let object_time = hit_object.time as i32;
-        let object_column = hit_object.mania_column(key_count);
+        let object_time = hit_object.time as f64;
+        let object_column = hit_object.mania_lane(key_count);
 
-        let beat = calculate_beat_from_time(object_time, start_time, (&bpm_times, &bpms));
+        let beat = timing_map.snapped_beat_at(object_time);
 
-        let key_sound = hit_object.get_generic_keysound(&mut soundbank);
+        let key_sound = hit_object.resolve_keysound(
+            &mut soundbank,
+            osu_file.timing_points.sample_point_at(hit_object.time),
+            &osu_file.general.sample_set,
+        );
 
         if hit_object.is_hold() {
-            let end_time = hit_object.end_time().unwrap_or(object_time);
+            let end_time = hit_object.end_time().map_or(object_time, |end_time| end_time as f64);
 
             let slider_start = generic::HitObject {
                 time: object_time,
@@ -169,7 +314,7 @@ pub(crate) fn from_osu_generic(
                 group: None
             };
 
-            let end_time_beat = calculate_beat_from_time(end_time, start_time, (&bpm_times, &bpms));
+            let end_time_beat = timing_map.snapped_beat_at(end_time);
 
             let slider_end = generic::HitObject {
                 time: end_time,
@@ -194,11 +339,370 @@ pub(crate) fn from_osu_generic(
         }
     }
 
-    Ok(GenericManiaChart::new(
+    GenericManiaChart::new(
         metadata,
         chartinfo,
         timing_points,
         hitobjects,
         Some(soundbank),
+        colors,
+    )
+}
+
+// osu!taiko balloons need a certain amount of hits depending on OD and length
+fn calculate_balloon_hits(duration: f32, overall_difficulty: f32) -> u32 {
+    let od_multiplier = if overall_difficulty > 5.0 {
+        5.0 + (7.5 - 5.0) * (overall_difficulty - 5.0) / 5.0
+    } else {
+        5.0 - (5.0 - 3.0) * (5.0 - overall_difficulty) / 5.0
+    };
+
+    ((duration / 1000.0 * od_multiplier * 1.65) as u32).max(1)
+}
+
+pub(crate) fn from_osu_taiko(
+    raw_chart: &str,
+) -> Result<GenericTaikoChart, Box<dyn std::error::Error>> {
+    let osu_file = from_osu(raw_chart)?;
+
+    validate_mode_taiko(osu_file.general.get_mode())?;
+
+    let metadata = process_metadata(&osu_file);
+    let mut chartinfo = process_chartinfo(&osu_file);
+    chartinfo.key_count = 2;
+    let timing_points = process_timing_points(&osu_file, &mut chartinfo);
+
+    let timing_map = TimingMap::new(&timing_points, chartinfo.bpm_affects_sv, last_object_time(&osu_file));
+
+    let mut hitobjects = TaikoHitObjects::with_capacity(osu_file.hitobjects.count());
+    let mut soundbank = SoundBank::new();
+    soundbank.audio_tracks.push(chartinfo.song_path.clone());
+
+    for hit_object in osu_file.hitobjects.iter() {
+        let time = hit_object.time as f64;
+        let beat = timing_map.snapped_beat_at(time);
+        let keysound = hit_object.resolve_keysound(
+            &mut soundbank,
+            osu_file.timing_points.sample_point_at(hit_object.time),
+            &osu_file.general.sample_set,
+        );
+
+        let mut note = hit_object.to_taiko();
+        let mut balloon_hits = None;
+
+        match note.note_type {
+            TaikoHitobjectType::DrumRoll | TaikoHitobjectType::BonusDrumRoll => {
+                note.end_time = hit_object
+                    .slider_end_time(&osu_file.timing_points, &osu_file.difficulty)
+                    .map(|end_time| end_time as f64)
+                    .or(Some(time));
+            }
+            TaikoHitobjectType::Balloon => {
+                let duration = (note.end_time.unwrap_or(time) - time) as f32;
+                balloon_hits = Some(calculate_balloon_hits(duration, osu_file.difficulty.overall_difficulty));
+            }
+            TaikoHitobjectType::Empty | TaikoHitobjectType::Unknown => continue,
+            _ => {}
+        }
+
+        let end_beat = note
+            .end_time
+            .map(|end_time| timing_map.snapped_beat_at(end_time));
+
+        hitobjects.add_hitobject_sorted(TaikoHitObject {
+            time,
+            beat,
+            keysound,
+            note,
+            end_beat,
+            balloon_hits,
+            branch: None,
+        });
+    }
+
+    let gogo_sections = osu_file
+        .timing_points
+        .kiai_sections()
+        .into_iter()
+        .map(|(start, end)| GogoSection {
+            start_time: start as f64,
+            end_time: if end == f32::MAX { f64::MAX } else { end as f64 },
+        })
+        .collect();
+
+    Ok(GenericTaikoChart::new(
+        metadata,
+        chartinfo,
+        timing_points,
+        hitobjects,
+        Some(soundbank),
+        TaikoCourse::Oni,
+        0,
+        gogo_sections,
+        Vec::new(),
     ))
-}
\ No newline at end of file
+}
+
+const CATCH_RNG_SEED: i32 = 1337;
+const LEGACY_LAST_TICK_OFFSET: f64 = 36.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SliderEventType {
+    Head,
+    Tick,
+    Repeat,
+    LegacyLastTick,
+    Tail,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SliderEvent {
+    event_type: SliderEventType,
+    time: f64,
+    path_progress: f64,
+}
+
+fn slider_events(
+    start_time: f64,
+    span_duration: f64,
+    velocity: f64,
+    tick_distance: f64,
+    total_distance: f64,
+    span_count: i32,
+) -> Vec<SliderEvent> {
+    let length = total_distance.min(100_000.0);
+    let tick_distance = tick_distance.clamp(0.0, length);
+    let min_distance_from_end = velocity * 10.0;
+
+    let mut events = vec![SliderEvent {
+        event_type: SliderEventType::Head,
+        time: start_time,
+        path_progress: 0.0,
+    }];
+
+    for span in 0..span_count {
+        let span_start_time = start_time + span as f64 * span_duration;
+        let reversed = span % 2 == 1;
+
+        let mut ticks = Vec::new();
+        if tick_distance > 0.0 {
+            let mut distance = tick_distance;
+            while distance <= length && distance < length - min_distance_from_end {
+                // ticks are always placed from the start of the path so repeats line up
+                let path_progress = distance / length;
+                let time_progress = if reversed { 1.0 - path_progress } else { path_progress };
+
+                ticks.push(SliderEvent {
+                    event_type: SliderEventType::Tick,
+                    time: span_start_time + time_progress * span_duration,
+                    path_progress,
+                });
+                distance += tick_distance;
+            }
+        }
+
+        if reversed {
+            ticks.reverse();
+        }
+        events.extend(ticks);
+
+        if span < span_count - 1 {
+            events.push(SliderEvent {
+                event_type: SliderEventType::Repeat,
+                time: span_start_time + span_duration,
+                path_progress: ((span + 1) % 2) as f64,
+            });
+        }
+    }
+
+    // osu!stable placed the last tick slightly before the end of the slider
+    let total_duration = span_count as f64 * span_duration;
+    let final_span_start_time = start_time + (span_count - 1) as f64 * span_duration;
+    let legacy_last_tick_time = (start_time + total_duration / 2.0)
+        .max(final_span_start_time + span_duration - LEGACY_LAST_TICK_OFFSET);
+    let mut legacy_last_tick_progress = (legacy_last_tick_time - final_span_start_time) / span_duration;
+    if span_count % 2 == 0 {
+        legacy_last_tick_progress = 1.0 - legacy_last_tick_progress;
+    }
+
+    events.push(SliderEvent {
+        event_type: SliderEventType::LegacyLastTick,
+        time: legacy_last_tick_time,
+        path_progress: legacy_last_tick_progress,
+    });
+
+    events.push(SliderEvent {
+        event_type: SliderEventType::Tail,
+        time: start_time + total_duration,
+        path_progress: (span_count % 2) as f64,
+    });
+
+    events
+}
+
+fn juice_stream_objects(
+    hit_object: &osu::HitObject,
+    osu_file: &OsuFile,
+    rng: &mut LegacyRandom,
+    mut make_object: impl FnMut(f64, f32, CatchHitobject) -> CatchHitObject,
+) -> Vec<CatchHitObject> {
+    let (Some((_, _, span_count, _)), Some(path)) = (hit_object.slider_params(), hit_object.slider_path()) else {
+        return Vec::new();
+    };
+
+    let span_count = span_count.max(1);
+    let length = path.distance();
+    let velocity = hit_object.slider_velocity(&osu_file.timing_points, &osu_file.difficulty) as f64;
+    let tick_distance = (100.0 * osu_file.difficulty.slider_multiplier / osu_file.difficulty.slider_tick_rate) as f64;
+    let span_duration = length / velocity;
+
+    let x_at = |progress: f64| {
+        let (x, _) = path.position_at(progress);
+        x.clamp(0.0, CATCH_PLAYFIELD_WIDTH)
+    };
+
+    let events = slider_events(
+        hit_object.time as f64,
+        span_duration,
+        velocity,
+        tick_distance,
+        length,
+        span_count,
+    );
+
+    let mut objects = Vec::with_capacity(events.len() * 4);
+    let mut last_event: Option<SliderEvent> = None;
+
+    for event in events {
+        if let Some(last) = last_event {
+            let since_last_tick = (event.time as i32 - last.time as i32) as f64;
+
+            if since_last_tick > 80.0 {
+                let mut time_between_tiny = since_last_tick;
+                while time_between_tiny > 100.0 {
+                    time_between_tiny /= 2.0;
+                }
+
+                let mut t = time_between_tiny;
+                while t < since_last_tick {
+                    let progress = last.path_progress + (t / since_last_tick) * (event.path_progress - last.path_progress);
+                    let x = x_at(progress);
+                    let offset = (rng.next_range(-20, 20) as f32).clamp(-x, CATCH_PLAYFIELD_WIDTH - x);
+                    objects.push(make_object(t + last.time, x + offset, CatchHitobject::tiny_droplet(0)));
+                    t += time_between_tiny;
+                }
+            }
+        }
+
+        // the legacy last tick isn't an object, but tiny droplets are still spaced around it
+        last_event = Some(event);
+
+        match event.event_type {
+            SliderEventType::Tick => {
+                // osu!stable rolled a random droplet rotation
+                rng.next();
+                objects.push(make_object(event.time, x_at(event.path_progress), CatchHitobject::juice(0)));
+            }
+            SliderEventType::Head | SliderEventType::Repeat | SliderEventType::Tail => {
+                objects.push(make_object(event.time, x_at(event.path_progress), CatchHitobject::fruit(0)));
+            }
+            SliderEventType::LegacyLastTick => {}
+        }
+    }
+
+    objects
+}
+
+fn banana_shower_objects(
+    hit_object: &osu::HitObject,
+    rng: &mut LegacyRandom,
+    mut make_object: impl FnMut(f64, f32, CatchHitobject) -> CatchHitObject,
+) -> Vec<CatchHitObject> {
+    let start_time = hit_object.time as f64;
+    let end_time = hit_object.end_time().unwrap_or(hit_object.time as i32) as f64;
+
+    let mut spacing = end_time - start_time;
+    while spacing > 100.0 {
+        spacing /= 2.0;
+    }
+
+    if spacing <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut objects = Vec::new();
+    let mut time = start_time;
+
+    while time <= end_time {
+        let x = (rng.next() % CATCH_PLAYFIELD_WIDTH as i32) as f32;
+        // osu!stable rolled a random banana type, rotation and colour
+        rng.next();
+        rng.next();
+        rng.next();
+
+        objects.push(make_object(time, x, CatchHitobject::banana(0, end_time)));
+        time += spacing;
+    }
+
+    objects
+}
+
+pub(crate) fn from_osu_catch(
+    raw_chart: &str,
+) -> Result<GenericCatchChart, Box<dyn std::error::Error>> {
+    let osu_file = from_osu(raw_chart)?;
+
+    validate_mode_catch(osu_file.general.get_mode())?;
+
+    let metadata = process_metadata(&osu_file);
+    let mut chartinfo = process_chartinfo(&osu_file);
+    chartinfo.key_count = 1;
+    let timing_points = process_timing_points(&osu_file, &mut chartinfo);
+
+    let timing_map = TimingMap::new(&timing_points, chartinfo.bpm_affects_sv, last_object_time(&osu_file));
+
+    let mut hitobjects = CatchHitObjects::with_capacity(osu_file.hitobjects.count() * 4);
+    let mut soundbank = SoundBank::new();
+    soundbank.audio_tracks.push(chartinfo.song_path.clone());
+
+    let mut rng = LegacyRandom::new(CATCH_RNG_SEED);
+
+    for hit_object in osu_file.hitobjects.iter() {
+        let keysound = hit_object.resolve_keysound(
+            &mut soundbank,
+            osu_file.timing_points.sample_point_at(hit_object.time),
+            &osu_file.general.sample_set,
+        );
+
+        let make_object = |time: f64, x: f32, note: CatchHitobject| {
+            // osu!stable keeps catch objects on whole milliseconds
+            let time = time.trunc();
+            let beat = timing_map.snapped_beat_at(time);
+            CatchHitObject::new(time, beat, x, keysound, note)
+        };
+
+        let objects = if hit_object.is_slider() {
+            juice_stream_objects(hit_object, &osu_file, &mut rng, make_object)
+        } else if hit_object.is_spinner() {
+            banana_shower_objects(hit_object, &mut rng, make_object)
+        } else if hit_object.is_hit_circle() {
+            vec![make_object(hit_object.time as f64, hit_object.x as f32, CatchHitobject::fruit(0))]
+        } else {
+            Vec::new()
+        };
+
+        for object in objects {
+            hitobjects.add_hitobject_sorted(object);
+        }
+    }
+
+    Ok(GenericCatchChart::new(
+        metadata,
+        chartinfo,
+        timing_points,
+        hitobjects,
+        Some(soundbank),
+        osu_file.difficulty.circle_size,
+        osu_file.difficulty.approach_rate,
+    ))
+}
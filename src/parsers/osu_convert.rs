@@ -0,0 +1,1117 @@
+use std::collections::VecDeque;
+use std::ops::BitOr;
+
+use crate::models::osu::{self, OsuFile};
+use crate::utils::random::LegacyRandom;
+
+// osu!mania converts, this follows osu!'s legacy pattern generators step by step so the
+// random number generator gets called in exactly the same order
+
+const MAX_NOTES_FOR_DENSITY: usize = 7;
+
+const HIT_WHISTLE: u8 = 2;
+const HIT_FINISH: u8 = 4;
+const HIT_CLAP: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct PatternType(u16);
+
+impl PatternType {
+    const NONE: Self = Self(0);
+    const FORCE_STACK: Self = Self(1);
+    const FORCE_NOT_STACK: Self = Self(1 << 1);
+    const KEEP_SINGLE: Self = Self(1 << 2);
+    const LOW_PROBABILITY: Self = Self(1 << 3);
+    const GATHERED: Self = Self(1 << 7);
+    const MIRROR: Self = Self(1 << 8);
+    const REVERSE: Self = Self(1 << 9);
+    const CYCLE: Self = Self(1 << 10);
+    const STAIR: Self = Self(1 << 11);
+    const REVERSE_STAIR: Self = Self(1 << 12);
+
+    fn contains(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for PatternType {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ConvertedNote {
+    column: i32,
+    start_time: f64,
+    end_time: Option<f64>,
+    hit_sound: u8,
+    source: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Pattern {
+    notes: Vec<ConvertedNote>,
+}
+
+impl Pattern {
+    fn add(&mut self, note: ConvertedNote) {
+        self.notes.push(note);
+    }
+
+    fn extend(&mut self, other: &Pattern) {
+        self.notes.extend_from_slice(&other.notes);
+    }
+
+    fn column_has_object(&self, column: i32) -> bool {
+        self.notes.iter().any(|note| note.column == column)
+    }
+
+    fn column_with_objects(&self) -> i32 {
+        let mut columns: Vec<i32> = self.notes.iter().map(|note| note.column).collect();
+        columns.sort_unstable();
+        columns.dedup();
+        columns.len() as i32
+    }
+}
+
+// osu! picks the key count from how many sliders and spinners there are
+pub(crate) fn key_count_for(osu_file: &OsuFile) -> u8 {
+    let total = osu_file.hitobjects.count();
+    if total == 0 {
+        return 7;
+    }
+
+    let rounded_cs = osu_file.difficulty.circle_size.round_ties_even();
+    let rounded_od = osu_file.difficulty.overall_difficulty.round_ties_even();
+    let special_objects = total - osu_file.hitobjects.hit_circle_count();
+    let percent_special = special_objects as f64 / total as f64;
+
+    if percent_special < 0.2 {
+        return 7;
+    }
+    if percent_special < 0.3 || rounded_cs >= 5.0 {
+        return if rounded_od > 5.0 { 7 } else { 6 };
+    }
+    if percent_special > 0.6 {
+        return if rounded_od > 4.0 { 5 } else { 4 };
+    }
+
+    (rounded_od as i32 + 1).clamp(4, 7) as u8
+}
+
+fn conversion_difficulty(osu_file: &OsuFile) -> f64 {
+    let hitobjects = &osu_file.hitobjects;
+    let first = hitobjects.first().map_or(0.0, |obj| obj.time as f64);
+    let last = hitobjects.last().map_or(0.0, |obj| obj.time as f64);
+
+    // drain time in seconds
+    let mut drain_time = ((last - first - osu_file.events.total_break_time() as f64) / 1000.0) as i32;
+    if drain_time == 0 {
+        drain_time = 10000;
+    }
+
+    let difficulty = &osu_file.difficulty;
+    let hp_and_ar = (difficulty.hp_drain_rate + difficulty.approach_rate.clamp(4.0, 7.0)) as f64;
+    let value = (hp_and_ar / 1.5 + hitobjects.count() as f64 / drain_time as f64 * 9.0) / 38.0 * 5.0 / 1.15;
+    value.min(12.0)
+}
+
+fn seed_for(difficulty: &osu::Difficulty) -> i32 {
+    (difficulty.hp_drain_rate + difficulty.circle_size).round_ties_even() as i32 * 20
+        + (difficulty.overall_difficulty as f64 * 41.2) as i32
+        + difficulty.approach_rate.round_ties_even() as i32
+}
+
+type NextColumn = dyn Fn(&mut LegacyRandom, i32) -> i32;
+
+struct PatternGenerator<'a> {
+    random: &'a mut LegacyRandom,
+    hit_object: &'a osu::HitObject,
+    source: usize,
+    previous_pattern: &'a Pattern,
+    total_columns: i32,
+    random_start: i32,
+    conversion_difficulty: f64,
+}
+
+impl PatternGenerator<'_> {
+    fn has_sample(&self, sample: u8) -> bool {
+        self.hit_object.hit_sound & sample != 0
+    }
+
+    fn note(&self, column: i32, start_time: f64, end_time: Option<f64>, hit_sound: u8) -> ConvertedNote {
+        ConvertedNote {
+            column,
+            start_time,
+            end_time,
+            hit_sound,
+            source: self.source,
+        }
+    }
+
+    fn get_column(&self, position: f32, allow_special: bool) -> i32 {
+        if allow_special && self.total_columns == 8 {
+            let local_x_divisor = 512.0 / 7.0;
+            return ((position / local_x_divisor).floor() as i32).clamp(0, 6) + 1;
+        }
+
+        let local_x_divisor = 512.0 / self.total_columns as f32;
+        ((position / local_x_divisor).floor() as i32).clamp(0, self.total_columns - 1)
+    }
+
+    fn get_random_column(&mut self, lower_bound: Option<i32>, upper_bound: Option<i32>) -> i32 {
+        self.random.next_range(
+            lower_bound.unwrap_or(self.random_start),
+            upper_bound.unwrap_or(self.total_columns),
+        )
+    }
+
+    fn get_random_note_count(&mut self, p2: f64, p3: f64, p4: f64, p5: f64) -> i32 {
+        let value = self.random.next_double();
+        if value >= 1.0 - p5 {
+            5
+        } else if value >= 1.0 - p4 {
+            4
+        } else if value >= 1.0 - p3 {
+            3
+        } else if value >= 1.0 - p2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn find_available_column(
+        &mut self,
+        initial_column: i32,
+        bounds: (Option<i32>, Option<i32>),
+        next_column: Option<&NextColumn>,
+        validation: Option<&dyn Fn(i32) -> bool>,
+        patterns: &[&Pattern],
+    ) -> i32 {
+        let lower_bound = bounds.0.unwrap_or(self.random_start);
+        let upper_bound = bounds.1.unwrap_or(self.total_columns);
+
+        let is_valid = |column: i32| {
+            validation.is_none_or(|validate| validate(column))
+                && patterns.iter().all(|pattern| !pattern.column_has_object(column))
+        };
+
+        if is_valid(initial_column) {
+            return initial_column;
+        }
+
+        // osu! gives up here, keep the column instead of looping forever
+        if !(lower_bound..upper_bound).any(is_valid) {
+            return initial_column;
+        }
+
+        let mut column = initial_column;
+        loop {
+            column = match next_column {
+                Some(next) => next(self.random, column),
+                None => self.random.next_range(lower_bound, upper_bound),
+            };
+            if is_valid(column) {
+                return column;
+            }
+        }
+    }
+}
+
+struct HitObjectPatternGenerator<'a> {
+    base: PatternGenerator<'a>,
+    convert_type: PatternType,
+    stair_type: PatternType,
+}
+
+impl<'a> HitObjectPatternGenerator<'a> {
+    fn new(
+        base: PatternGenerator<'a>,
+        timing_points: &osu::TimingPoints,
+        previous_time: f64,
+        previous_position: (f32, f32),
+        density: f64,
+        last_stair: PatternType,
+    ) -> Self {
+        let hit_object = base.hit_object;
+        let beat_length = timing_points.beat_length_at(hit_object.time) as f64;
+        let kiai = timing_points.is_kiai_at(hit_object.time);
+
+        let position_separation = ((hit_object.x as f32 - previous_position.0).powi(2)
+            + (hit_object.y as f32 - previous_position.1).powi(2))
+        .sqrt();
+        let time_separation = hit_object.time as f64 - previous_time;
+
+        let mut convert_type = PatternType::NONE;
+
+        if time_separation <= 80.0 {
+            // more than 187 BPM
+            convert_type.insert(PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE);
+        } else if time_separation <= 95.0 {
+            // more than 157 BPM
+            convert_type.insert(PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE | last_stair);
+        } else if time_separation <= 105.0 {
+            // more than 140 BPM
+            convert_type.insert(PatternType::FORCE_NOT_STACK | PatternType::LOW_PROBABILITY);
+        } else if time_separation <= 125.0 {
+            // more than 120 BPM
+            convert_type.insert(PatternType::FORCE_NOT_STACK);
+        } else if time_separation <= 135.0 && position_separation < 20.0 {
+            // more than 111 BPM stream
+            convert_type.insert(PatternType::CYCLE | PatternType::KEEP_SINGLE);
+        } else if time_separation <= 150.0 && position_separation < 20.0 {
+            // more than 100 BPM stream
+            convert_type.insert(PatternType::FORCE_STACK | PatternType::LOW_PROBABILITY);
+        } else if position_separation < 20.0 && density >= beat_length / 2.5 {
+            // low density stream
+            convert_type.insert(PatternType::REVERSE | PatternType::LOW_PROBABILITY);
+        } else if density < beat_length / 2.5 || kiai {
+            // high density
+        } else {
+            convert_type.insert(PatternType::LOW_PROBABILITY);
+        }
+
+        if !convert_type.contains(PatternType::KEEP_SINGLE) {
+            if base.has_sample(HIT_FINISH) && base.total_columns != 8 {
+                convert_type.insert(PatternType::MIRROR);
+            } else if base.has_sample(HIT_CLAP) {
+                convert_type.insert(PatternType::GATHERED);
+            }
+        }
+
+        Self {
+            base,
+            convert_type,
+            stair_type: last_stair,
+        }
+    }
+
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32) {
+        let hit_object = self.base.hit_object;
+        pattern.add(self.base.note(column, hit_object.time as f64, None, hit_object.hit_sound));
+    }
+
+    fn generate(&mut self) -> Pattern {
+        let pattern = self.generate_pattern();
+
+        for note in &pattern.notes {
+            if self.convert_type.contains(PatternType::STAIR) && note.column == self.base.total_columns - 1 {
+                self.stair_type = PatternType::REVERSE_STAIR;
+            }
+            if self.convert_type.contains(PatternType::REVERSE_STAIR) && note.column == self.base.random_start {
+                self.stair_type = PatternType::STAIR;
+            }
+        }
+
+        pattern
+    }
+
+    fn generate_pattern(&mut self) -> Pattern {
+        let mut pattern = Pattern::default();
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+        let previous_pattern = self.base.previous_pattern;
+        let convert_type = self.convert_type;
+
+        if total_columns == 1 {
+            self.add_to_pattern(&mut pattern, 0);
+            return pattern;
+        }
+
+        let last_column = previous_pattern.notes.first().map_or(0, |note| note.column);
+
+        if convert_type.contains(PatternType::REVERSE) && !previous_pattern.notes.is_empty() {
+            // copy the previous notes in reverse column order
+            for column in random_start..total_columns {
+                if previous_pattern.column_has_object(column) {
+                    self.add_to_pattern(&mut pattern, random_start + total_columns - column - 1);
+                }
+            }
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::CYCLE)
+            && previous_pattern.notes.len() == 1
+            // don't overload the special key in 7K + 1
+            && (total_columns != 8 || last_column != 0)
+            // and don't cycle from the centre column
+            && (total_columns % 2 == 0 || last_column != total_columns / 2)
+        {
+            self.add_to_pattern(&mut pattern, random_start + total_columns - last_column - 1);
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::FORCE_STACK) && !previous_pattern.notes.is_empty() {
+            for column in random_start..total_columns {
+                if previous_pattern.column_has_object(column) {
+                    self.add_to_pattern(&mut pattern, column);
+                }
+            }
+            return pattern;
+        }
+
+        if previous_pattern.notes.len() == 1 {
+            if convert_type.contains(PatternType::STAIR) {
+                let mut target_column = last_column + 1;
+                if target_column == total_columns {
+                    target_column = random_start;
+                }
+                self.add_to_pattern(&mut pattern, target_column);
+                return pattern;
+            }
+
+            if convert_type.contains(PatternType::REVERSE_STAIR) {
+                let mut target_column = last_column - 1;
+                if target_column == random_start - 1 {
+                    target_column = total_columns - 1;
+                }
+                self.add_to_pattern(&mut pattern, target_column);
+                return pattern;
+            }
+        }
+
+        if convert_type.contains(PatternType::KEEP_SINGLE) {
+            return self.generate_random_notes(1);
+        }
+
+        let conversion_difficulty = self.base.conversion_difficulty;
+        let low_probability = convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if convert_type.contains(PatternType::MIRROR) {
+            return if conversion_difficulty > 6.5 {
+                self.generate_random_pattern_with_mirrored(0.12, 0.38, 0.12)
+            } else if conversion_difficulty > 4.0 {
+                self.generate_random_pattern_with_mirrored(0.12, 0.17, 0.0)
+            } else {
+                self.generate_random_pattern_with_mirrored(0.12, 0.0, 0.0)
+            };
+        }
+
+        if conversion_difficulty > 6.5 {
+            if low_probability {
+                return self.generate_random_pattern(0.78, 0.42, 0.0, 0.0);
+            }
+            return self.generate_random_pattern(1.0, 0.62, 0.0, 0.0);
+        }
+
+        if conversion_difficulty > 4.0 {
+            if low_probability {
+                return self.generate_random_pattern(0.35, 0.08, 0.0, 0.0);
+            }
+            return self.generate_random_pattern(0.52, 0.15, 0.0, 0.0);
+        }
+
+        if conversion_difficulty > 2.0 {
+            if low_probability {
+                return self.generate_random_pattern(0.18, 0.0, 0.0, 0.0);
+            }
+            return self.generate_random_pattern(0.45, 0.0, 0.0, 0.0);
+        }
+
+        self.generate_random_pattern(0.0, 0.0, 0.0, 0.0)
+    }
+
+    fn generate_random_notes(&mut self, note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+        let previous_pattern = self.base.previous_pattern;
+
+        let allow_stacking = !self.convert_type.contains(PatternType::FORCE_NOT_STACK);
+        let note_count = if allow_stacking {
+            note_count
+        } else {
+            note_count.min(total_columns - random_start - previous_pattern.column_with_objects())
+        };
+
+        let gathered = self.convert_type.contains(PatternType::GATHERED);
+        let next = move |random: &mut LegacyRandom, last: i32| {
+            if gathered {
+                let next = last + 1;
+                if next == total_columns { random_start } else { next }
+            } else {
+                random.next_range(random_start, total_columns)
+            }
+        };
+
+        let mut next_column = self.base.get_column(self.base.hit_object.x as f32, true);
+
+        for _ in 0..note_count {
+            next_column = if allow_stacking {
+                self.base.find_available_column(next_column, (None, None), Some(&next), None, &[&pattern])
+            } else {
+                self.base.find_available_column(next_column, (None, None), Some(&next), None, &[&pattern, previous_pattern])
+            };
+            self.add_to_pattern(&mut pattern, next_column);
+        }
+
+        pattern
+    }
+
+    // only 7K + 1 has a special column
+    fn has_special_column(&self) -> bool {
+        self.base.has_sample(HIT_CLAP) && self.base.has_sample(HIT_FINISH)
+    }
+
+    fn generate_random_pattern(&mut self, p2: f64, p3: f64, p4: f64, p5: f64) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let note_count = self.random_note_count(p2, p3, p4, p5);
+        pattern.extend(&self.generate_random_notes(note_count));
+
+        if self.base.random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+
+        pattern
+    }
+
+    fn generate_random_pattern_with_mirrored(&mut self, centre_probability: f64, p2: f64, p3: f64) -> Pattern {
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK) {
+            return self.generate_random_pattern(0.5 + p2 / 2.0, p2, (p2 + p3) / 2.0, p3);
+        }
+
+        let mut pattern = Pattern::default();
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+
+        let (note_count, add_to_centre) = self.random_note_count_mirrored(centre_probability, p2, p3);
+
+        let column_limit = (if total_columns % 2 == 0 { total_columns } else { total_columns - 1 }) / 2;
+        let mut next_column = self.base.get_random_column(None, Some(column_limit));
+
+        for _ in 0..note_count {
+            next_column = self.base.find_available_column(next_column, (None, Some(column_limit)), None, None, &[&pattern]);
+            self.add_to_pattern(&mut pattern, next_column);
+            self.add_to_pattern(&mut pattern, random_start + total_columns - next_column - 1);
+        }
+
+        if add_to_centre {
+            self.add_to_pattern(&mut pattern, total_columns / 2);
+        }
+
+        if random_start > 0 && self.has_special_column() {
+            self.add_to_pattern(&mut pattern, 0);
+        }
+
+        pattern
+    }
+
+    fn random_note_count(&mut self, mut p2: f64, mut p3: f64, mut p4: f64, mut p5: f64) -> i32 {
+        match self.base.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.23);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            5 => {
+                p3 = p3.min(0.15);
+                p4 = p4.min(0.03);
+                p5 = 0.0;
+            }
+            _ => {}
+        }
+
+        if self.base.has_sample(HIT_CLAP) {
+            p2 = 1.0;
+        }
+
+        self.base.get_random_note_count(p2, p3, p4, p5)
+    }
+
+    fn random_note_count_mirrored(&mut self, mut centre_probability: f64, mut p2: f64, mut p3: f64) -> (i32, bool) {
+        match self.base.total_columns {
+            2 => {
+                centre_probability = 0.0;
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            3 => {
+                centre_probability = centre_probability.min(0.03);
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            4 => {
+                centre_probability = 0.0;
+                // osu!stable doubled the inverse probability
+                p2 = 1.0 - ((1.0 - p2) * 2.0).max(0.8);
+                p3 = 0.0;
+            }
+            5 => {
+                centre_probability = centre_probability.min(0.03);
+                p3 = 0.0;
+            }
+            6 => {
+                centre_probability = 0.0;
+                p2 = 1.0 - ((1.0 - p2) * 2.0).max(0.5);
+                p3 = 1.0 - ((1.0 - p3) * 2.0).max(0.85);
+            }
+            _ => {}
+        }
+
+        let p2 = p2.clamp(0.0, 1.0);
+        let p3 = p3.clamp(0.0, 1.0);
+
+        let centre_value = self.base.random.next_double();
+        let note_count = self.base.get_random_note_count(p2, p3, 0.0, 0.0);
+
+        let add_to_centre = self.base.total_columns % 2 != 0 && note_count != 3 && centre_value > 1.0 - centre_probability;
+        (note_count, add_to_centre)
+    }
+}
+
+struct EndTimeObjectPatternGenerator<'a> {
+    base: PatternGenerator<'a>,
+    end_time: i32,
+}
+
+impl<'a> EndTimeObjectPatternGenerator<'a> {
+    fn new(base: PatternGenerator<'a>) -> Self {
+        let end_time = base.hit_object.end_time().unwrap_or(base.hit_object.time as i32);
+        Self { base, end_time }
+    }
+
+    fn generate(&mut self) -> Pattern {
+        let mut pattern = Pattern::default();
+        let start_time = self.base.hit_object.time as f64;
+        let duration = self.end_time as f64 - start_time;
+        let generate_hold = duration >= 100.0;
+        let previous_pattern = self.base.previous_pattern;
+
+        let column = if self.base.total_columns == 8 {
+            if self.base.has_sample(HIT_FINISH) && duration < 1000.0 {
+                0
+            } else {
+                let initial = self.base.get_random_column(None, None);
+                self.base.find_available_column(initial, (None, None), None, None, &[previous_pattern])
+            }
+        } else {
+            let initial = self.base.get_random_column(Some(0), None);
+            self.base.find_available_column(initial, (Some(0), None), None, None, &[previous_pattern])
+        };
+
+        let end_time = if generate_hold { Some(self.end_time as f64) } else { None };
+        pattern.add(self.base.note(column, start_time, end_time, self.base.hit_object.hit_sound));
+        pattern
+    }
+}
+
+struct PathObjectPatternGenerator<'a> {
+    base: PatternGenerator<'a>,
+    start_time: i32,
+    end_time: i32,
+    segment_duration: i32,
+    span_count: i32,
+    node_samples: Vec<u8>,
+    convert_type: PatternType,
+}
+
+impl<'a> PathObjectPatternGenerator<'a> {
+    fn new(base: PatternGenerator<'a>, timing_points: &osu::TimingPoints, difficulty: &osu::Difficulty) -> Option<Self> {
+        let hit_object = base.hit_object;
+        let (_, _, slides, _) = hit_object.slider_params()?;
+        let distance = hit_object.slider_path()?.distance();
+
+        let convert_type = if timing_points.is_kiai_at(hit_object.time) {
+            PatternType::NONE
+        } else {
+            PatternType::LOW_PROBABILITY
+        };
+
+        // osu!stable turned the slider velocity back into a beat length with limited precision
+        let slider_velocity = timing_points.slider_velocity_at(hit_object.time) as f64;
+        let bpm_multiplier = ((100.0 / slider_velocity) as f32).clamp(10.0, 10000.0) as f64 / 100.0;
+        let beat_length = timing_points.beat_length_at(hit_object.time) as f64 * bpm_multiplier;
+
+        let span_count = slides.max(1);
+        let start_time = (hit_object.time as f64).round_ties_even() as i32;
+        let end_time = (start_time as f64
+            + distance * beat_length * span_count as f64 * 0.01 / difficulty.slider_multiplier as f64)
+            .floor() as i32;
+        let segment_duration = (end_time - start_time) / span_count;
+
+        let mut node_samples = vec![hit_object.hit_sound; span_count as usize + 1];
+        for (node, sound) in node_samples.iter_mut().zip(hit_object.slider_edge_sounds()) {
+            *node = sound as u8;
+        }
+
+        Some(Self {
+            base,
+            start_time,
+            end_time,
+            segment_duration,
+            span_count,
+            node_samples,
+            convert_type,
+        })
+    }
+
+    fn generate(&mut self) -> Vec<Pattern> {
+        let original = self.generate_pattern();
+        if original.notes.len() == 1 {
+            return vec![original];
+        }
+
+        // notes that end with the slider are used as the previous pattern for the next object
+        let mut intermediate = Pattern::default();
+        let mut end_time_pattern = Pattern::default();
+
+        for note in original.notes {
+            let note_end_time = note.end_time.unwrap_or(note.start_time).round_ties_even() as i32;
+            if note_end_time != self.end_time {
+                intermediate.add(note);
+            } else {
+                end_time_pattern.add(note);
+            }
+        }
+
+        vec![intermediate, end_time_pattern]
+    }
+
+    fn generate_pattern(&mut self) -> Pattern {
+        let start_time = self.start_time;
+
+        if self.base.total_columns == 1 {
+            let mut pattern = Pattern::default();
+            self.add_to_pattern(&mut pattern, 0, start_time, self.end_time);
+            return pattern;
+        }
+
+        let conversion_difficulty = self.base.conversion_difficulty;
+        let low_probability = self.convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if self.span_count > 1 {
+            if self.segment_duration <= 90 {
+                return self.generate_random_hold_notes(start_time, 1);
+            }
+
+            if self.segment_duration <= 120 {
+                self.convert_type.insert(PatternType::FORCE_NOT_STACK);
+                return self.generate_random_notes(start_time, self.span_count + 1);
+            }
+
+            if self.segment_duration <= 160 {
+                return self.generate_stair(start_time);
+            }
+
+            if self.segment_duration <= 200 && conversion_difficulty > 3.0 {
+                return self.generate_random_multiple_notes(start_time);
+            }
+
+            if self.end_time - self.start_time >= 4000 {
+                return self.generate_n_random_notes(start_time, 0.23, 0.0, 0.0);
+            }
+
+            if self.segment_duration > 400 && self.span_count < self.base.total_columns - 1 - self.base.random_start {
+                return self.generate_tiled_hold_notes(start_time);
+            }
+
+            return self.generate_hold_and_normal_notes(start_time);
+        }
+
+        if self.segment_duration <= 110 {
+            if self.base.previous_pattern.column_with_objects() < self.base.total_columns {
+                self.convert_type.insert(PatternType::FORCE_NOT_STACK);
+            } else {
+                self.convert_type.remove(PatternType::FORCE_NOT_STACK);
+            }
+            let note_count = if self.segment_duration < 80 { 1 } else { 2 };
+            return self.generate_random_notes(start_time, note_count);
+        }
+
+        if conversion_difficulty > 6.5 {
+            if low_probability {
+                return self.generate_n_random_notes(start_time, 0.78, 0.3, 0.0);
+            }
+            return self.generate_n_random_notes(start_time, 0.85, 0.36, 0.03);
+        }
+
+        if conversion_difficulty > 4.0 {
+            if low_probability {
+                return self.generate_n_random_notes(start_time, 0.43, 0.08, 0.0);
+            }
+            return self.generate_n_random_notes(start_time, 0.56, 0.18, 0.0);
+        }
+
+        if conversion_difficulty > 2.5 {
+            if low_probability {
+                return self.generate_n_random_notes(start_time, 0.3, 0.0, 0.0);
+            }
+            return self.generate_n_random_notes(start_time, 0.37, 0.08, 0.0);
+        }
+
+        if low_probability {
+            return self.generate_n_random_notes(start_time, 0.17, 0.0, 0.0);
+        }
+        self.generate_n_random_notes(start_time, 0.27, 0.0, 0.0)
+    }
+
+    fn head_column(&mut self) -> i32 {
+        let column = self.base.get_column(self.base.hit_object.x as f32, true);
+        let previous_pattern = self.base.previous_pattern;
+
+        if self.convert_type.contains(PatternType::FORCE_NOT_STACK)
+            && previous_pattern.column_with_objects() < self.base.total_columns
+        {
+            return self.base.find_available_column(column, (None, None), None, None, &[previous_pattern]);
+        }
+        column
+    }
+
+    // hold notes that all start and end together
+    fn generate_random_hold_notes(&mut self, start_time: i32, note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let previous_pattern = self.base.previous_pattern;
+
+        let usable_columns = self.base.total_columns - self.base.random_start - previous_pattern.column_with_objects();
+        let mut next_column = self.base.get_random_column(None, None);
+
+        for _ in 0..usable_columns.min(note_count) {
+            next_column = self.base.find_available_column(next_column, (None, None), None, None, &[&pattern, previous_pattern]);
+            self.add_to_pattern(&mut pattern, next_column, start_time, self.end_time);
+        }
+
+        // can't be merged with the loop above because of the rng
+        for _ in 0..note_count - usable_columns {
+            next_column = self.base.find_available_column(next_column, (None, None), None, None, &[&pattern]);
+            self.add_to_pattern(&mut pattern, next_column, start_time, self.end_time);
+        }
+
+        pattern
+    }
+
+    // one note per row, never twice in a row on the same column
+    fn generate_random_notes(&mut self, mut start_time: i32, note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let mut next_column = self.head_column();
+        let mut last_column = next_column;
+
+        for _ in 0..note_count {
+            self.add_to_pattern(&mut pattern, next_column, start_time, start_time);
+            let previous = last_column;
+            next_column = self.base.find_available_column(next_column, (None, None), None, Some(&|column| column != previous), &[]);
+            last_column = next_column;
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    fn generate_stair(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+
+        let mut column = self.base.get_column(self.base.hit_object.x as f32, true);
+        let mut increasing = self.base.random.next_double() > 0.5;
+
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, column, start_time, start_time);
+            start_time += self.segment_duration;
+
+            // turn around at the edges of the stage
+            if increasing {
+                if column >= total_columns - 1 {
+                    increasing = false;
+                    column -= 1;
+                } else {
+                    column += 1;
+                }
+            } else if column <= random_start {
+                increasing = true;
+                column += 1;
+            } else {
+                column -= 1;
+            }
+        }
+
+        pattern
+    }
+
+    // one or two notes per row
+    fn generate_random_multiple_notes(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let total_columns = self.base.total_columns;
+        let random_start = self.base.random_start;
+
+        let legacy = (4..=8).contains(&total_columns);
+        let interval = self.base.random.next_range(1, total_columns - i32::from(legacy));
+
+        let mut next_column = self.base.get_column(self.base.hit_object.x as f32, true);
+
+        for _ in 0..=self.span_count {
+            self.add_to_pattern(&mut pattern, next_column, start_time, start_time);
+
+            next_column += interval;
+            if next_column >= total_columns - random_start {
+                next_column = next_column - total_columns - random_start + i32::from(legacy);
+            }
+            next_column += random_start;
+
+            // avoid too many consecutive doubles in 2K
+            if total_columns > 2 {
+                self.add_to_pattern(&mut pattern, next_column, start_time, start_time);
+            }
+
+            next_column = self.base.get_random_column(None, None);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    fn generate_n_random_notes(&mut self, start_time: i32, mut p2: f64, mut p3: f64, mut p4: f64) -> Pattern {
+        match self.base.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.3);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+            }
+            5 => {
+                p2 = p2.min(0.34);
+                p3 = p3.min(0.1);
+                p4 = p4.min(0.03);
+            }
+            _ => {}
+        }
+
+        let is_double_sample = |hit_sound: u8| hit_sound & (HIT_CLAP | HIT_FINISH) != 0;
+        let can_generate_two_notes = !self.convert_type.contains(PatternType::LOW_PROBABILITY)
+            && (is_double_sample(self.base.hit_object.hit_sound) || is_double_sample(self.sample_at(self.start_time)));
+
+        if can_generate_two_notes {
+            p2 = 1.0;
+        }
+
+        let note_count = self.base.get_random_note_count(p2, p3, p4, 0.0);
+        self.generate_random_hold_notes(start_time, note_count)
+    }
+
+    // a stair of hold notes
+    fn generate_tiled_hold_notes(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+
+        let column_repeat = self.span_count.min(self.base.total_columns);
+        // not always the same as the slider end time because of integer rounding
+        let end_time = start_time + self.segment_duration * self.span_count;
+
+        let mut next_column = self.head_column();
+
+        for _ in 0..column_repeat {
+            next_column = self.base.find_available_column(next_column, (None, None), None, None, &[&pattern]);
+            self.add_to_pattern(&mut pattern, next_column, start_time, end_time);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    fn generate_hold_and_normal_notes(&mut self, mut start_time: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let total_columns = self.base.total_columns;
+
+        let hold_column = self.head_column();
+        self.add_to_pattern(&mut pattern, hold_column, start_time, self.end_time);
+
+        let mut next_column = self.base.get_random_column(None, None);
+        let conversion_difficulty = self.base.conversion_difficulty;
+        let note_count = if conversion_difficulty > 6.5 {
+            self.base.get_random_note_count(0.63, 0.0, 0.0, 0.0)
+        } else if conversion_difficulty > 4.0 {
+            self.base.get_random_note_count(if total_columns < 6 { 0.12 } else { 0.45 }, 0.0, 0.0, 0.0)
+        } else if conversion_difficulty > 2.5 {
+            self.base.get_random_note_count(if total_columns < 6 { 0.0 } else { 0.24 }, 0.0, 0.0, 0.0)
+        } else {
+            0
+        };
+        let note_count = note_count.min(total_columns - 1);
+
+        let ignore_head = self.sample_at(start_time) & (HIT_WHISTLE | HIT_FINISH | HIT_CLAP) == 0;
+
+        for _ in 0..=self.span_count {
+            let mut row_pattern = Pattern::default();
+
+            if !(ignore_head && start_time == self.start_time) {
+                for _ in 0..note_count {
+                    next_column = self.base.find_available_column(
+                        next_column,
+                        (None, None),
+                        None,
+                        Some(&|column| column != hold_column),
+                        &[&row_pattern],
+                    );
+                    self.add_to_pattern(&mut row_pattern, next_column, start_time, start_time);
+                }
+            }
+
+            pattern.extend(&row_pattern);
+            start_time += self.segment_duration;
+        }
+
+        pattern
+    }
+
+    fn sample_at(&self, time: i32) -> u8 {
+        let index = if self.segment_duration == 0 {
+            0
+        } else {
+            (time - self.start_time) / self.segment_duration
+        };
+
+        self.node_samples
+            .get(index.max(0) as usize)
+            .copied()
+            .unwrap_or(self.base.hit_object.hit_sound)
+    }
+
+    fn add_to_pattern(&self, pattern: &mut Pattern, column: i32, start_time: i32, end_time: i32) {
+        if start_time == end_time {
+            pattern.add(self.base.note(column, start_time as f64, None, self.sample_at(start_time)));
+        } else {
+            pattern.add(self.base.note(column, start_time as f64, Some(end_time as f64), self.base.hit_object.hit_sound));
+        }
+    }
+}
+
+// returns osu!mania hit objects, so the result can go through the normal mania parsing
+pub(crate) fn convert_to_mania(osu_file: &OsuFile, key_count: u8) -> osu::HitObjects {
+    let total_columns = key_count as i32;
+    let random_start = if total_columns == 8 { 1 } else { 0 };
+    let conversion_difficulty = conversion_difficulty(osu_file);
+    let timing_points = &osu_file.timing_points;
+
+    let mut random = LegacyRandom::new(seed_for(&osu_file.difficulty));
+    let mut last_pattern = Pattern::default();
+    let mut last_stair = PatternType::STAIR;
+    let mut last_time = 0.0;
+    let mut last_position = (0.0, 0.0);
+    let mut previous_note_times: VecDeque<f64> = VecDeque::with_capacity(MAX_NOTES_FOR_DENSITY);
+    let mut density = i32::MAX as f64;
+
+    let mut compute_density = |time: f64, density: &mut f64| {
+        if previous_note_times.len() == MAX_NOTES_FOR_DENSITY {
+            previous_note_times.pop_front();
+        }
+        previous_note_times.push_back(time);
+
+        if previous_note_times.len() >= 2 {
+            let first = previous_note_times[0];
+            let last = previous_note_times[previous_note_times.len() - 1];
+            *density = (last - first) / previous_note_times.len() as f64;
+        }
+    };
+
+    let mut notes: Vec<ConvertedNote> = Vec::with_capacity(osu_file.hitobjects.count());
+
+    for (index, hit_object) in osu_file.hitobjects.iter().enumerate() {
+        let previous_pattern = last_pattern.clone();
+        let base = PatternGenerator {
+            random: &mut random,
+            hit_object,
+            source: index,
+            previous_pattern: &previous_pattern,
+            total_columns,
+            random_start,
+            conversion_difficulty,
+        };
+
+        let position = (hit_object.x as f32, hit_object.y as f32);
+
+        if hit_object.is_slider() {
+            let Some(mut generator) = PathObjectPatternGenerator::new(base, timing_points, &osu_file.difficulty) else {
+                continue;
+            };
+
+            for span in 0..=generator.span_count {
+                let time = hit_object.time as f64 + (generator.segment_duration * span) as f64;
+                last_time = time;
+                last_position = position;
+                compute_density(time, &mut density);
+            }
+
+            for pattern in generator.generate() {
+                notes.extend_from_slice(&pattern.notes);
+                last_pattern = pattern;
+            }
+        } else if hit_object.is_spinner() || hit_object.is_hold() {
+            let mut generator = EndTimeObjectPatternGenerator::new(base);
+
+            last_time = generator.end_time as f64;
+            last_position = (256.0, 192.0);
+            compute_density(generator.end_time as f64, &mut density);
+
+            // spinners don't become the previous pattern
+            notes.extend_from_slice(&generator.generate().notes);
+        } else {
+            compute_density(hit_object.time as f64, &mut density);
+
+            let mut generator = HitObjectPatternGenerator::new(base, timing_points, last_time, last_position, density, last_stair);
+            let pattern = generator.generate();
+            last_stair = generator.stair_type;
+
+            last_time = hit_object.time as f64;
+            last_position = position;
+
+            notes.extend_from_slice(&pattern.notes);
+            last_pattern = pattern;
+        }
+    }
+
+    notes.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    let mut hitobjects = osu::HitObjects::new();
+    for note in notes {
+        let source = &osu_file.hitobjects[note.source];
+        let x = (note.column * 512 + 256) / total_columns;
+
+        let mut hit_object = match note.end_time {
+            Some(end_time) => {
+                let mut hold = osu::HitObject::new(x, 192, note.start_time as f32, 128, note.hit_sound);
+                hold.object_params.push((end_time as i32).to_string());
+                hold
+            }
+            None => osu::HitObject::new(x, 192, note.start_time as f32, 1, note.hit_sound),
+        };
+        hit_object.hit_sample = source.hit_sample.clone();
+
+        hitobjects.add_hit_object(hit_object);
+    }
+
+    hitobjects
+}
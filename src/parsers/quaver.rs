@@ -11,22 +11,22 @@ use crate::models::generic::{
     Metadata,
     SoundBank,
     SoundEffect,
+    TimingMap,
     TimingPoints
 };
 use crate::models::quaver::{self, QuaFile};
 use crate::models::timeline::{TimelineOps, TimelineTimingPoint, TimingPointTimeline};
 use crate::quaver::TimingGroup;
 use crate::utils::quaver::get_keycount_from_str;
-use crate::utils::rhythm::{calculate_beat_from_time, get_ms_per_beat_at};
 
 fn process_timing_points(
-    timing_points: Vec<quaver::TimingPoint>,
+    timing_points: &[quaver::TimingPoint],
     chartinfo: &mut ChartInfo,
     timeline: &mut TimingPointTimeline,
 ) -> Result<(), Box<dyn std::error::Error>> {
     for timing_point in timing_points {
         timeline.add_sorted(TimelineTimingPoint {
-            time: timing_point.start_time as i32,
+            time: timing_point.start_time as f64,
             value: timing_point.bpm,
             group: String::new(),
             change_type: TimingChangeType::Bpm,
@@ -34,7 +34,7 @@ fn process_timing_points(
     }
 
     let start_time = if timeline.is_empty() {
-        0
+        0.0
     } else {
         timeline[0].time
     };
@@ -43,13 +43,34 @@ fn process_timing_points(
     Ok(())
 }
 
+// signatures go in after the bpms so their beats can be worked out
+fn process_signatures(
+    quaver_timing_points: &[quaver::TimingPoint],
+    timing_points: &mut TimingPoints,
+    timing_map: &TimingMap,
+) {
+    let mut signature = quaver::TimeSignature::Quadruple;
+
+    for timing_point in quaver_timing_points {
+        let current = timing_point.signature.unwrap_or_default();
+        if current == signature {
+            continue;
+        }
+        signature = current;
+
+        let time = timing_point.start_time as f64;
+        let beat = timing_map.snapped_beat_at(time);
+        timing_points.insert_time_signature(time, beat, generic::TimeSignature::new(signature.beats_per_measure(), 4));
+    }
+}
+
 fn process_sv(
     slider_velocities: Vec<quaver::SliderVelocity>,
     timeline: &mut TimingPointTimeline,
 ) -> Result<(), Box<dyn std::error::Error>> {
     for sv in slider_velocities {
         timeline.add_sorted(TimelineTimingPoint {
-            time: sv.start_time as i32,
+            time: sv.start_time as f64,
             value: sv.multiplier.unwrap_or(1.0),
             group: String::new(),
             change_type: TimingChangeType::Sv,
@@ -68,7 +89,7 @@ fn process_timing_groups(
 
         for sv in &group.scroll_velocities {
             timeline.add_sorted(TimelineTimingPoint {
-                time: sv.start_time as i32,
+                time: sv.start_time as f64,
                 value: sv.multiplier.unwrap_or(1.0),
                 group: name.clone(),
                 change_type: TimingChangeType::Sv,
@@ -84,9 +105,10 @@ fn process_soundeffects(
 ) -> Result<(), Box<dyn std::error::Error>> {
     for sound_effect in sound_effects {
         soundbank.add_sound_effect(SoundEffect {
-            time: sound_effect.start_time as i32,
+            time: sound_effect.start_time as f64,
             volume: sound_effect.volume,
-            sample: sound_effect.sample,
+            // quaver counts samples from 1
+            sample: sound_effect.sample.saturating_sub(1),
         });
     }
     Ok(())
@@ -102,13 +124,19 @@ fn process_samples(
     Ok(())
 }
 
+// the time the last note ends, picks how long the last bpm lasts
+fn last_object_time(quaver_hitobjects: &[quaver::HitObject]) -> f64 {
+    quaver_hitobjects
+        .iter()
+        .map(|hitobject| hitobject.endtime.unwrap_or(hitobject.start_time).max(hitobject.start_time) as f64)
+        .fold(0.0, f64::max)
+}
+
 fn process_notes(
     quaver_hitobjects: Vec<quaver::HitObject>,
     hitobjects: &mut HitObjects,
     chartinfo: &mut ChartInfo,
-    offset: i32,
-    bpm_times: &Vec<i32>,
-    bpms: &Vec<f32>,
+    timing_map: &TimingMap,
     has_scratch: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut key_count = chartinfo.key_count as usize;
@@ -120,9 +148,9 @@ fn process_notes(
     for hitobject in quaver_hitobjects {
         let lane = hitobject.lane();
         let key_sound = hitobject.get_generic_keysound();
-        let time = hitobject.start_time() as i32;
+        let time = hitobject.start_time() as f64;
 
-        let beat = calculate_beat_from_time(time, offset, (bpm_times, bpms));
+        let beat = timing_map.snapped_beat_at(time);
 
         match hitobject.hit_type {
             quaver::QuaverHitType::NormalOrHold => {
@@ -131,13 +159,13 @@ fn process_notes(
                         time,
                         beat,
                         lane,
-                        key: Key::slider_start(Some(hitobject.end_time().unwrap_or(0))),
+                        key: Key::slider_start(Some(hitobject.end_time().unwrap_or(0) as f64)),
                         keysound: key_sound,
                         group: hitobject.timing_group().map(|s| s.to_string()),
                     };
 
-                    let slider_end_time = hitobject.end_time().unwrap_or(0);
-                    let end_time_beat = calculate_beat_from_time(slider_end_time, offset, (bpm_times, bpms));
+                    let slider_end_time = hitobject.end_time().unwrap_or(0) as f64;
+                    let end_time_beat = timing_map.snapped_beat_at(slider_end_time);
 
                     let slider_end = generic::HitObject {
                         time: slider_end_time,
@@ -165,17 +193,17 @@ fn process_notes(
                 if hitobject.is_ln() {
                     // most games doesn't support LN mines, so let's just put a bunch of mines every 1/4 beat
                     // we do have support for LN mines but, we're expanding the LNs in the quaver parser instead of doing it in every writer, hence Key::mine(None)
-                    let end_time = hitobject.end_time().unwrap_or(0) as f32;
-                    let mut t = time as f32;
+                    let end_time = hitobject.end_time().unwrap_or(0) as f64;
+                    let mut t = time;
                     let mut last_placed = t;
 
                     while t < end_time {
                         // in case the bpm changes during the LN mine (idk might happen on some weird maps)
-                        let ms_per_beat = get_ms_per_beat_at(t as i32, bpm_times, bpms);
-                        let beat = calculate_beat_from_time(t as i32, offset, (bpm_times, bpms));
+                        let ms_per_beat = timing_map.ms_per_beat_at(t).unwrap_or(500.0);
+                        let beat = timing_map.snapped_beat_at(t);
 
                         hitobjects.add_hitobject_sorted(generic::HitObject {
-                            time: t as i32,
+                            time: t,
                             beat,
                             lane,
                             key: Key::mine(None),
@@ -184,15 +212,15 @@ fn process_notes(
                         });
 
                         last_placed = t;
-                        t += ms_per_beat / 4.0;
+                        t += ms_per_beat as f64 / 4.0;
                     }
 
                     // if the endtime is too far from the last mine we placed then put a mine directly at the endtime
-                    let ms_per_beat_at_end = get_ms_per_beat_at(end_time as i32, bpm_times, bpms);
-                    if (last_placed - end_time).abs() > ms_per_beat_at_end / 16.0 {
-                        let end_beat = calculate_beat_from_time(end_time as i32, offset, (bpm_times, bpms));
+                    let ms_per_beat_at_end = timing_map.ms_per_beat_at(end_time).unwrap_or(500.0);
+                    if (last_placed - end_time).abs() > ms_per_beat_at_end as f64 / 16.0 {
+                        let end_beat = timing_map.snapped_beat_at(end_time);
                         hitobjects.add_hitobject_sorted(generic::HitObject {
-                            time: end_time as i32,
+                            time: end_time,
                             beat: end_beat,
                             lane,
                             key: Key::mine(None),
@@ -233,10 +261,12 @@ pub(crate) fn from_qua_generic(
         title: quaver_file.title,
         artist: quaver_file.artist,
         source: quaver_file.source,
+        // quaver separates tags with commas, charts without any use spaces like osu!
         tags: quaver_file
             .tags
-            .split(" ")
-            .map(|s| s.to_string())
+            .split(if quaver_file.tags.contains(',') { ',' } else { ' ' })
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
             .collect(),
         creator: quaver_file.creator,
         ..Metadata::empty()
@@ -260,19 +290,18 @@ pub(crate) fn from_qua_generic(
 
     process_samples(quaver_file.custom_audio_samples, &mut soundbank)?;
     process_soundeffects(quaver_file.sound_effects, &mut soundbank)?;
-    process_timing_points(quaver_file.timing_points, &mut chartinfo, &mut timeline)?;
+    process_timing_points(&quaver_file.timing_points, &mut chartinfo, &mut timeline)?;
     process_sv(quaver_file.slider_velocities, &mut timeline)?;
     process_timing_groups(quaver_file.timing_groups, &mut timeline)?;
 
-    let offset = chartinfo.audio_offset;
-    timeline.to_timing_points(&mut timing_points, chartinfo.audio_offset);
+    timeline.to_timing_points(&mut timing_points);
+    let timing_map = TimingMap::new(&timing_points, chartinfo.bpm_affects_sv, last_object_time(&quaver_file.hitobjects));
+    process_signatures(&quaver_file.timing_points, &mut timing_points, &timing_map);
     process_notes(
         quaver_file.hitobjects,
         &mut hitobjects,
         &mut chartinfo,
-        offset,
-        &timing_points.bpms_times(),
-        &timing_points.bpms(),
+        &timing_map,
         quaver_file.has_scratch_key,
     )?;
 
@@ -282,5 +311,6 @@ pub(crate) fn from_qua_generic(
         timing_points,
         hitobjects,
         Some(soundbank),
+        None,
     ))
 }
@@ -5,6 +5,7 @@ use crate::models::generic::{
    ChartInfo,
    HitObjects,
    Metadata,
+   TimeSignature,
    TimingPoints
 };
 use crate::models::timeline::{HitObjectTimeline, TimelineOps, TimelineTimingPoint, TimingPointTimeline};
@@ -14,11 +15,11 @@ use crate::utils::string::{
 };
 use crate::utils::time::{merge_bpm_and_stops, to_millis};
 
-type BpmsAndStops = (Vec<f32>, Vec<f32>, Vec<TimingChangeType>);
+type BpmsAndStops = (Vec<Beat>, Vec<f32>, Vec<TimingChangeType>);
 
-pub fn parse_bpms(raw: &str) -> (Vec<f32>, Vec<f32>) {
+pub fn parse_bpms(raw: &str) -> (Vec<Beat>, Vec<f32>) {
     match raw {
-        ChartDefaults::RAW_BPMS => return (vec![0.0], vec![*ChartDefaults::BPM]),
+        ChartDefaults::RAW_BPMS => return (vec![Beat::ZERO], vec![*ChartDefaults::BPM]),
         _ => {}
     }
 
@@ -30,9 +31,8 @@ pub fn parse_bpms(raw: &str) -> (Vec<f32>, Vec<f32>) {
             let mut beat_bpm = beat_bpm_str.trim().split('=');
 
             if let (Some(beat_str), Some(bpm_str)) = (beat_bpm.next(), beat_bpm.next()) {
-                if let (Ok(beat), Ok(bpm)) = (beat_str.parse::<f32>(), bpm_str.parse::<f32>()) {
-                    debug_assert_ne!(bpm, 0.0);
-                    return Some((beat, bpm));
+                if let (Ok(beat), Ok(bpm)) = (beat_str.parse::<f64>(), bpm_str.parse::<f32>()) {
+                    return Some((Beat::from_f64(beat), bpm));
                 }
             }
             None
@@ -45,7 +45,7 @@ pub fn parse_bpms(raw: &str) -> (Vec<f32>, Vec<f32>) {
     (beats, bpms)
 }
 
-pub fn parse_stops(raw: &str) -> (Vec<f32>, Vec<f32>) {
+pub fn parse_stops(raw: &str) -> (Vec<Beat>, Vec<f32>) {
     match raw {
         ChartDefaults::RAW_STOPS => return (vec![], vec![]),
         _ => {}
@@ -60,9 +60,9 @@ pub fn parse_stops(raw: &str) -> (Vec<f32>, Vec<f32>) {
 
             if let (Some(beat_str), Some(duration_str)) = (beat_bpm.next(), beat_bpm.next()) {
                 if let (Ok(beat), Ok(duration)) =
-                    (beat_str.parse::<f32>(), duration_str.parse::<f32>())
+                    (beat_str.parse::<f64>(), duration_str.parse::<f32>())
                 {
-                    return Some((beat, to_millis(duration)));
+                    return Some((Beat::from_f64(beat), to_millis(duration)));
                 }
             }
             None
@@ -75,6 +75,24 @@ pub fn parse_stops(raw: &str) -> (Vec<f32>, Vec<f32>) {
     (beats, durations)
 }
 
+// beat=numerator=denominator pairs, these only move barlines and don't change how notes are read
+pub fn parse_time_signatures(raw: &str) -> Vec<(Beat, TimeSignature)> {
+    raw.split(',')
+        .filter_map(|signature_str| {
+            let mut parts = signature_str.trim().split('=');
+            let beat = Beat::from_f64(parts.next()?.trim().parse::<f64>().ok()?);
+            let numerator = parts.next()?.trim().parse::<u32>().ok()?;
+            let denominator = parts.next()?.trim().parse::<u32>().ok()?;
+
+            if numerator == 0 || denominator == 0 {
+                return None;
+            }
+
+            Some((beat, TimeSignature::new(numerator, denominator)))
+        })
+        .collect()
+}
+
 pub fn parse_keys_in_row(row: &str) -> Vec<Key> {
     let mut result: Vec<Key> = Vec::with_capacity(row.len());
 
@@ -120,7 +138,7 @@ where
     }
 }
 
-fn process_timing_points(bpms_and_stops: &BpmsAndStops, start_time: i32) -> TimingPoints {
+fn process_timing_points(bpms_and_stops: &BpmsAndStops, start_time: f64) -> TimingPoints {
     let mut timeline = TimingPointTimeline::new();
     let (beats, bpms_and_durations, change_types) = bpms_and_stops;
 
@@ -152,7 +170,15 @@ fn process_timing_points(bpms_and_stops: &BpmsAndStops, start_time: i32) -> Timi
         })
         .collect();
 
+    // the svs freeze the playfield in games without stops, the stop keeps beats right for timing lookups
     for (stop_time, stop_duration) in stops {
+        timeline.add(TimelineTimingPoint {
+            time: stop_time,
+            value: stop_duration,
+            group: String::new(),
+            change_type: TimingChangeType::Stop,
+        });
+
         timeline.add(TimelineTimingPoint {
             time: stop_time,
             value: 0.0,
@@ -160,7 +186,7 @@ fn process_timing_points(bpms_and_stops: &BpmsAndStops, start_time: i32) -> Timi
             change_type: TimingChangeType::Sv,
         });
 
-        let stop_end_time = stop_time + stop_duration as i32;
+        let stop_end_time = stop_time + stop_duration as f64;
 
         timeline.add(TimelineTimingPoint {
             time: stop_end_time,
@@ -171,11 +197,31 @@ fn process_timing_points(bpms_and_stops: &BpmsAndStops, start_time: i32) -> Timi
     }
 
     let mut timing_points = TimingPoints::with_capacity(timeline.len());
-    timeline.to_timing_points(&mut timing_points, start_time);
+    timeline.to_timing_points(&mut timing_points);
 
     timing_points
 }
 
+fn process_time_signatures(
+    signatures: &[(Beat, TimeSignature)],
+    timing_points: &mut TimingPoints,
+    bpms_and_stops: &BpmsAndStops,
+    start_time: f64,
+) {
+    let (beats, bpms_and_durations, change_types) = bpms_and_stops;
+    let mut current = TimeSignature::default();
+
+    for &(beat, signature) in signatures {
+        if signature == current {
+            continue;
+        }
+        current = signature;
+
+        let time = calculate_time_from_beat(beat, start_time, (beats, bpms_and_durations, change_types));
+        timing_points.insert_time_signature(time, beat, signature);
+    }
+}
+
 fn process_notes(
     raw_note_data: &str,
     chartinfo: &mut ChartInfo,
@@ -201,17 +247,16 @@ fn process_notes(
         .unwrap_or(&"Failed to get raw notes in notes section");
     let measures: Vec<&str> = raw_notes.split(",").collect();
 
-    let mut measure_beat_count: f32 = 0.0;
+    let mut measure_beat_count = Beat::ZERO;
     let mut rows = Vec::new();
 
     for measure in measures {
         let trimmed_measure = measure.trim();
         let measure_rows: Vec<_> = trimmed_measure.split('\n').collect();
-        let row_count = measure_rows.len();
-        let beat_time_per_row = 4.0 / row_count as f32;
+        let row_count = measure_rows.len() as u32;
 
         for (row_index, row) in measure_rows.into_iter().enumerate() {
-            let row_beat = measure_beat_count + row_index as f32 * beat_time_per_row;
+            let row_beat = measure_beat_count + Beat::new(4 * row_index as i64, row_count);
 
             let row_time = calculate_time_from_beat(
                 row_beat,
@@ -228,7 +273,7 @@ fn process_notes(
             });
         }
 
-        measure_beat_count += 4.0;
+        measure_beat_count += Beat::whole(4);
     }
 
     let flattened = HitObjectTimeline::flatten_rows(&rows, key_count);
@@ -250,11 +295,12 @@ pub(crate) fn from_sm_generic(
 
     chartinfo.bpm_affects_sv = false; 
 
-    let mut bpms: (Vec<f32>, Vec<f32>) = (vec![0.0], vec![0.0]);
+    let mut bpms: (Vec<Beat>, Vec<f32>) = (vec![Beat::ZERO], vec![0.0]);
     let mut raw_bpms = ChartDefaults::RAW_BPMS.to_string();
     let mut stops = (vec![], vec![]);
     let mut raw_stops = ChartDefaults::RAW_STOPS.to_string();
     let mut raw_notes = ChartDefaults::RAW_NOTES.to_string();
+    let mut time_signatures = Vec::new();
 
     process_sections(&uncommented_chart, |header, content| {
         match header {
@@ -273,9 +319,8 @@ pub(crate) fn from_sm_generic(
             "#BACKGROUND" => chartinfo.bg_path = content.or_default_empty(ChartDefaults::BG_PATH),
             "#MUSIC" => chartinfo.song_path = content.or_default_empty(ChartDefaults::SONG_PATH),
             "#OFFSET" => {
-                chartinfo.audio_offset = -to_millis(
-                    content.or_default_empty_as(*ChartDefaults::AUDIO_OFFSET as f32),
-                ) as i32
+                chartinfo.audio_offset =
+                    -content.or_default_empty_as(*ChartDefaults::AUDIO_OFFSET as f64) * 1000.0
             }
             "#SAMPLESTART" => {
                 chartinfo.preview_time = to_millis(
@@ -290,6 +335,7 @@ pub(crate) fn from_sm_generic(
                 raw_stops = content.or_default_empty(ChartDefaults::RAW_STOPS);
                 stops = parse_stops(&raw_stops);
             }
+            "#TIMESIGNATURES" => time_signatures = parse_time_signatures(content),
             "#NOTES" => raw_notes = content.or_default_empty(ChartDefaults::RAW_NOTES),
             _ => {}
         }
@@ -297,7 +343,8 @@ pub(crate) fn from_sm_generic(
 
     let bpms_and_stops = merge_bpm_and_stops(bpms.0, bpms.1, stops.0, stops.1);
 
-    let timing_points = process_timing_points(&bpms_and_stops, chartinfo.audio_offset);
+    let mut timing_points = process_timing_points(&bpms_and_stops, chartinfo.audio_offset);
+    process_time_signatures(&time_signatures, &mut timing_points, &bpms_and_stops, chartinfo.audio_offset);
 
     let hitobjects = process_notes(&raw_notes, &mut chartinfo, &bpms_and_stops);
 
@@ -307,6 +354,7 @@ pub(crate) fn from_sm_generic(
         timing_points,
         hitobjects,
         None,
+        None,
     ))
 }
 
@@ -0,0 +1,580 @@
+use crate::errors;
+use crate::models::common::*;
+use crate::models::generic::{
+    BranchCondition,
+    BranchSection,
+    BranchType,
+    ChartInfo,
+    GenericTaikoChart,
+    GogoSection,
+    KeySound,
+    Metadata,
+    TaikoCourse,
+    TaikoHitObject,
+    TaikoHitObjects,
+    TimeSignature,
+    TimingChange,
+    TimingPoint,
+    TimingPoints,
+};
+use crate::utils::string::{remove_comments, StrDefaultExtension, StrNumericDefaultExtension};
+use crate::utils::time::to_millis;
+
+#[derive(Debug, Clone, Default)]
+struct CourseHeader {
+    course: Option<TaikoCourse>,
+    level: u8,
+    balloons: Vec<u32>,
+    branch_balloons: [Vec<u32>; 3],
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TimingState {
+    time: f64,
+    beat: Beat,
+    bpm: f64,
+    measure: Beat,
+    signature: TimeSignature,
+    scroll: f64,
+    gogo_start: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BranchState {
+    snapshot: TimingState,
+    pending_roll: Option<usize>,
+    condition: BranchCondition,
+    expert_requirement: f32,
+    master_requirement: f32,
+    timing_branch: Option<BranchType>,
+}
+
+enum MeasureItem {
+    Note(char),
+    Command(String, String),
+}
+
+struct CourseParser<'a> {
+    header: &'a CourseHeader,
+    state: TimingState,
+    branch: Option<BranchType>,
+    branch_state: Option<BranchState>,
+    hitobjects: TaikoHitObjects,
+    timing_points: TimingPoints,
+    gogo_sections: Vec<GogoSection>,
+    branches: Vec<BranchSection>,
+    pending_roll: Option<usize>,
+    balloon_index: usize,
+    branch_balloon_index: [usize; 3],
+}
+
+#[inline]
+fn branch_index(branch: BranchType) -> usize {
+    match branch {
+        BranchType::Normal => 0,
+        BranchType::Expert => 1,
+        BranchType::Master => 2,
+    }
+}
+
+#[inline]
+fn parse_balloons(raw: &str) -> Vec<u32> {
+    raw.split(',')
+        .filter_map(|s| s.trim().parse::<u32>().ok())
+        .collect()
+}
+
+#[inline]
+fn get_tja_note_type(note: char) -> Option<TaikoHitobject> {
+    match note {
+        '1' => Some(TaikoHitobject::don()),
+        '2' => Some(TaikoHitobject::kat()),
+        '3' | 'A' => Some(TaikoHitobject::bonus_don()),
+        '4' | 'B' => Some(TaikoHitobject::bonus_kat()),
+        '5' => Some(TaikoHitobject::drum_roll(0.0)),
+        '6' => Some(TaikoHitobject::bonus_drum_roll(0.0)),
+        '7' | '9' => Some(TaikoHitobject::balloon(0.0)),
+        _ => None,
+    }
+}
+
+// whole signatures stay exact, fractional ones get snapped
+fn parse_measure_signature(raw: &str) -> Option<Beat> {
+    let (numerator, denominator) = raw.split_once('/')?;
+    let numerator = numerator.trim().parse::<f64>().ok()?;
+    let denominator = denominator.trim().parse::<f64>().ok()?;
+
+    if numerator <= 0.0 || denominator <= 0.0 {
+        return None;
+    }
+
+    if numerator.fract() == 0.0 && denominator.fract() == 0.0 && denominator <= u32::MAX as f64 {
+        return Some(Beat::new(4 * numerator as i64, denominator as u32));
+    }
+
+    Some(Beat::from_f64(4.0 * numerator / denominator))
+}
+
+impl<'a> CourseParser<'a> {
+    fn new(header: &'a CourseHeader, start_time: f64, bpm: f64) -> Self {
+        let mut timing_points = TimingPoints::with_capacity(64);
+        timing_points.add(
+            start_time,
+            Beat::ZERO,
+            String::new(),
+            TimingChange {
+                change_type: TimingChangeType::Bpm,
+                value: bpm as f32,
+            },
+        );
+
+        Self {
+            header,
+            state: TimingState {
+                time: start_time,
+                beat: Beat::ZERO,
+                bpm,
+                measure: Beat::whole(4),
+                signature: TimeSignature::default(),
+                scroll: 1.0,
+                gogo_start: None,
+            },
+            branch: None,
+            branch_state: None,
+            hitobjects: TaikoHitObjects::with_capacity(1024),
+            timing_points,
+            gogo_sections: Vec::new(),
+            branches: Vec::new(),
+            pending_roll: None,
+            balloon_index: 0,
+            branch_balloon_index: [0; 3],
+        }
+    }
+
+    // timing changes are repeated in every branch, only keep the ones from the first branch we parse
+    #[inline]
+    fn records_timing(&self) -> bool {
+        match (self.branch, self.branch_state) {
+            (Some(branch), Some(state)) => state.timing_branch == Some(branch),
+            _ => true,
+        }
+    }
+
+    fn add_timing_point(&mut self, change_type: TimingChangeType, value: f32) {
+        if !self.records_timing() {
+            return;
+        }
+
+        self.timing_points.add(
+            self.state.time,
+            self.state.beat,
+            String::new(),
+            TimingChange { change_type, value },
+        );
+    }
+
+    fn add_time_signature(&mut self, signature: TimeSignature) {
+        if !self.records_timing() {
+            return;
+        }
+
+        self.timing_points.push(TimingPoint::time_signature(
+            self.state.time,
+            self.state.beat,
+            signature,
+        ));
+    }
+
+    fn next_balloon_hits(&mut self) -> u32 {
+        if let Some(branch) = self.branch {
+            let idx = branch_index(branch);
+            let balloons = &self.header.branch_balloons[idx];
+            if !balloons.is_empty() {
+                let hits = balloons.get(self.branch_balloon_index[idx]).copied().unwrap_or(5);
+                self.branch_balloon_index[idx] += 1;
+                return hits;
+            }
+        }
+
+        let hits = self.header.balloons.get(self.balloon_index).copied().unwrap_or(5);
+        self.balloon_index += 1;
+        hits
+    }
+
+    fn close_branch(&mut self) {
+        if let Some(state) = self.branch_state.take() {
+            self.branches.push(BranchSection {
+                start_time: state.snapshot.time,
+                end_time: self.state.time,
+                condition: state.condition,
+                expert_requirement: state.expert_requirement,
+                master_requirement: state.master_requirement,
+            });
+        }
+        self.branch = None;
+    }
+
+    fn process_branch_command(&mut self, command: &str, value: &str) {
+        match command {
+            "#BRANCHSTART" => {
+                self.close_branch();
+
+                let params: Vec<&str> = value.split(',').map(|s| s.trim()).collect();
+                let condition = match params.first().map(|s| s.to_lowercase()) {
+                    Some(c) if c == "r" => BranchCondition::Roll,
+                    Some(c) if c == "s" => BranchCondition::Score,
+                    _ => BranchCondition::Accuracy,
+                };
+
+                self.branch_state = Some(BranchState {
+                    snapshot: self.state,
+                    pending_roll: self.pending_roll,
+                    condition,
+                    expert_requirement: params.get(1).map_or(0.0, |s| s.or_default_empty_as(0.0)),
+                    master_requirement: params.get(2).map_or(0.0, |s| s.or_default_empty_as(0.0)),
+                    timing_branch: None,
+                });
+            }
+            "#N" | "#E" | "#M" => {
+                let branch = match command {
+                    "#N" => BranchType::Normal,
+                    "#E" => BranchType::Expert,
+                    _ => BranchType::Master,
+                };
+
+                if let Some(state) = self.branch_state.as_mut() {
+                    if state.timing_branch.is_none() {
+                        state.timing_branch = Some(branch);
+                    } else {
+                        self.state = state.snapshot;
+                        self.pending_roll = state.pending_roll;
+                    }
+                }
+
+                self.branch = Some(branch);
+            }
+            "#BRANCHEND" => self.close_branch(),
+            _ => {}
+        }
+    }
+
+    fn process_command(&mut self, command: &str, value: &str) {
+        match command {
+            "#BPMCHANGE" => {
+                let bpm: f64 = value.or_default_empty_as(self.state.bpm);
+                if bpm > 0.0 {
+                    self.state.bpm = bpm;
+                    self.add_timing_point(TimingChangeType::Bpm, bpm as f32);
+                }
+            }
+            "#MEASURE" => {
+                if let Some(measure) = parse_measure_signature(value) {
+                    self.state.measure = measure;
+
+                    // fractional signatures still get a close enough one
+                    let signature = value
+                        .parse()
+                        .unwrap_or_else(|_| TimeSignature::from_beats_per_measure(measure.as_f32()));
+                    if signature != self.state.signature {
+                        self.state.signature = signature;
+                        self.add_time_signature(signature);
+                    }
+                }
+            }
+            "#SCROLL" => {
+                let scroll: f64 = value.or_default_empty_as(1.0);
+                self.state.scroll = scroll;
+                self.add_timing_point(TimingChangeType::Sv, scroll as f32);
+            }
+            "#DELAY" => {
+                let delay = to_millis(value.or_default_empty_as(0.0f32)) as f64;
+                if delay != 0.0 {
+                    self.add_timing_point(TimingChangeType::Stop, delay as f32);
+                    self.state.time += delay;
+                }
+            }
+            "#GOGOSTART" if self.state.gogo_start.is_none() => {
+                self.state.gogo_start = Some(self.state.time);
+            }
+            "#GOGOEND" => {
+                if let Some(start_time) = self.state.gogo_start.take() {
+                    if self.records_timing() {
+                        self.gogo_sections.push(GogoSection {
+                            start_time,
+                            end_time: self.state.time,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn process_note(&mut self, note: char) {
+        let time = self.state.time;
+        let beat = self.state.beat;
+
+        if note == '8' {
+            if let Some(idx) = self.pending_roll.take() {
+                let roll = &mut self.hitobjects.objects[idx];
+                roll.note.end_time = Some(time);
+                roll.end_beat = Some(beat);
+            }
+            return;
+        }
+
+        let Some(taiko_note) = get_tja_note_type(note) else {
+            return;
+        };
+
+        // a new roll or note can't start while we're still inside a roll
+        if self.pending_roll.is_some() {
+            return;
+        }
+
+        let balloon_hits = if taiko_note.note_type == TaikoHitobjectType::Balloon {
+            Some(self.next_balloon_hits())
+        } else {
+            None
+        };
+
+        let object = TaikoHitObject {
+            time,
+            beat,
+            keysound: KeySound::default(),
+            note: taiko_note,
+            end_beat: None,
+            balloon_hits,
+            branch: self.branch,
+        };
+
+        let is_roll = object.is_roll();
+        self.hitobjects.add_hitobject_sorted(object);
+
+        if is_roll {
+            let idx = self.hitobjects.objects
+                .iter()
+                .rposition(|obj| obj.time == time && obj.is_roll())
+                .unwrap_or(self.hitobjects.objects.len() - 1);
+            self.pending_roll = Some(idx);
+        }
+    }
+
+    fn process_measure(&mut self, items: Vec<MeasureItem>) {
+        let note_count = items.iter().filter(|i| matches!(i, MeasureItem::Note(_))).count();
+        let mut items = items.into_iter().peekable();
+
+        // commands before the first note apply to the whole measure
+        while let Some(MeasureItem::Command(command, value)) = items.peek() {
+            self.process_command(command, value);
+            items.next();
+        }
+
+        let measure_beats = self.state.measure;
+
+        if note_count == 0 {
+            for item in items {
+                if let MeasureItem::Command(command, value) = item {
+                    self.process_command(&command, &value);
+                }
+            }
+            self.state.time += measure_beats.as_f64() * 60_000.0 / self.state.bpm;
+            self.state.beat += measure_beats;
+            return;
+        }
+
+        let slot_beats = measure_beats / note_count as u32;
+
+        for item in items {
+            match item {
+                MeasureItem::Command(command, value) => self.process_command(&command, &value),
+                MeasureItem::Note(note) => {
+                    self.process_note(note);
+                    self.state.time += slot_beats.as_f64() * 60_000.0 / self.state.bpm;
+                    self.state.beat += slot_beats;
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> (TimingPoints, TaikoHitObjects, Vec<GogoSection>, Vec<BranchSection>) {
+        self.close_branch();
+
+        if let Some(start_time) = self.state.gogo_start.take() {
+            self.gogo_sections.push(GogoSection {
+                start_time,
+                end_time: self.state.time,
+            });
+        }
+
+        // drop rolls that never got closed
+        self.hitobjects.objects.retain(|obj| !obj.is_roll() || obj.end_beat.is_some());
+
+        (self.timing_points, self.hitobjects, self.gogo_sections, self.branches)
+    }
+}
+
+fn split_command(line: &str) -> (String, String) {
+    let line = line.trim();
+    match line.find(|c: char| c.is_whitespace() || c == ':') {
+        Some(idx) => (line[..idx].to_uppercase(), line[idx + 1..].trim().to_string()),
+        None => (line.to_uppercase(), String::new()),
+    }
+}
+
+fn parse_course(
+    lines: &[&str],
+    header: &CourseHeader,
+    start_time: f64,
+    bpm: f64,
+) -> (TimingPoints, TaikoHitObjects, Vec<GogoSection>, Vec<BranchSection>) {
+    let mut parser = CourseParser::new(header, start_time, bpm);
+    let mut measure_items = Vec::new();
+
+    for line in lines {
+        if line.starts_with('#') {
+            let (command, value) = split_command(line);
+            match command.as_str() {
+                "#BRANCHSTART" | "#N" | "#E" | "#M" | "#BRANCHEND" => {
+                    // commands right before a branch belong to the time before it
+                    if !measure_items.iter().any(|item| matches!(item, MeasureItem::Note(_))) {
+                        for item in measure_items.drain(..) {
+                            if let MeasureItem::Command(command, value) = item {
+                                parser.process_command(&command, &value);
+                            }
+                        }
+                    }
+                    parser.process_branch_command(&command, &value);
+                }
+                _ => measure_items.push(MeasureItem::Command(command, value)),
+            }
+            continue;
+        }
+
+        for c in line.chars() {
+            match c {
+                ',' => parser.process_measure(std::mem::take(&mut measure_items)),
+                c if c.is_ascii_alphanumeric() => measure_items.push(MeasureItem::Note(c)),
+                _ => {}
+            }
+        }
+    }
+
+    parser.finish()
+}
+
+pub(crate) fn from_tja_taiko(
+    raw_chart: &str,
+) -> Result<Vec<GenericTaikoChart>, Box<dyn std::error::Error>> {
+    let uncommented_chart = remove_comments(raw_chart.trim_start_matches('\u{feff}'), "//");
+
+    if uncommented_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    chartinfo.key_count = 2;
+    chartinfo.bpm_affects_sv = true;
+
+    let mut bpm = 120.0;
+    let mut offset = 0.0;
+    let mut header = CourseHeader::default();
+    let mut charts = Vec::new();
+
+    let lines: Vec<&str> = uncommented_chart.lines().map(str::trim).collect();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+
+        if line.to_uppercase().starts_with("#START") {
+            let player = line[6..].trim().to_uppercase();
+            let start = i;
+            while i < lines.len() && !lines[i].to_uppercase().starts_with("#END") {
+                i += 1;
+            }
+            let course_lines = &lines[start..i];
+            i += 1;
+
+            let start_time = -offset * 1000.0;
+            let (timing_points, hitobjects, gogo_sections, branches) =
+                parse_course(course_lines, &header, start_time, bpm as f64);
+
+            let course = header.course.unwrap_or(TaikoCourse::Oni);
+            let mut difficulty_name = course.to_tja().to_string();
+            if !player.is_empty() {
+                difficulty_name.push(' ');
+                difficulty_name.push_str(&player);
+            }
+
+            let course_chartinfo = ChartInfo {
+                difficulty_name,
+                audio_offset: start_time,
+                ..chartinfo.clone()
+            };
+
+            charts.push(GenericTaikoChart::new(
+                metadata.clone(),
+                course_chartinfo,
+                timing_points,
+                hitobjects,
+                None,
+                course,
+                header.level,
+                gogo_sections,
+                branches,
+            ));
+
+            // balloons are declared per course, the course itself carries over for double charts
+            header = CourseHeader {
+                course: header.course,
+                ..CourseHeader::default()
+            };
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match key.trim().to_uppercase().as_str() {
+            "TITLE" => metadata.title = value.or_default_empty(ChartDefaults::TITLE),
+            "TITLEEN" => metadata.alt_title = value.to_string(),
+            "SUBTITLE" => {
+                let subtitle = value.trim_start_matches("--").trim_start_matches("++");
+                metadata.source = subtitle.or_default_empty(ChartDefaults::SOURCE);
+            }
+            "GENRE" => metadata.genre = value.or_default_empty(ChartDefaults::GENRE),
+            "MAKER" => metadata.creator = value.or_default_empty(ChartDefaults::CREATOR),
+            "BPM" => bpm = value.or_default_empty_as(120.0f32),
+            "WAVE" => chartinfo.song_path = value.or_default_empty(ChartDefaults::SONG_PATH),
+            "OFFSET" => offset = value.or_default_empty_as(0.0f64),
+            "DEMOSTART" => chartinfo.preview_time = to_millis(value.or_default_empty_as(0.0f32)) as i32,
+            "BGIMAGE" => chartinfo.bg_path = value.to_string(),
+            "BGMOVIE" => chartinfo.video_path = value.to_string(),
+            "COURSE" => {
+                header = CourseHeader {
+                    course: TaikoCourse::from_tja(value),
+                    ..CourseHeader::default()
+                };
+            }
+            "LEVEL" => header.level = value.or_default_empty_as(0u8),
+            "BALLOON" => header.balloons = parse_balloons(value),
+            "BALLOONNOR" => header.branch_balloons[0] = parse_balloons(value),
+            "BALLOONEXP" => header.branch_balloons[1] = parse_balloons(value),
+            "BALLOONMAS" => header.branch_balloons[2] = parse_balloons(value),
+            _ => {}
+        }
+    }
+
+    if charts.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart(
+            "TJA file has no #START/#END blocks".to_string(),
+        )));
+    }
+
+    Ok(charts)
+}
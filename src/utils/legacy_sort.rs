@@ -0,0 +1,154 @@
+use std::cmp::Ordering;
+
+// .NET Framework's introsort that osu! keeps as LegacySortHelper, it isn't stable
+// and difficulty calculation depends on how it shuffles objects that compare equal
+const INTROSORT_SIZE_THRESHOLD: usize = 16;
+
+pub fn legacy_sort<T: Clone, F>(keys: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if keys.len() < 2 {
+        return;
+    }
+
+    let depth_limit = 2 * floor_log2(keys.len());
+    intro_sort(keys, 0, keys.len() - 1, depth_limit, &mut compare);
+}
+
+// not actually floor(log2(n)), it's one more than that but osu! uses it as is
+fn floor_log2(mut n: usize) -> usize {
+    let mut result = 0;
+    while n >= 1 {
+        result += 1;
+        n /= 2;
+    }
+    result
+}
+
+fn intro_sort<T: Clone, F>(keys: &mut [T], lo: usize, mut hi: usize, mut depth_limit: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while hi > lo {
+        let partition_size = hi - lo + 1;
+
+        if partition_size <= INTROSORT_SIZE_THRESHOLD {
+            match partition_size {
+                2 => swap_if_greater(keys, compare, lo, hi),
+                3 => {
+                    swap_if_greater(keys, compare, lo, hi - 1);
+                    swap_if_greater(keys, compare, lo, hi);
+                    swap_if_greater(keys, compare, hi - 1, hi);
+                }
+                _ => insertion_sort(keys, lo, hi, compare),
+            }
+            return;
+        }
+
+        if depth_limit == 0 {
+            heap_sort(keys, lo, hi, compare);
+            return;
+        }
+        depth_limit -= 1;
+
+        let pivot = pick_pivot_and_partition(keys, lo, hi, compare);
+        intro_sort(keys, pivot + 1, hi, depth_limit, compare);
+        // the left scan always moves past lo so the pivot can't be there
+        hi = pivot - 1;
+    }
+}
+
+#[inline]
+fn swap_if_greater<T, F>(keys: &mut [T], compare: &mut F, a: usize, b: usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if a != b && compare(&keys[a], &keys[b]) == Ordering::Greater {
+        keys.swap(a, b);
+    }
+}
+
+fn pick_pivot_and_partition<T: Clone, F>(keys: &mut [T], lo: usize, hi: usize, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let middle = lo + ((hi - lo) >> 1);
+
+    swap_if_greater(keys, compare, lo, middle);
+    swap_if_greater(keys, compare, lo, hi);
+    swap_if_greater(keys, compare, middle, hi);
+
+    let pivot = keys[middle].clone();
+    keys.swap(middle, hi - 1);
+
+    let mut left = lo;
+    let mut right = hi - 1;
+    while left < right {
+        left += 1;
+        while compare(&keys[left], &pivot) == Ordering::Less {
+            left += 1;
+        }
+        right -= 1;
+        while compare(&pivot, &keys[right]) == Ordering::Less {
+            right -= 1;
+        }
+
+        if left >= right {
+            break;
+        }
+        keys.swap(left, right);
+    }
+
+    keys.swap(left, hi - 1);
+    left
+}
+
+fn insertion_sort<T: Clone, F>(keys: &mut [T], lo: usize, hi: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in lo..hi {
+        let t = keys[i + 1].clone();
+        let mut j = i + 1;
+        while j > lo && compare(&t, &keys[j - 1]) == Ordering::Less {
+            keys[j] = keys[j - 1].clone();
+            j -= 1;
+        }
+        keys[j] = t;
+    }
+}
+
+fn heap_sort<T: Clone, F>(keys: &mut [T], lo: usize, hi: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = hi - lo + 1;
+    for i in (1..=n >> 1).rev() {
+        down_heap(keys, i, n, lo, compare);
+    }
+    for i in (2..=n).rev() {
+        keys.swap(lo, lo + i - 1);
+        down_heap(keys, 1, i - 1, lo, compare);
+    }
+}
+
+// the heap is 1-based like the original
+fn down_heap<T: Clone, F>(keys: &mut [T], mut i: usize, n: usize, lo: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let d = keys[lo + i - 1].clone();
+    while i <= n / 2 {
+        let mut child = 2 * i;
+        if child < n && compare(&keys[lo + child - 1], &keys[lo + child]) == Ordering::Less {
+            child += 1;
+        }
+        if compare(&d, &keys[lo + child - 1]) != Ordering::Less {
+            break;
+        }
+        keys[lo + i - 1] = keys[lo + child - 1].clone();
+        i = child;
+    }
+    keys[lo + i - 1] = d;
+}
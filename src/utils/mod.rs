@@ -1,6 +1,7 @@
-pub mod math;
 pub mod rhythm;
 pub mod string;
 pub mod time;
 pub mod serde;
-pub mod quaver;
\ No newline at end of file
+pub mod quaver;
+pub mod random;
+pub mod legacy_sort;
\ No newline at end of file
@@ -0,0 +1,46 @@
+// xorshift RNG from osu!stable, needed to reproduce its random object placement
+pub struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+}
+
+impl LegacyRandom {
+    const INT_TO_REAL: f64 = 1.0 / (i32::MAX as f64 + 1.0);
+    const INT_MASK: u32 = 0x7FFF_FFFF;
+
+    pub fn new(seed: i32) -> Self {
+        Self {
+            x: seed as u32,
+            y: 842_502_087,
+            z: 3_579_807_591,
+            w: 273_326_509,
+        }
+    }
+
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+        self.w
+    }
+
+    #[inline]
+    pub fn next(&mut self) -> i32 {
+        (Self::INT_MASK & self.next_u32()) as i32
+    }
+
+    #[inline]
+    pub fn next_double(&mut self) -> f64 {
+        Self::INT_TO_REAL * self.next() as f64
+    }
+
+    #[inline]
+    pub fn next_range(&mut self, lower_bound: i32, upper_bound: i32) -> i32 {
+        (lower_bound as f64 + self.next_double() * (upper_bound - lower_bound) as f64) as i32
+    }
+}
@@ -22,13 +22,15 @@ where
     let mut current_content = String::with_capacity(string.len());
     let mut current_section = "";
     
-    for line in string.lines().map(str::trim) {
-        if line.starts_with('[') && line.ends_with(']') {
+    // leading whitespace is kept since storyboard commands use it for nesting
+    for line in string.lines().map(str::trim_end) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
             if !current_content.is_empty() && !current_section.is_empty() {
                 lambda(current_section, &current_content)?;
                 current_content.clear();
             }
-            current_section = &line[1..line.len()-1];
+            current_section = &trimmed[1..trimmed.len()-1];
         } else {
             if !current_content.is_empty() {
                 current_content.push('\n');
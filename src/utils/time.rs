@@ -1,4 +1,4 @@
-use crate::models::common::{KeyType, HitObjectRow, TimingChangeType};
+use crate::models::common::{Beat, KeyType, HitObjectRow, TimingChangeType};
 use crate::models::generic::sound::KeySoundRow;
 
 #[inline(always)]
@@ -14,11 +14,11 @@ pub fn to_seconds(number: f32) -> f32 {
 // If you make fun of using a match statement here
 // I will find where you live and personally come to your house and what will happen next will have you praying that you were never born into this world
 pub fn merge_bpm_and_stops(
-    bpms_beats: Vec<f32>,
+    bpms_beats: Vec<Beat>,
     bpms_values: Vec<f32>,
-    stops_beats: Vec<f32>,
+    stops_beats: Vec<Beat>,
     stops_values: Vec<f32>,
-) -> (Vec<f32>, Vec<f32>, Vec<TimingChangeType>) {
+) -> (Vec<Beat>, Vec<f32>, Vec<TimingChangeType>) {
     let mut beats = Vec::new();
     let mut values = Vec::new();
     let mut types = Vec::new();
@@ -40,7 +40,7 @@ pub fn merge_bpm_and_stops(
             }
         },
         (false, false) => {
-            let mut merged: Vec<((f32, f32), TimingChangeType)> =
+            let mut merged: Vec<((Beat, f32), TimingChangeType)> =
                 Vec::with_capacity(bpms_beats.len() + stops_beats.len());
 
             for i in 0..bpms_beats.len() {
@@ -50,11 +50,7 @@ pub fn merge_bpm_and_stops(
                 merged.push(((stops_beats[i], stops_values[i]), TimingChangeType::Stop));
             }
 
-            merged.sort_unstable_by(|a, b| {
-                a.0.0
-                    .partial_cmp(&b.0.0)
-                    .expect("Comparison between NaN values when merging bpm and stops")
-            });
+            merged.sort_unstable_by_key(|a| a.0.0);
 
             for ((beat, value), change_type) in merged {
                 beats.push(beat);
@@ -73,10 +69,10 @@ pub fn merge_bpm_and_stops(
 pub fn find_sliderend_time(
     start_idx: usize,
     key_idx: usize,
-    hitobjects: &[(&i32, &f32, &KeySoundRow, &HitObjectRow)],
-) -> i32 {
+    hitobjects: &[(&f64, &Beat, &KeySoundRow, &HitObjectRow)],
+) -> f64 {
     if start_idx >= hitobjects.len() {
-        return 0;
+        return 0.0;
     }
 
     let start_time = hitobjects[start_idx].0;
@@ -27,16 +27,47 @@ pub(crate) fn to_fsc_generic(
         ..Default::default()
     };
 
-    let timing_points = chart
+    let barline_sections = chart.timing_points.barline_sections(chart.end_time());
+
+    let mut timing_points: Vec<fluxis::TimingPoint> = chart
         .timing_points
-        .bpm_changes()
-        .map(|tp| fluxis::TimingPoint {
-            time: tp.time as f32,
-            bpm: tp.change.value,
-            ..Default::default()
+        .iter()
+        .filter_map(|tp| {
+            let bpm = match tp.change.change_type {
+                TimingChangeType::Bpm => tp.change.value,
+                TimingChangeType::TimeSignature => chart.timing_points.bpm_for_signature_change(tp.time)?,
+                _ => return None,
+            };
+
+            // fluXis signatures count quarter notes
+            let beats = chart.timing_points.signature_at(tp.time).beats_per_measure();
+            let hide_lines = barline_sections
+                .iter()
+                .find(|section| section.start == tp.time)
+                .is_some_and(|section| section.hidden || section.omit_first);
+
+            Some(fluxis::TimingPoint {
+                time: tp.time as f32,
+                bpm,
+                signature: (beats.round() as u32).max(1),
+                hide_lines,
+            })
         })
         .collect();
 
+    // fluXis hides the whole section, so the lines after an omitted first one need their own point
+    for section in barline_sections.iter().filter(|section| section.omit_first && !section.hidden) {
+        if let Some(time) = section.measure_times().nth(1) {
+            timing_points.push(fluxis::TimingPoint {
+                time: time as f32,
+                bpm: section.bpm,
+                signature: (section.beats_per_measure.round() as u32).max(1),
+                hide_lines: false,
+            });
+        }
+    }
+    timing_points.sort_by(|a, b| a.time.total_cmp(&b.time));
+
     let scroll_velocities = chart
         .timing_points
         .sv_changes()
@@ -67,7 +98,7 @@ pub(crate) fn to_fsc_generic(
                 let slider_end_time = if let Some(time) = hitobject.key.slider_end_time() {
                     time
                 } else {
-                    0
+                    0.0
                 };
 
                 fsc_hitobjects.push(fluxis::HitObject {
@@ -97,7 +128,7 @@ pub(crate) fn to_fsc_generic(
         background_file: chart.chartinfo.bg_path.clone(),
         video_file: chart.chartinfo.video_path.clone(),
         metadata,
-        colors: Colors::default(),
+        colors: chart.colors.as_ref().map(Colors::from_generic).unwrap_or_default(),
         hit_objects: fsc_hitobjects,
         timing_points,
         scroll_velocities,
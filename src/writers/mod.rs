@@ -1,4 +1,5 @@
 pub mod osu;
 pub mod stepmania;
 pub mod quaver;
-pub mod fluxis;
\ No newline at end of file
+pub mod fluxis;
+pub mod tja;
\ No newline at end of file
@@ -1,10 +1,13 @@
 use crate::common::TimingChangeType;
-use crate::models::generic::{GenericManiaChart, HitSoundType, SoundBank};
+use crate::models::common::TaikoHitobjectType;
+use crate::models::generic::{
+    self, BranchType, ChartInfo, GenericManiaChart, GenericTaikoChart, HitSoundType, SoundBank,
+};
 use crate::models::osu::{self, *};
 
 #[allow(unused)]
 use crate::errors;
-use crate::KeyType;
+use crate::{GameMode, KeyType};
 
 #[inline(always)]
 fn bpm_to_beatlength(bpm: &f32) -> f32 {
@@ -19,14 +22,166 @@ fn multiplier_to_beatlength(multiplier: &f32) -> f32 {
     -100.0 / multiplier.abs()
 }
 
+// the other way around from HitObject::mania_lane, lane 1 is osu!'s column 0
 #[inline(always)]
 fn column_to_coords(lane: u8, key_count: usize) -> u16 {
     ((lane - 1) as f32 * 512.0 / key_count as f32).ceil() as u16
 }
 
+fn process_metadata(metadata: &generic::Metadata, chartinfo: &ChartInfo) -> osu::Metadata {
+    osu::Metadata {
+        title: metadata.title.replace("\n", ""),
+        title_unicode: metadata.alt_title.clone(),
+        artist: metadata.artist.clone(),
+        artist_unicode: metadata.alt_artist.clone(),
+        creator: metadata.creator.clone(),
+        version: chartinfo.difficulty_name.clone(),
+        source: metadata.source.clone(),
+        tags: metadata.tags.clone(),
+        beatmap_id: -1,
+        beatmap_set_id: -1,
+    }
+}
+
+fn process_events(chartinfo: &ChartInfo) -> osu::Events {
+    osu::Events {
+        background: if !chartinfo.bg_path.trim().is_empty() {
+            Some(osu::Background {
+                filename: chartinfo.bg_path.clone(),
+                x_offset: 0,
+                y_offset: 0,
+            })
+        } else {
+            None
+        },
+        video: if !chartinfo.video_path.trim().is_empty() {
+            Some(osu::Video {
+                start_time: 0,
+                filename: chartinfo.video_path.clone(),
+                x_offset: 0,
+                y_offset: 0,
+            })
+        } else {
+            None
+        },
+        ..Default::default()
+    }
+}
+
+// points without hitsound context fall back to the normal set at full volume
+fn to_osu_timing_point(
+    timing_point: &generic::TimingPoint,
+    beat_length: f32,
+    uninherited: bool,
+    effects: i32,
+    meter: i32,
+) -> osu::TimingPoint {
+    let sample = timing_point.sample.unwrap_or(generic::SampleContext {
+        sample_set: 1,
+        sample_index: 0,
+        volume: 100,
+    });
+
+    osu::TimingPoint {
+        time: timing_point.time as f32,
+        beat_length,
+        meter,
+        sample_set: sample.sample_set as i32,
+        sample_index: sample.sample_index as i32,
+        volume: sample.volume as i32,
+        uninherited,
+        effects,
+    }
+}
+
+fn effects_bits(timing_point: &generic::TimingPoint) -> i32 {
+    timing_point.effects.map_or(0, |effects| effects.to_osu_bits())
+}
+
+// osu! meters count quarter notes, other signatures get rounded
+fn osu_meters(timing_points: &generic::TimingPoints) -> Vec<(f64, i32)> {
+    timing_points
+        .time_signature_changes()
+        .filter_map(|p| Some((p.time, (p.signature?.beats_per_measure().round() as i32).max(1))))
+        .collect()
+}
+
+fn osu_meter_at(meters: &[(f64, i32)], time: f64) -> i32 {
+    match meters.partition_point(|(meter_time, _)| *meter_time <= time) {
+        0 => 4,
+        index => meters[index - 1].1,
+    }
+}
+
+// osu! only omits the first barline of an uninherited point, so a hidden section gets one on every measure
+// they reset the sv, an inherited point right after restores it
+fn add_hidden_barlines(timing_points: &mut osu::TimingPoints, sections: &[generic::BarlineSection]) {
+    if !sections.iter().any(|section| section.hidden) {
+        return;
+    }
+
+    timing_points.sort_by_time();
+    let mut added = Vec::new();
+
+    for section in sections.iter().filter(|section| section.hidden) {
+        let start = section.start as f32;
+        let Some(uninherited) = timing_points.timing_points.iter().find(|tp| tp.is_uninherited() && tp.time == start).copied() else {
+            continue;
+        };
+
+        for time in section.measure_times().skip(1).map(|time| time as f32) {
+            let current = timing_points
+                .timing_points
+                .iter()
+                .take_while(|tp| tp.time <= time)
+                .last()
+                .copied()
+                .unwrap_or(uninherited);
+            let sv = timing_points.slider_velocity_at(time);
+
+            let barline = osu::TimingPoint {
+                time,
+                sample_set: current.sample_set,
+                sample_index: current.sample_index,
+                volume: current.volume,
+                effects: (uninherited.effects & !1) | (current.effects & 1),
+                ..uninherited
+            };
+            added.push(barline);
+
+            if sv != 1.0 {
+                added.push(osu::TimingPoint {
+                    beat_length: multiplier_to_beatlength(&sv),
+                    uninherited: false,
+                    effects: current.effects & 1,
+                    ..barline
+                });
+            }
+        }
+    }
+
+    timing_points.timing_points.extend(added);
+    timing_points.sort_by_time();
+}
+
 pub(crate) fn to_osu_generic(
     chart: &GenericManiaChart,
 ) -> Result<String, Box<dyn std::error::Error>> {
+    to_osu_generic_with_version(chart, osu::LATEST_FORMAT_VERSION)
+}
+
+pub(crate) fn to_osu_generic_with_version(
+    chart: &GenericManiaChart,
+    version: u8,
+) -> Result<String, Box<dyn std::error::Error>> {
+    if !(3..=osu::LATEST_FORMAT_VERSION).contains(&version) {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidVersion(
+            version,
+            format!("v3 to v{}", osu::LATEST_FORMAT_VERSION),
+            "osu!".to_string(),
+        )));
+    }
+
     let key_count = chart.chartinfo.key_count;
 
     let general = osu::General {
@@ -51,46 +206,16 @@ pub(crate) fn to_osu_generic(
         ..Default::default()
     });
 
-    let metadata = osu::Metadata {
-        title: chart.metadata.title.replace("\n", ""),
-        title_unicode: chart.metadata.alt_title.clone(),
-        artist: chart.metadata.artist.clone(),
-        artist_unicode: chart.metadata.alt_artist.clone(),
-        creator: chart.metadata.creator.clone(),
-        version: chart.chartinfo.difficulty_name.clone(),
-        source: chart.metadata.source.clone(),
-        tags: chart.metadata.tags.clone(),
-        beatmap_id: -1,
-        beatmap_set_id: -1,
-    };
+    let metadata = process_metadata(&chart.metadata, &chart.chartinfo);
 
     let difficulty = osu::Difficulty {
+        hp_drain_rate: chart.chartinfo.hp,
         circle_size: key_count as f32,
+        overall_difficulty: chart.chartinfo.od,
         ..Default::default()
     };
 
-    let mut events = osu::Events {
-        background: if !chart.chartinfo.bg_path.trim().is_empty() {
-            Some(osu::Background {
-                filename: chart.chartinfo.bg_path.clone(),
-                x_offset: 0,
-                y_offset: 0,
-            })
-        } else {
-            None
-        },
-        video: if !chart.chartinfo.video_path.trim().is_empty() {
-            Some(osu::Video {
-                start_time: 0,
-                filename: chart.chartinfo.video_path.clone(),
-                x_offset: 0,
-                y_offset: 0,
-            })
-        } else {
-            None
-        },
-        ..Default::default()
-    };
+    let mut events = process_events(&chart.chartinfo);
 
     if let Some(ref soundbank) = chart.soundbank {
         for sound_effect in &soundbank.sound_effects {
@@ -98,7 +223,7 @@ pub(crate) fn to_osu_generic(
                 .get_sound_sample(sound_effect.sample)
                 .unwrap_or_default();
             events.add_sample(
-                sound_effect.time,
+                sound_effect.time.round() as i32,
                 0,
                 sample_path,
                 sound_effect.volume,
@@ -107,46 +232,54 @@ pub(crate) fn to_osu_generic(
     }
 
     let mut timing_points = osu::TimingPoints::new();
+    let meters = osu_meters(&chart.timing_points);
 
     for timing_point in chart.timing_points.iter() {
         match timing_point.change.change_type {
             TimingChangeType::Bpm => {
-                timing_points.add_timing_point(osu::TimingPoint {
-                    time: timing_point.time as f32,
-                    beat_length: bpm_to_beatlength(&timing_point.change.value),
-                    meter: 4,
-                    sample_set: 1,
-                    sample_index: 0,
-                    volume: 100,
-                    uninherited: true,
-                    effects: 0,
-                });
+                timing_points.add_timing_point(to_osu_timing_point(
+                    timing_point,
+                    bpm_to_beatlength(&timing_point.change.value),
+                    true,
+                    effects_bits(timing_point),
+                    osu_meter_at(&meters, timing_point.time),
+                ));
             }
             TimingChangeType::Sv => {
                 // TODO: allow to configure which SV groups we want to convert?
                 //       only assume default group for now
                 if timing_point.has_default_group_qua() {
-                    timing_points.add_timing_point(osu::TimingPoint {
-                        time: timing_point.time as f32,
-                        beat_length: multiplier_to_beatlength(&timing_point.change.value),
-                        meter: 4,
-                        sample_set: 1,
-                        sample_index: 0,
-                        volume: 100,
-                        uninherited: false,
-                        effects: 0,
-                    });
+                    timing_points.add_timing_point(to_osu_timing_point(
+                        timing_point,
+                        multiplier_to_beatlength(&timing_point.change.value),
+                        false,
+                        effects_bits(timing_point),
+                        osu_meter_at(&meters, timing_point.time),
+                    ));
+                }
+            }
+            TimingChangeType::TimeSignature => {
+                if let Some(bpm) = chart.timing_points.bpm_for_signature_change(timing_point.time) {
+                    timing_points.add_timing_point(to_osu_timing_point(
+                        timing_point,
+                        bpm_to_beatlength(&bpm),
+                        true,
+                        effects_bits(timing_point),
+                        osu_meter_at(&meters, timing_point.time),
+                    ));
                 }
             }
             _ => {}
         }
     }
+    add_hidden_barlines(&mut timing_points, &chart.timing_points.barline_sections(chart.end_time()));
 
     let mut hitobjects = osu::HitObjects::new();
     let mut soundbank = chart.soundbank.clone().unwrap_or(SoundBank::new());
 
+    // hitobjects are written on whole milliseconds, timing points keep their fraction
     for hitobject in chart.hitobjects.iter() {
-        let time = hitobject.time as f32;
+        let time = hitobject.time.round() as f32;
         let lane = hitobject.lane;
         let coords = column_to_coords(lane, key_count as usize);
 
@@ -196,7 +329,7 @@ pub(crate) fn to_osu_generic(
             }
             KeyType::SliderStart => {
                 let slider_end_time = if let Some(end_time) = hitobject.key.slider_end_time() {
-                    end_time
+                    end_time.round() as i32
                 } else {
                     0
                 };
@@ -216,15 +349,180 @@ pub(crate) fn to_osu_generic(
         }
     }
 
-    let osu_file = OsuFile {
+    let colours = chart
+        .colors
+        .as_ref()
+        .filter(|c| !c.is_empty())
+        .map(osu::Colours::from_generic);
+
+    let mut osu_file = OsuFile {
+        version: osu::LATEST_FORMAT_VERSION,
         general,
         editor,
         metadata,
         difficulty,
         events,
         timing_points,
+        colours,
         hitobjects,
     };
+    osu_file.set_format_version(version);
 
     Ok(osu_file.to_str_mania(&mut soundbank))
-}
\ No newline at end of file
+}
+pub(crate) fn to_osu_taiko(
+    chart: &GenericTaikoChart,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let general = osu::General {
+        audio_filename: chart.chartinfo.song_path.clone(),
+        audio_lead_in: 0,
+        preview_time: chart.chartinfo.preview_time,
+        countdown: 0,
+        sample_set: osu::SampleSet::Normal,
+        stack_leniency: 0.7,
+        mode: 1,
+        letterbox_in_breaks: false,
+        widescreen_storyboard: true,
+        ..Default::default()
+    };
+
+    let editor = Some(osu::Editor {
+        distance_spacing: Some(1.0),
+        beat_divisor: Some(4),
+        grid_size: Some(4),
+        timeline_zoom: Some(1.0),
+        ..Default::default()
+    });
+
+    let metadata = process_metadata(&chart.metadata, &chart.chartinfo);
+
+    let difficulty = osu::Difficulty {
+        circle_size: 5.0,
+        hp_drain_rate: chart.chartinfo.hp,
+        overall_difficulty: chart.chartinfo.od,
+        ..Default::default()
+    };
+
+    let events = process_events(&chart.chartinfo);
+
+    let mut timing_points = osu::TimingPoints::new();
+    let meters = osu_meters(&chart.timing_points);
+
+    for timing_point in chart.timing_points.iter() {
+        // gogo sections decide kiai in osu!taiko, the rest of the effects are kept
+        let effects = (effects_bits(timing_point) & !1) | chart.is_gogo_at(timing_point.time) as i32;
+
+        match timing_point.change.change_type {
+            TimingChangeType::Bpm => {
+                timing_points.add_timing_point(to_osu_timing_point(
+                    timing_point,
+                    bpm_to_beatlength(&timing_point.change.value),
+                    true,
+                    effects,
+                    osu_meter_at(&meters, timing_point.time),
+                ));
+            }
+            TimingChangeType::Sv if timing_point.has_default_group_qua() => {
+                timing_points.add_timing_point(to_osu_timing_point(
+                    timing_point,
+                    multiplier_to_beatlength(&timing_point.change.value),
+                    false,
+                    effects,
+                    osu_meter_at(&meters, timing_point.time),
+                ));
+            }
+            TimingChangeType::TimeSignature => {
+                if let Some(bpm) = chart.timing_points.bpm_for_signature_change(timing_point.time) {
+                    timing_points.add_timing_point(to_osu_timing_point(
+                        timing_point,
+                        bpm_to_beatlength(&bpm),
+                        true,
+                        effects,
+                        osu_meter_at(&meters, timing_point.time),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // kiai can only be toggled by a timing point, add inherited ones where gogo sections start or end
+    for section in &chart.gogo_sections {
+        for time in [section.start_time, section.end_time] {
+            if time == f64::MAX || timing_points.timing_points.iter().any(|tp| tp.time == time as f32) {
+                continue;
+            }
+
+            let sv = timing_points.slider_velocity_at(time as f32);
+            let sample = timing_points.sample_point_at(time as f32);
+            timing_points.add_timing_point(osu::TimingPoint {
+                time: time as f32,
+                beat_length: multiplier_to_beatlength(&sv),
+                meter: osu_meter_at(&meters, time),
+                sample_set: sample.map_or(1, |tp| tp.sample_set),
+                sample_index: sample.map_or(0, |tp| tp.sample_index),
+                volume: sample.map_or(100, |tp| tp.volume),
+                uninherited: false,
+                effects: if chart.is_gogo_at(time) { 1 } else { 0 },
+            });
+        }
+    }
+    timing_points.sort_by_time();
+    add_hidden_barlines(&mut timing_points, &chart.timing_points.barline_sections(chart.end_time()));
+
+    let mut hitobjects = osu::HitObjects::new();
+    let slider_multiplier = difficulty.slider_multiplier;
+
+    // osu!taiko has no branches, always take the hardest one
+    for hitobject in chart.hitobjects.iter_branch(BranchType::Master) {
+        let time = hitobject.time.round() as f32;
+        let finish = if hitobject.is_big() { 4 } else { 0 };
+        let end_time = hitobject.note.end_time.unwrap_or(hitobject.time);
+
+        let (object_type, hit_sound, object_params) = match hitobject.note.note_type {
+            TaikoHitobjectType::Don | TaikoHitobjectType::BonusDon => (1, finish, Vec::new()),
+            TaikoHitobjectType::Kat | TaikoHitobjectType::BonusKat => (1, 8 | finish, Vec::new()),
+            TaikoHitobjectType::DrumRoll | TaikoHitobjectType::BonusDrumRoll => {
+                let beat_length = timing_points.beat_length_at(time);
+                let sv = timing_points.slider_velocity_at(time);
+                let length = (end_time - hitobject.time) as f32 * slider_multiplier * 100.0 * sv / beat_length;
+
+                (2, finish, vec![
+                    format!("L|{}:192", 256 + length.round() as i32),
+                    "1".to_string(),
+                    length.to_string(),
+                ])
+            }
+            TaikoHitobjectType::Balloon => (8, 0, vec![(end_time.round() as i32).to_string()]),
+            TaikoHitobjectType::Empty | TaikoHitobjectType::Unknown => continue,
+        };
+
+        hitobjects.add_hit_object(osu::HitObject {
+            x: 256,
+            y: 192,
+            time,
+            object_type,
+            hit_sound,
+            object_params,
+            hit_sample: osu::HitSample::default(),
+        });
+    }
+
+    let osu_file = OsuFile {
+        version: osu::LATEST_FORMAT_VERSION,
+        general,
+        editor,
+        metadata,
+        difficulty,
+        events,
+        timing_points,
+        colours: None,
+        hitobjects,
+    };
+
+    Ok(osu_file.to_str())
+}
+
+pub(crate) fn to_osb(storyboard: &Storyboard) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(storyboard.to_str())
+}
@@ -58,10 +58,23 @@ pub(crate) fn to_qua_generic(
 
     let timing_points = chart
         .timing_points
-        .bpm_changes()
-        .map(|tp| quaver::TimingPoint {
-            start_time: tp.time as f32,
-            bpm: tp.change.value,
+        .iter()
+        .filter_map(|tp| {
+            let bpm = match tp.change.change_type {
+                TimingChangeType::Bpm => tp.change.value,
+                TimingChangeType::TimeSignature => chart.timing_points.bpm_for_signature_change(tp.time)?,
+                _ => return None,
+            };
+
+            // anything that isn't 3/4 is written as 4/4
+            let beats = chart.timing_points.signature_at(tp.time).beats_per_measure();
+            let signature = (beats == 3.0).then_some(quaver::TimeSignature::Triple);
+
+            Some(quaver::TimingPoint {
+                start_time: tp.time as f32,
+                bpm,
+                signature,
+            })
         })
         .collect();
 
@@ -94,7 +107,7 @@ pub(crate) fn to_qua_generic(
     let mut qua_hitobjects = Vec::new();
 
     for hitobject in chart.hitobjects.iter() {
-        let time = hitobject.time;
+        let time = hitobject.time.round() as i32;
         let lane = hitobject.lane;
         let keysound = hitobject.keysound;
         let timing_group = hitobject.group.clone();
@@ -112,7 +125,7 @@ pub(crate) fn to_qua_generic(
                 });
             },
             KeyType::SliderStart => {
-                let slider_end_time = hitobject.key.slider_end_time().unwrap_or(0);
+                let slider_end_time = hitobject.key.slider_end_time().map_or(0, |end_time| end_time.round() as i32);
 
                 qua_hitobjects.push(quaver::HitObject {
                     start_time: time,
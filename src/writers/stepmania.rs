@@ -1,8 +1,8 @@
 use crate::models::common::*;
 use crate::models::generic::GenericManiaChart;
 use crate::models::timeline::HitObjectTimeline;
-use crate::utils::math::approx_eq;
-use crate::utils::rhythm::{MeasureRange, snap_to_nearest_note_type_normed};
+use crate::models::beat::ROWS_PER_BEAT;
+use crate::utils::rhythm::BEAT_DENOMS;
 use crate::utils::string::add_key_value_template;
 use crate::utils::time::to_seconds;
 
@@ -26,67 +26,36 @@ fn sm_row_to_str(row: &[Key]) -> String {
     result
 }
 
-#[inline]
-fn pad_measure(rows: &[HitObjectRow], range: &MeasureRange) -> Measure {
-    let key_count = rows.first().map_or(0, |row| row.keys.len());
-
-    if range.is_empty() {
-        return vec![HitObjectRow::empty(0, 0.0, key_count); 4];
-    }
-
-    let measure: Vec<&HitObjectRow> = rows
-        .iter()
-        .skip(range.0)
-        .take(range.1 - range.0)
-        .collect();
-
-    if measure.is_empty() {
-        return vec![HitObjectRow::empty(0, 0.0, key_count); 4];
-    }
-
-    let measure_start_beat = measure[0].beat;
-    let measure_start_time = measure[0].time;
-    let normalized_measure: Vec<_> = measure
+// picks the coarsest StepMania row count that puts every row exactly on the grid,
+// anything finer than 192nds gets snapped
+fn pad_measure(rows: &[&HitObjectRow], measure_start: Beat, key_count: usize) -> Measure {
+    let rows_per_beat = BEAT_DENOMS
         .iter()
-        .map(|row| (row.time, row.beat - measure_start_beat, &row.keys))
+        .map(|&row_count| row_count as u32 / 4)
+        .find(|&rows_per_beat| {
+            rows.iter().all(|row| (row.beat - measure_start).row(rows_per_beat).is_some())
+        })
+        .unwrap_or(ROWS_PER_BEAT);
+    let row_count = 4 * rows_per_beat as usize;
+
+    let mut padded_measure: Measure = (0..row_count)
+        .map(|index| {
+            let beat = measure_start + Beat::from_row(index as i64, rows_per_beat);
+            HitObjectRow::empty(0.0, beat, key_count)
+        })
         .collect();
 
-    let mut minimal_beat_diff = 4.0;
-    for i in 1..normalized_measure.len() {
-        let diff = normalized_measure[i].1 - normalized_measure[i - 1].1;
-        if diff < minimal_beat_diff && diff > 1e-5 {
-            minimal_beat_diff = diff;
-        }
-    }
-
-    let snapped_beat_diff = snap_to_nearest_note_type_normed(minimal_beat_diff);
-    let row_count = (4.0 / snapped_beat_diff).round() as usize;
-
-    let expected_beats: Vec<f32> = (0..row_count)
-        .map(|k| k as f32 * snapped_beat_diff)
-        .collect();
+    for row in rows {
+        let index = (row.beat - measure_start).nearest_row(rows_per_beat).clamp(0, row_count as i64 - 1) as usize;
+        let padded_row = &mut padded_measure[index];
+        padded_row.time = row.time;
 
-    let mut padded_measure = Vec::with_capacity(row_count);
-    let mut measure_iter = normalized_measure.iter().peekable();
-
-    for &expected_beat in &expected_beats {
-        if let Some(row) = measure_iter.peek() {
-            if approx_eq(row.1, expected_beat, 0.15) {
-                padded_measure.push(HitObjectRow {
-                    time: row.0,
-                    beat: measure_start_beat + expected_beat,
-                    keys: row.2.clone(),
-                });
-                measure_iter.next();
-                continue;
+        // rows merged by snapping keep whatever was placed first
+        for (padded_key, key) in padded_row.keys.iter_mut().zip(&row.keys) {
+            if padded_key.key_type == KeyType::Empty {
+                *padded_key = *key;
             }
         }
-        let empty_time = measure_start_time + ((expected_beat / 4.0) * 1000.0) as i32;
-        padded_measure.push(HitObjectRow::empty(
-            empty_time,
-            measure_start_beat + expected_beat,
-            key_count,
-        ));
     }
 
     padded_measure
@@ -97,56 +66,29 @@ pub(crate) fn to_sm_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn
     let mut bpm_template = String::new();
     let mut notes_template = String::new();
 
-    let mut prev_beat = 0.0;
-    let mut prev_measure = 0;
-    let mut prev_measure_beat = 0.0;
-    let mut current_measure_index = 0;
-
-    let scale_factor = 24.0;
-    let beats_per_measure = 4.0;
-    let beats_per_measure_scaled = scale_factor * beats_per_measure;
-
+    // note data is always split into 4 beat measures, time signatures only move the barlines
     let key_count = chart
         .hitobjects
         .iter()
-        .map(|obj| obj.lane as usize + 1)
+        .map(|obj| obj.lane as usize)
         .max()
         .unwrap_or(4);
     let rows = HitObjectTimeline::to_rows(&chart.hitobjects.objects, key_count);
-
-    let mut measure_indices: Vec<MeasureRange> = Vec::with_capacity(85);
-    let mut padded_measures: Vec<Measure> = Vec::with_capacity(rows.len() * 2);
     let bpms: Vec<_> = chart.timing_points.bpm_changes().collect();
 
     // get measures
-    for (row_index, row) in rows.iter().enumerate() {
-        let beat = row.beat;
-        let beat_scaled = (beat * scale_factor).round();
-        let measure = (beat_scaled / beats_per_measure_scaled) as u32;
-
-        if measure != prev_measure {
-            measure_indices.push(MeasureRange(current_measure_index, row_index, false));
-
-            if beat - prev_measure_beat > 5.0 {
-                let empty_measure_count = ((beat - prev_beat) / 4.0).floor() as usize;
-
-                for _ in 0..empty_measure_count {
-                    measure_indices.push(MeasureRange(current_measure_index, row_index, true));
-                    current_measure_index = row_index;
-                }
-            }
-
-            current_measure_index = row_index;
-            prev_measure = measure;
-            prev_measure_beat = beat;
-        }
-        prev_beat = beat;
+    let measure_of = |row: &HitObjectRow| (row.beat / 4).floor().max(0) as usize;
+    let measure_count = rows.iter().map(measure_of).max().map_or(1, |measure| measure + 1);
+    let mut measure_rows: Vec<Vec<&HitObjectRow>> = vec![Vec::new(); measure_count];
+    for row in &rows {
+        measure_rows[measure_of(row)].push(row);
     }
-    measure_indices.push(MeasureRange(current_measure_index, rows.len(), false));
 
-    for measure_range in measure_indices {
-        padded_measures.push(pad_measure(&rows, &measure_range));
-    }
+    let padded_measures: Vec<Measure> = measure_rows
+        .iter()
+        .enumerate()
+        .map(|(index, rows)| pad_measure(rows, Beat::whole(4 * index as i64), key_count))
+        .collect();
 
     // process bpms
     let last_bpm_beat = bpms.last().unwrap().beat;
@@ -154,7 +96,7 @@ pub(crate) fn to_sm_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn
         if bpm.beat < last_bpm_beat {
             add_key_value_template(
                 &mut bpm_template,
-                &bpm.beat.to_string(),
+                &bpm.beat.as_f32().to_string(),
                 "=",
                 &bpm.change.value.to_string(),
                 ",\n",
@@ -162,7 +104,7 @@ pub(crate) fn to_sm_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn
         } else {
             add_key_value_template(
                 &mut bpm_template,
-                &bpm.beat.to_string(),
+                &bpm.beat.as_f32().to_string(),
                 "=",
                 &bpm.change.value.to_string(),
                 "\n",
@@ -170,6 +112,35 @@ pub(crate) fn to_sm_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn
         }
     }
 
+    // process stops, the durations are kept in milliseconds
+    let stop_template = chart
+        .timing_points
+        .iter()
+        .filter(|point| point.change.change_type == TimingChangeType::Stop)
+        .map(|stop| format!("{}={}", stop.beat.as_f32(), to_seconds(stop.change.value)))
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    // process time signatures
+    let mut time_signature_template = String::new();
+    let signatures: Vec<_> = chart.timing_points.time_signature_changes().collect();
+    if signatures.first().is_some_and(|signature| signature.beat > Beat::ZERO) {
+        time_signature_template.push_str("0=4=4");
+    }
+    for signature in signatures {
+        let Some(time_signature) = signature.signature else {
+            continue;
+        };
+
+        if !time_signature_template.is_empty() {
+            time_signature_template.push_str(",\n");
+        }
+        time_signature_template.push_str(&format!(
+            "{}={}={}",
+            signature.beat.as_f32(), time_signature.numerator, time_signature.denominator
+        ));
+    }
+
     // process notes
     let last_measure_index = padded_measures.len() - 1;
     notes_template.push('\n');
@@ -235,11 +206,12 @@ pub(crate) fn to_sm_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn
     add_key_value_template(&mut template, "#LYRICSPATH", ":", "", ";\n");
     add_key_value_template(&mut template, "#CDTITLE", ":", "", ";\n");
     add_key_value_template(&mut template, "#MUSIC", ":", &chart.chartinfo.song_path, ";\n");
+    // subtracting from zero keeps a zero offset from being written as -0
     add_key_value_template(
         &mut template,
         "#OFFSET",
         ":",
-        &to_seconds(-chart.chartinfo.audio_offset as f32).to_string(),
+        &((0.0 - chart.chartinfo.audio_offset) / 1000.0).to_string(),
         ";\n",
     );
     add_key_value_template(
@@ -249,10 +221,16 @@ pub(crate) fn to_sm_generic(chart: &GenericManiaChart) -> Result<String, Box<dyn
         &to_seconds(chart.chartinfo.preview_time as f32).to_string(),
         ";\n",
     );
-    add_key_value_template(&mut template, "#SAMPLELENGTH", ":", "12.000", ";\n"); // TODO: maybe add chart length in chart info
+    // the sample can't play past the end of the chart
+    let remaining_length = chart.stats().total_length - chart.chartinfo.preview_time as f64;
+    let sample_length = if remaining_length > 0.0 { (remaining_length / 1000.0).min(12.0) } else { 12.0 };
+    add_key_value_template(&mut template, "#SAMPLELENGTH", ":", &format!("{sample_length:.3}"), ";\n");
     add_key_value_template(&mut template, "#SELECTABLE", ":", "YES", ";\n");
     add_key_value_template(&mut template, "#BPMS", ":", &bpm_template, ";\n");
-    add_key_value_template(&mut template, "#STOPS", ":", "", ";\n");
+    add_key_value_template(&mut template, "#STOPS", ":", &stop_template, ";\n");
+    if !time_signature_template.is_empty() {
+        add_key_value_template(&mut template, "#TIMESIGNATURES", ":", &time_signature_template, ";\n");
+    }
     add_key_value_template(&mut template, "#BGCHANGES", ":", "", ";\n");
     add_key_value_template(&mut template, "#KEYSOUNDS", ":", "", ";\n");
     add_key_value_template(&mut template, "#NOTES", ":", &notes_template, ";\n");
@@ -0,0 +1,512 @@
+use crate::models::common::{ChartDefaults, GameMode, TaikoHitobjectType, TimingChangeType};
+use crate::models::generic::{
+    BranchCondition,
+    BranchSection,
+    BranchType,
+    GenericTaikoChart,
+    TaikoHitObject,
+    TimeSignature,
+    TimingPoints,
+};
+use crate::utils::string::add_key_value_template;
+use crate::utils::time::to_seconds;
+use crate::errors;
+
+// 4/4 measure length, lead-in measures in front of the chart always use it
+const MEASURE_BEATS: f64 = 4.0;
+const MEASURE_DIVISORS: [usize; 14] = [1, 2, 3, 4, 6, 8, 12, 16, 24, 32, 48, 64, 96, 192];
+const SNAP_TOLERANCE: f64 = 0.02;
+
+struct NoteEvent {
+    beat: f64,
+    note: char,
+    balloon_hits: Option<u32>,
+}
+
+struct CommandEvent {
+    beat: f64,
+    command: String,
+}
+
+#[derive(Clone, Copy)]
+struct CourseStart {
+    time: f64,
+    bpm: f32,
+}
+
+struct MeasureSpan {
+    start_beat: f64,
+    beats: f64,
+    signature: TimeSignature,
+}
+
+#[inline]
+// values come in as f32, 3 decimals keeps 0.6 from being written as 0.59999996
+fn format_value(value: f32) -> String {
+    let formatted = format!("{value:.3}");
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() }
+}
+
+fn tja_note_char(object: &TaikoHitObject) -> char {
+    match object.note.note_type {
+        TaikoHitobjectType::Don => '1',
+        TaikoHitobjectType::Kat => '2',
+        TaikoHitobjectType::BonusDon => '3',
+        TaikoHitobjectType::BonusKat => '4',
+        TaikoHitobjectType::DrumRoll => '5',
+        TaikoHitobjectType::BonusDrumRoll => '6',
+        TaikoHitobjectType::Balloon => '7',
+        TaikoHitobjectType::Empty | TaikoHitobjectType::Unknown => '0',
+    }
+}
+
+#[inline]
+fn measure_of(layout: &[MeasureSpan], beat: f64) -> usize {
+    layout
+        .partition_point(|measure| measure.start_beat <= beat + SNAP_TOLERANCE)
+        .saturating_sub(1)
+}
+
+// measures follow the time signature changes, a change in the middle of a measure cuts it short,
+// the layout covers the last object and reaches at least up to the end beat
+fn measure_layout(chart: &GenericTaikoChart, lead_beats: f64, last_beat: f64, end_beat: f64) -> Vec<MeasureSpan> {
+    let mut changes = chart
+        .timing_points
+        .time_signature_changes()
+        .filter_map(|tp| Some((tp.beat.as_f64() + lead_beats, tp.signature?)))
+        .peekable();
+
+    let mut layout = Vec::new();
+    let mut signature = TimeSignature::default();
+    let mut beat = 0.0;
+
+    while beat <= last_beat + SNAP_TOLERANCE || beat < end_beat - 1e-3 {
+        while let Some((_, next)) = changes.next_if(|(change_beat, _)| *change_beat <= beat + SNAP_TOLERANCE) {
+            signature = next;
+        }
+
+        let full_end = beat + signature.beats_per_measure() as f64;
+        let (end, measure_signature) = match changes.peek() {
+            Some(&(change_beat, _)) if change_beat < full_end - SNAP_TOLERANCE => {
+                (change_beat, TimeSignature::from_beats_per_measure((change_beat - beat) as f32))
+            }
+            _ => (full_end, signature),
+        };
+
+        layout.push(MeasureSpan {
+            start_beat: beat,
+            beats: end - beat,
+            signature: measure_signature,
+        });
+        beat = end;
+    }
+
+    layout
+}
+
+// walks the timing points so delays (stops) shift the time without advancing the beat,
+// times before the first bpm point end up with negative beats
+fn time_to_beat(time: f64, timing_points: &TimingPoints) -> f64 {
+    let Some(first_bpm) = timing_points.bpm_changes().next() else {
+        return 0.0;
+    };
+
+    if time < first_bpm.time {
+        return first_bpm.beat.as_f64() + (time - first_bpm.time) * first_bpm.change.value as f64 / 60_000.0;
+    }
+
+    let mut anchor_time = 0.0;
+    let mut anchor_beat = 0.0;
+    let mut bpm = 0.0;
+
+    for tp in timing_points.iter() {
+        if tp.time > time {
+            break;
+        }
+
+        match tp.change.change_type {
+            TimingChangeType::Bpm => {
+                anchor_time = tp.time;
+                anchor_beat = tp.beat.as_f64();
+                bpm = tp.change.value as f64;
+            }
+            TimingChangeType::Stop => {
+                anchor_beat += (tp.time - anchor_time) * bpm / 60_000.0;
+                anchor_time = tp.time + tp.change.value as f64;
+            }
+            _ => {}
+        }
+    }
+
+    anchor_beat + ((time - anchor_time) * bpm / 60_000.0).max(0.0)
+}
+
+fn collect_commands(chart: &GenericTaikoChart, lead_beats: f64, header: CourseStart) -> Vec<CommandEvent> {
+    let mut commands = Vec::new();
+    let mut first_bpm = true;
+
+    // every course starts at the header offset and bpm, the others catch up on their first beat
+    let start = course_start(chart, lead_beats);
+    if start.bpm != header.bpm {
+        commands.push(CommandEvent { beat: 0.0, command: format!("#BPMCHANGE {}", format_value(start.bpm)) });
+    }
+    let delay = to_seconds((start.time - header.time) as f32);
+    if format_value(delay) != "0" {
+        commands.push(CommandEvent { beat: 0.0, command: format!("#DELAY {}", format_value(delay)) });
+    }
+    let mut scroll = 1.0;
+
+    for tp in chart.timing_points.iter() {
+        let command = match tp.change.change_type {
+            TimingChangeType::Bpm if first_bpm => {
+                first_bpm = false;
+                continue;
+            }
+            TimingChangeType::Bpm => format!("#BPMCHANGE {}", format_value(tp.change.value)),
+            TimingChangeType::Sv if tp.has_default_group_qua() => {
+                if tp.change.value == scroll {
+                    continue;
+                }
+                scroll = tp.change.value;
+                format!("#SCROLL {}", format_value(scroll))
+            }
+            TimingChangeType::Stop => format!("#DELAY {}", format_value(to_seconds(tp.change.value))),
+            _ => continue,
+        };
+
+        commands.push(CommandEvent {
+            beat: tp.beat.as_f64() + lead_beats,
+            command,
+        });
+    }
+
+    for section in &chart.gogo_sections {
+        commands.push(CommandEvent {
+            beat: time_to_beat(section.start_time, &chart.timing_points) + lead_beats,
+            command: "#GOGOSTART".to_string(),
+        });
+
+        if section.end_time != f64::MAX {
+            commands.push(CommandEvent {
+                beat: time_to_beat(section.end_time, &chart.timing_points) + lead_beats,
+                command: "#GOGOEND".to_string(),
+            });
+        }
+    }
+
+    commands.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+    commands
+}
+
+#[inline]
+fn object_beat(object: &TaikoHitObject, start_time: f64, timing_points: &TimingPoints) -> f64 {
+    // beats get clamped to 0 before the first bpm point
+    if object.time < start_time {
+        time_to_beat(object.time, timing_points)
+    } else {
+        object.beat.as_f64()
+    }
+}
+
+fn collect_notes(chart: &GenericTaikoChart, branch: BranchType, lead_beats: f64) -> Vec<NoteEvent> {
+    let start_time = chart.timing_points.bpms_times().first().copied().unwrap_or(0.0);
+    let mut notes = Vec::with_capacity(chart.hitobjects.objects.len());
+
+    for object in chart.hitobjects.iter_branch(branch) {
+        let note = tja_note_char(object);
+        if note == '0' {
+            continue;
+        }
+
+        notes.push(NoteEvent {
+            beat: object_beat(object, start_time, &chart.timing_points) + lead_beats,
+            note,
+            balloon_hits: object.balloon_hits,
+        });
+
+        if object.is_roll() {
+            let end_time = object.note.end_time.unwrap_or(object.time);
+            let end_beat = match object.end_beat {
+                Some(end_beat) if end_time >= start_time => end_beat.as_f64(),
+                _ => time_to_beat(end_time, &chart.timing_points),
+            };
+
+            notes.push(NoteEvent {
+                beat: end_beat + lead_beats,
+                note: '8',
+                balloon_hits: None,
+            });
+        }
+    }
+
+    notes.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+    notes
+}
+
+// where the lead-in measures in front of the first bpm point start
+fn course_start(chart: &GenericTaikoChart, lead_beats: f64) -> CourseStart {
+    let bpm = chart.timing_points.bpms().first().copied().unwrap_or(120.0);
+    let first_time = chart
+        .timing_points
+        .bpms_times()
+        .first()
+        .copied()
+        .unwrap_or(chart.chartinfo.audio_offset);
+
+    CourseStart {
+        time: first_time - lead_beats * 60_000.0 / bpm as f64,
+        bpm,
+    }
+}
+
+// TJA can't place notes before the first measure, so we add empty measures in front when needed
+fn lead_measures(chart: &GenericTaikoChart) -> usize {
+    let start_time = chart.timing_points.bpms_times().first().copied().unwrap_or(0.0);
+    let first_beat = chart
+        .hitobjects
+        .iter()
+        .map(|object| object_beat(object, start_time, &chart.timing_points))
+        .fold(0.0, f64::min);
+
+    (-first_beat / MEASURE_BEATS).ceil().max(0.0) as usize
+}
+
+// the divisors split a 4/4 measure, other measures use the same slot lengths when they fit evenly
+fn measure_divisor(positions: &[f64], measure_beats: f64) -> usize {
+    for divisor in MEASURE_DIVISORS {
+        let slot_beats = MEASURE_BEATS / divisor as f64;
+        let slot_count = measure_beats / slot_beats;
+        if (slot_count - slot_count.round()).abs() > 1e-3 || slot_count.round() < 1.0 {
+            continue;
+        }
+
+        let fits = positions.iter().all(|pos| {
+            let snapped = (pos / slot_beats).round() * slot_beats;
+            (pos - snapped).abs() <= SNAP_TOLERANCE
+        });
+
+        if fits {
+            return slot_count.round() as usize;
+        }
+    }
+
+    (measure_beats / MEASURE_BEATS * 192.0).round().max(1.0) as usize
+}
+
+fn write_measure(
+    template: &mut String,
+    layout: &[MeasureSpan],
+    measure: usize,
+    commands: &[CommandEvent],
+    notes: &[NoteEvent],
+    balloons: &mut Vec<u32>,
+) {
+    let in_measure = |beat: f64| measure_of(layout, beat) == measure;
+    let span = &layout[measure];
+    let start_beat = span.start_beat;
+
+    let previous_signature = measure
+        .checked_sub(1)
+        .map_or(TimeSignature::default(), |previous| layout[previous].signature);
+    if span.signature != previous_signature {
+        template.push_str(&format!("#MEASURE {}\n", span.signature));
+    }
+
+    let commands: Vec<&CommandEvent> = commands.iter().filter(|c| in_measure(c.beat)).collect();
+    let notes: Vec<&NoteEvent> = notes.iter().filter(|n| in_measure(n.beat)).collect();
+
+    let positions: Vec<f64> = commands
+        .iter()
+        .map(|c| c.beat - start_beat)
+        .chain(notes.iter().map(|n| n.beat - start_beat))
+        .map(|pos| pos.max(0.0))
+        .collect();
+
+    let divisor = measure_divisor(&positions, span.beats);
+    let slot_of = |beat: f64| {
+        let pos = (beat - start_beat).max(0.0);
+        ((pos / span.beats * divisor as f64).round() as usize).min(divisor - 1)
+    };
+
+    let mut slots = vec!['0'; divisor];
+    for note in &notes {
+        let slot = slot_of(note.beat);
+        if slots[slot] == '0' {
+            slots[slot] = note.note;
+            if let Some(hits) = note.balloon_hits {
+                balloons.push(hits);
+            }
+        }
+    }
+
+    let mut line = String::with_capacity(divisor + 1);
+    let mut commands = commands.into_iter().peekable();
+
+    for (slot, note) in slots.into_iter().enumerate() {
+        while let Some(command) = commands.next_if(|c| slot_of(c.beat) == slot) {
+            if !line.is_empty() {
+                template.push_str(&line);
+                template.push('\n');
+                line.clear();
+            }
+            template.push_str(&command.command);
+            template.push('\n');
+        }
+        line.push(note);
+    }
+
+    line.push(',');
+    template.push_str(&line);
+    template.push('\n');
+}
+
+fn branch_condition_str(condition: BranchCondition) -> &'static str {
+    match condition {
+        BranchCondition::Roll => "r",
+        BranchCondition::Accuracy => "p",
+        BranchCondition::Score => "s",
+    }
+}
+
+// branch sections have to line up with measures in TJA
+fn branch_measure_ranges<'a>(
+    chart: &'a GenericTaikoChart,
+    layout: &[MeasureSpan],
+    lead_beats: f64,
+) -> Vec<(usize, usize, &'a BranchSection)> {
+    let mut ranges: Vec<(usize, usize, &BranchSection)> = Vec::with_capacity(chart.branches.len());
+
+    for section in &chart.branches {
+        let start = measure_of(layout, time_to_beat(section.start_time, &chart.timing_points) + lead_beats);
+        let end_beat = time_to_beat(section.end_time, &chart.timing_points) + lead_beats;
+        let end = layout
+            .partition_point(|measure| measure.start_beat < end_beat - 1e-3)
+            .max(start + 1);
+
+        if ranges.last().is_some_and(|(_, prev_end, _)| start < *prev_end) {
+            continue;
+        }
+
+        ranges.push((start, end, section));
+    }
+
+    ranges
+}
+
+fn write_course(chart: &GenericTaikoChart, lead_beats: f64, header: CourseStart, template: &mut String) {
+    let commands = collect_commands(chart, lead_beats, header);
+    let master_notes = collect_notes(chart, BranchType::Master, lead_beats);
+
+    let last_beat = commands
+        .iter()
+        .map(|c| c.beat)
+        .chain(master_notes.iter().map(|n| n.beat))
+        .fold(0.0, f64::max);
+    let branch_end_beat = chart
+        .branches
+        .iter()
+        .map(|b| time_to_beat(b.end_time, &chart.timing_points) + lead_beats)
+        .fold(0.0, f64::max);
+
+    let layout = measure_layout(chart, lead_beats, last_beat, branch_end_beat);
+    let ranges = branch_measure_ranges(chart, &layout, lead_beats);
+    let total_measures = layout.len();
+
+    let mut balloons = Vec::new();
+    let mut body = String::with_capacity(total_measures * 24);
+    let mut measure = 0;
+
+    while measure < total_measures {
+        let Some((start, end, section)) = ranges.iter().find(|r| r.0 == measure) else {
+            write_measure(&mut body, &layout, measure, &commands, &master_notes, &mut balloons);
+            measure += 1;
+            continue;
+        };
+
+        body.push_str(&format!(
+            "#BRANCHSTART {},{},{}\n",
+            branch_condition_str(section.condition),
+            section.expert_requirement,
+            section.master_requirement
+        ));
+
+        for (branch, command) in [
+            (BranchType::Normal, "#N"),
+            (BranchType::Expert, "#E"),
+            (BranchType::Master, "#M"),
+        ] {
+            let notes = collect_notes(chart, branch, lead_beats);
+            body.push_str(command);
+            body.push('\n');
+            for m in *start..*end {
+                write_measure(&mut body, &layout, m, &commands, &notes, &mut balloons);
+            }
+        }
+
+        body.push_str("#BRANCHEND\n");
+        measure = *end;
+    }
+
+    add_key_value_template(template, "COURSE", ":", chart.course.to_tja(), "\n");
+    add_key_value_template(template, "LEVEL", ":", &chart.level.to_string(), "\n");
+    if !balloons.is_empty() {
+        let balloons = balloons.iter().map(|b| b.to_string()).collect::<Vec<_>>().join(",");
+        add_key_value_template(template, "BALLOON", ":", &balloons, "\n");
+    }
+
+    template.push_str("\n#START\n");
+    template.push_str(&body);
+    template.push_str("#END\n\n");
+}
+
+pub(crate) fn to_tja_taiko_courses(
+    charts: &[GenericTaikoChart],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let Some(chart) = charts.first() else {
+        return Err(Box::new(errors::WriteError::<GameMode>::EmptyChartData("TJA".to_string())));
+    };
+
+    let mut template = String::with_capacity(charts.len() * 4096);
+
+    // the header offset is the earliest course start, so no course needs a negative delay
+    let lead_beats = charts.iter().map(lead_measures).max().unwrap_or(0) as f64 * MEASURE_BEATS;
+    let header = charts
+        .iter()
+        .map(|chart| course_start(chart, lead_beats))
+        .min_by(|a, b| a.time.total_cmp(&b.time))
+        .unwrap_or(CourseStart { time: 0.0, bpm: 120.0 });
+
+    add_key_value_template(&mut template, "TITLE", ":", &chart.metadata.title, "\n");
+    if !chart.metadata.alt_title.is_empty() && chart.metadata.alt_title != chart.metadata.title {
+        add_key_value_template(&mut template, "TITLEEN", ":", &chart.metadata.alt_title, "\n");
+    }
+    if !chart.metadata.source.is_empty() && chart.metadata.source != ChartDefaults::SOURCE {
+        add_key_value_template(&mut template, "SUBTITLE", ":", &format!("--{}", chart.metadata.source), "\n");
+    }
+    add_key_value_template(&mut template, "BPM", ":", &format_value(header.bpm), "\n");
+    add_key_value_template(&mut template, "WAVE", ":", &chart.chartinfo.song_path, "\n");
+    add_key_value_template(&mut template, "OFFSET", ":", &(-header.time / 1000.0).to_string(), "\n");
+    add_key_value_template(&mut template, "DEMOSTART", ":", &to_seconds(chart.chartinfo.preview_time as f32).to_string(), "\n");
+    add_key_value_template(&mut template, "GENRE", ":", &chart.metadata.genre, "\n");
+    add_key_value_template(&mut template, "MAKER", ":", &chart.metadata.creator, "\n");
+    if !chart.chartinfo.bg_path.is_empty() {
+        add_key_value_template(&mut template, "BGIMAGE", ":", &chart.chartinfo.bg_path, "\n");
+    }
+    if !chart.chartinfo.video_path.is_empty() {
+        add_key_value_template(&mut template, "BGMOVIE", ":", &chart.chartinfo.video_path, "\n");
+    }
+    template.push('\n');
+
+    for chart in charts {
+        write_course(chart, lead_beats, header, &mut template);
+    }
+
+    Ok(template)
+}
+
+pub(crate) fn to_tja_taiko(
+    chart: &GenericTaikoChart,
+) -> Result<String, Box<dyn std::error::Error>> {
+    to_tja_taiko_courses(std::slice::from_ref(chart))
+}
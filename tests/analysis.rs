@@ -0,0 +1,383 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_mania_star_rating_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [7K OVERLOAD].osu").unwrap();
+    let chart = parse::from_osu_generic(&raw_chart).unwrap();
+
+    let nomod = analysis::osu_mania_star_rating(&chart, analysis::OsuMods::NONE);
+    let double_time = analysis::osu_mania_star_rating(&chart, analysis::OsuMods::DOUBLE_TIME);
+    let half_time = analysis::osu_mania_star_rating(&chart, analysis::OsuMods::HALF_TIME);
+
+    println_test("osu_mania_star_rating", "\x1b[32m", &format!("{nomod:.2} {double_time:.2} {half_time:.2}"));
+    // lazer can't be run here, these come from a separate port of its ManiaDifficultyCalculator
+    // that sorts with LegacySortHelper the same way, the order inside chords moves the nomod value by 0.015
+    assert!((nomod - 6.455489).abs() < 1e-5);
+    assert!((double_time - 8.803751).abs() < 1e-5);
+    assert!((half_time - 5.235316).abs() < 1e-5);
+}
+
+#[test]
+fn quaver_difficulty_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/4548_886_Ziqqurat/34785.qua").unwrap();
+    let insane = parse::from_qua_generic(&raw_chart).unwrap();
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/24312_870_AngyBirdPhonk/125133.qua").unwrap();
+    let easy = parse::from_qua_generic(&raw_chart).unwrap();
+
+    let difficulty = analysis::quaver_difficulty(&insane, 1.0).unwrap();
+    let faster = analysis::quaver_difficulty(&insane, 1.3).unwrap();
+    let easy_difficulty = analysis::quaver_difficulty(&easy, 1.0).unwrap();
+
+    println_test("quaver_difficulty", "\x1b[32m", &format!("{difficulty:.2} {faster:.2} {easy_difficulty:.2}"));
+    // pinned so changes to the calculator show up
+    assert!((difficulty - 19.63).abs() < 0.01);
+    assert!(faster > difficulty && difficulty > easy_difficulty);
+    assert!(analysis::quaver_difficulty(&insane, -1.0).is_err());
+
+    // 7k hands have three fingers, so a 1-2-3 roll never comes back to its first finger and isn't nerfed
+    // without that check every roll on a hand counted as manipulation and this came out at 11.42
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/2366_177_NewNonBiyori/19248.qua").unwrap();
+    let seven_keys = parse::from_qua_generic(&raw_chart).unwrap();
+    let seven_keys_difficulty = analysis::quaver_difficulty(&seven_keys, 1.0).unwrap();
+    assert!((seven_keys_difficulty - 12.17).abs() < 0.01, "{seven_keys_difficulty}");
+}
+
+#[test]
+fn chart_stats_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/4548_886_Ziqqurat/34785.qua").unwrap();
+    let chart = parse::from_qua_generic(&raw_chart).unwrap();
+    let stats = chart.stats();
+
+    println_test("chart_stats", "\x1b[32m", &format!("{stats:?}"));
+    let heads: u32 = stats.chord_histogram.iter().enumerate().map(|(size, rows)| (size as u32 + 1) * rows).sum();
+    assert_eq!(heads, stats.note_count + stats.ln_count);
+    assert_eq!(stats.row_count, stats.chord_histogram.iter().sum::<u32>());
+    assert_eq!(stats.max_combo_quaver, stats.note_count + stats.ln_count * 2);
+    assert_eq!(stats.max_combo_osu, stats.max_combo_quaver);
+    assert!(stats.drain_time > 0.0 && stats.total_length >= stats.drain_time);
+    assert!(stats.peak_nps >= stats.average_nps && stats.average_nps > 0.0);
+    assert!(stats.min_bpm <= stats.dominant_bpm && stats.dominant_bpm <= stats.max_bpm);
+}
+
+#[test]
+fn density_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/4548_886_Ziqqurat/34785.qua").unwrap();
+    let chart = parse::from_qua_generic(&raw_chart).unwrap();
+    let stats = chart.stats();
+
+    // one second windows that don't overlap add up to every note
+    let density = analysis::density(&chart, 1000.0, 1000.0);
+    let total: f32 = density.iter().sum();
+    assert_eq!(total as u32, stats.note_count + stats.ln_count);
+
+    let left = analysis::density_filtered(&chart, 1000.0, 1000.0, analysis::DensityFilter::LEFT_HAND);
+    let right = analysis::density_filtered(&chart, 1000.0, 1000.0, analysis::DensityFilter::RIGHT_HAND);
+    assert!(density.iter().zip(left.iter().zip(&right)).all(|(all, (left, right))| *all == left + right));
+
+    let lns = analysis::density_filtered(&chart, 1000.0, 1000.0, analysis::DensityFilter::LNS);
+    assert_eq!(lns.iter().sum::<f32>() as u32, stats.ln_count);
+
+    let smooth = analysis::density(&chart, 2000.0, 250.0);
+    println_test("density", "\x1b[32m", &format!("{} samples, peak {}", smooth.len(), smooth.iter().fold(0.0, |a: f32, b| a.max(*b))));
+    assert!(smooth.iter().all(|nps| *nps as f64 <= stats.peak_nps));
+}
+
+#[test]
+fn patterns_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/etterna/MysticOrientalLoveConsultant/asdf.sm").unwrap();
+    let chart = parse::from_sm_generic(&raw_chart).unwrap();
+    let patterns = analysis::patterns(&chart);
+
+    let dominant = patterns.dominant();
+    println_test("patterns", "\x1b[32m", &format!("{} sections, mostly {dominant:?}", patterns.sections.len()));
+    assert_eq!(dominant, Some(analysis::PatternType::Jumpstream));
+    assert!(patterns.share(analysis::PatternType::Jumpstream) > 0.5);
+    assert!(patterns.average_bpm(analysis::PatternType::Jumpstream).is_some_and(|bpm| bpm > 100.0));
+
+    let rows: u32 = patterns.sections.iter().map(|s| s.row_count).sum();
+    assert_eq!(rows, chart.stats().row_count);
+    assert!(patterns.sections.windows(2).all(|pair| pair[0].end_time < pair[1].start_time));
+}
+
+#[test]
+fn lints_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/24312_870_AngyBirdPhonk/125133.qua").unwrap();
+    let mut chart = parse::from_qua_generic(&raw_chart).unwrap();
+    assert!(analysis::validate(&chart).is_empty());
+
+    // halfway between two 1/48 lines
+    let unsnap = chart.timing_map().ms_per_beat_at(0.0).unwrap() as f64 / 96.0;
+    let objects = &mut chart.hitobjects.objects;
+    let overlapping = objects[10].clone();
+    objects.push(overlapping);
+    objects[20].time += unsnap;
+    objects[30].key = common::Key::slider_start(Some(objects[30].time - 100.0));
+    objects[40].lane = chart.chartinfo.key_count + 1;
+    objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+    let mut zero_bpm = chart.timing_points.iter().find(|p| p.change.change_type == common::TimingChangeType::Bpm).unwrap().clone();
+    zero_bpm.time += 1.0;
+    zero_bpm.change.value = 0.0;
+    chart.timing_points.points.push(zero_bpm);
+
+    let kinds: Vec<analysis::LintKind> = analysis::validate(&chart).iter().map(|lint| lint.kind).collect();
+    println_test("lints", "\x1b[32m", &format!("{kinds:?}"));
+    for kind in [
+        analysis::LintKind::OverlappingNotes,
+        analysis::LintKind::UnsnappedNote,
+        analysis::LintKind::LnEndsBeforeStart,
+        analysis::LintKind::LaneOutOfRange,
+        analysis::LintKind::InvalidBpm,
+    ] {
+        assert!(kinds.contains(&kind), "{kind:?} wasn't found");
+    }
+
+    // the lane can't be guessed so it's the only thing left
+    let left = analysis::fix(&mut chart);
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].kind, analysis::LintKind::LaneOutOfRange);
+}
+
+#[test]
+fn lint_fixes_test() {
+    use analysis::LintKind;
+    use common::{Key, KeyType};
+
+    let kinds = |chart: &GenericManiaChart| analysis::validate(chart).iter().map(|lint| lint.kind).collect::<Vec<_>>();
+    let hold = |chart: &GenericManiaChart, lane: u8| {
+        let head = chart.hitobjects.iter().find(|obj| obj.lane == lane && obj.key.key_type == KeyType::SliderStart)?;
+        let end = chart.hitobjects.iter().find(|obj| obj.lane == lane && obj.key.key_type == KeyType::SliderEnd)?;
+        Some((head.time, head.key.slider_end_time, end.time))
+    };
+
+    // the hold is cut 30ms before the tap, then the next pass snaps its end back onto the 1/48 grid
+    let mut chart = chart_from_objects(&[(0.0, 1, Some(800.0)), (400.0, 1, None), (400.0, 2, None)]);
+    assert_eq!(kinds(&chart), vec![LintKind::TapInsideLn]);
+    assert!(analysis::fix(&mut chart).is_empty());
+    let (start, head_end, end) = hold(&chart, 1).unwrap();
+    assert_eq!(start, 0.0);
+    assert!((end - 1100.0 / 3.0).abs() < 1e-6, "hold ends at {end}");
+    assert_eq!(head_end, Some(end));
+    assert!(chart.hitobjects.iter().any(|obj| obj.lane == 1 && obj.time == 400.0 && obj.key.key_type == KeyType::Normal));
+
+    // a head that lost its end time gets the end object that follows it back
+    let mut chart = chart_from_objects(&[(0.0, 1, Some(400.0)), (800.0, 1, None)]);
+    chart.hitobjects.objects[0].key = Key::slider_start(None);
+    assert_eq!(kinds(&chart), vec![LintKind::MissingLnEnd]);
+    assert!(analysis::fix(&mut chart).is_empty());
+    assert_eq!(hold(&chart, 1), Some((0.0, Some(400.0), 400.0)));
+
+    // without an end object it becomes a tap
+    let mut chart = chart_from_objects(&[(0.0, 1, None), (800.0, 1, None)]);
+    chart.hitobjects.objects[0].key = Key::slider_start(None);
+    assert_eq!(kinds(&chart), vec![LintKind::MissingLnEnd]);
+    assert!(analysis::fix(&mut chart).is_empty());
+    assert_eq!(chart.hitobjects.objects[0].key.key_type, KeyType::Normal);
+    assert_eq!(chart.hitobjects.objects.len(), 2);
+
+    // a copy of the first bpm point goes a whole 1600ms measure earlier so the barlines don't move
+    let mut chart = chart_from_objects(&[(-500.0, 1, None), (0.0, 2, None)]);
+    assert_eq!(kinds(&chart), vec![LintKind::NoteBeforeFirstBpm]);
+    assert!(analysis::fix(&mut chart).is_empty());
+    let bpms: Vec<(f64, f32)> = chart.timing_points.bpm_changes().map(|point| (point.time, point.change.value)).collect();
+    assert_eq!(bpms, vec![(-1600.0, 150.0), (0.0, 150.0)]);
+    assert_eq!(chart.hitobjects.objects.len(), 2);
+
+    // the later of two points at the same time is the one games use, so the earlier one goes
+    let mut chart = chart_from_objects(&[(0.0, 1, None), (100.0, 2, None)]);
+    let mut faster = chart.timing_points.bpm_changes().next().unwrap().clone();
+    faster.change.value = 300.0;
+    chart.timing_points.points.push(faster);
+    assert_eq!(kinds(&chart), vec![LintKind::DuplicateTimingPoint]);
+    assert!(analysis::fix(&mut chart).is_empty());
+    let bpms: Vec<f32> = chart.timing_points.bpm_changes().map(|point| point.change.value).collect();
+    assert_eq!(bpms, vec![300.0]);
+}
+
+#[test]
+fn snaps_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/1888601_LunaticEyes/COOL&CREATE - Lunatic Eyes ~ Invisible Full Moon (Cut Ver.) (TheFunk) [Blood Moon 1.35x (270bpm)].osu").unwrap();
+    let mut chart = parse::from_osu_generic(&raw_chart).unwrap();
+    assert!(analysis::snaps(&chart).iter().all(|snap| snap.snapped && snap.divisor <= 4));
+
+    // 3ms is more than half a 1/48 row at 270bpm so the note ends up on an odd 192nd row
+    let index = chart.hitobjects.iter().position(|obj| matches!(obj.key.key_type, common::KeyType::Normal | common::KeyType::SliderStart)).unwrap();
+    let timing_map = chart.timing_map();
+    let obj = &mut chart.hitobjects.objects[index];
+    obj.time += 3.0;
+    obj.beat = timing_map.snapped_beat_at(obj.time);
+
+    let snap = analysis::snaps(&chart).into_iter().find(|snap| snap.index == index).unwrap();
+    println_test("snaps", "\x1b[32m", &format!("1/{} {:.2}ms", snap.divisor, snap.error));
+    assert_eq!(snap.divisor, 48);
+
+    let measures_of_192 = |chart: &GenericManiaChart| {
+        let sm = write::to_sm_generic(chart).unwrap();
+        sm.split(',')
+            .filter(|measure| measure.lines().filter(|line| line.len() == 4 && line.chars().all(|c| c.is_ascii_digit())).count() == 192)
+            .count()
+    };
+    assert!(measures_of_192(&chart) > 0);
+
+    // notes under a millisecond off are only the .osu rounding and stay where they are
+    assert!(analysis::quantize(&mut chart, &[1, 2, 4], 4.0) > 0);
+    assert!(analysis::snaps(&chart).iter().all(|snap| snap.snapped && snap.error.abs() < 1.0));
+    assert_eq!(measures_of_192(&chart), 0);
+}
+
+#[test]
+fn snaps_from_bpm_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/360565_HatsuneMikuNoShoushitsu/cosMo@BousouP feat. Hatsune Miku - Hatsune Miku no Shoushitsu (juankristal) [NM].osu").unwrap();
+    let mut chart = parse::from_osu_generic(&raw_chart).unwrap();
+
+    // the 240bpm point at 26701 is off the earlier grid, counted from it every note is a 1/1 or a 1/2
+    let snaps = analysis::snaps(&chart);
+    let count = |divisor: u32| snaps.iter().filter(|snap| snap.divisor == divisor).count();
+    assert_eq!(snaps.len(), 1219);
+    assert_eq!((count(1), count(2)), (975, 244));
+    assert!(snaps.iter().all(|snap| snap.snapped));
+    assert!(analysis::validate(&chart).iter().all(|lint| lint.kind != analysis::LintKind::UnsnappedNote));
+
+    let original = write::to_osu_generic(&chart).unwrap();
+    assert_eq!(analysis::quantize(&mut chart, &[1, 2, 4], 10.0), 0);
+    assert_eq!(write::to_osu_generic(&chart).unwrap(), original);
+}
+
+#[test]
+fn quantize_hold_end_test() {
+    // only the end of the hold is off the grid, the head counts as moved along with the end object
+    let mut chart = chart_from_objects(&[(0.0, 1, Some(402.0)), (100.0, 2, None)]);
+    assert_eq!(analysis::quantize(&mut chart, &[4], 5.0), 2);
+
+    let head = chart.hitobjects.iter().find(|obj| obj.key.key_type == common::KeyType::SliderStart).unwrap();
+    assert_eq!(head.key.slider_end_time, Some(400.0));
+    let end = chart.hitobjects.iter().find(|obj| obj.key.key_type == common::KeyType::SliderEnd).unwrap();
+    assert_eq!(end.time, 400.0);
+
+    assert_eq!(analysis::quantize(&mut chart, &[4], 5.0), 0);
+}
+
+#[test]
+fn diff_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/24312_870_AngyBirdPhonk/125133.qua").unwrap();
+    let chart = parse::from_qua_generic(&raw_chart).unwrap();
+    assert!(analysis::diff(&chart, &chart).is_empty());
+
+    let mut edited = chart.clone();
+    let objects = &mut edited.hitobjects.objects;
+    objects[5].time += 4.0;
+    let removed = objects.remove(10);
+    // a note on the neighbouring lane 1ms away is an add, not a move
+    let mut added = objects[20].clone();
+    added.lane = added.lane % edited.chartinfo.key_count + 1;
+    added.time += 1.0;
+    objects.push(added);
+    objects.sort_by(|a, b| a.time.total_cmp(&b.time));
+    edited.metadata.title = "Angry Birds Theme".to_string();
+    edited.timing_points.points[0].change.value += 10.0;
+
+    let diff = analysis::diff(&chart, &edited);
+    println_test("diff", "\x1b[32m", &format!("{diff:?}"));
+    assert_eq!(diff.moved.len(), 1);
+    assert_eq!(diff.moved[0].delta, 4.0);
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].time, removed.time);
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.timing_points.len(), 1);
+    assert_eq!(diff.timing_points[0].kind, analysis::DiffKind::Changed);
+    let fields: Vec<&str> = diff.timing_points[0].fields.iter().map(|change| change.field.as_str()).collect();
+    assert_eq!(fields, vec!["value"]);
+    assert_eq!(diff.metadata.len(), 1);
+    assert_eq!(diff.metadata[0].field, "title");
+}
+
+#[test]
+fn diff_timing_effects_test() {
+    let chart = chart_from_objects(&[(0.0, 1, None), (1600.0, 2, None)]);
+    let mut signatures = chart.clone();
+    signatures.timing_points.insert_time_signature(1600.0, common::Beat::new(4, 1), generic::TimeSignature::new(3, 4));
+
+    // only kiai turns on, the bpm stays the same
+    let mut kiai = chart.clone();
+    kiai.timing_points.points[0].effects = Some(generic::TimingEffects { kiai: true, ..Default::default() });
+    let diff = analysis::diff(&chart, &kiai);
+    assert_eq!(diff.timing_points.len(), 1);
+    assert_eq!(diff.timing_points[0].kind, analysis::DiffKind::Changed);
+    assert_eq!(diff.timing_points[0].old_value, diff.timing_points[0].new_value);
+    let change = |field: &str, old_value: &str, new_value: &str| analysis::FieldChange {
+        field: field.to_string(),
+        old_value: old_value.to_string(),
+        new_value: new_value.to_string(),
+    };
+    assert_eq!(diff.timing_points[0].fields, vec![change("effects", "none", "kiai")]);
+
+    // 6/8 has as many beats per measure as 3/4, so only the meter differs
+    let mut meter = signatures.clone();
+    meter.timing_points.points[1].signature = Some(generic::TimeSignature::new(6, 8));
+    let diff = analysis::diff(&signatures, &meter);
+    assert_eq!(diff.timing_points.len(), 1);
+    assert_eq!(diff.timing_points[0].change_type, common::TimingChangeType::TimeSignature);
+    assert_eq!(diff.timing_points[0].kind, analysis::DiffKind::Changed);
+    assert_eq!(diff.timing_points[0].fields, vec![change("signature", "3/4", "6/8")]);
+    assert!(diff.moved.is_empty() && diff.added.is_empty() && diff.removed.is_empty());
+}
+
+#[test]
+fn pattern_types_test() {
+    use analysis::PatternType;
+
+    let rows = |pattern: &str, repeats: usize| chart_from_rows(&vec![pattern; repeats].join(" "));
+    // every column gets a hold every 400ms, the next one starts 50ms after the last one ends
+    let inverse: Vec<_> = (0..32).map(|i| (i as f64 * 100.0, (i % 4) as u8 + 1, Some(i as f64 * 100.0 + 350.0))).collect();
+    // holds end between the presses of the next notes
+    let release: Vec<_> = (0..32).map(|i| (i as f64 * 100.0, (i % 4) as u8 + 1, Some(i as f64 * 100.0 + 150.0))).collect();
+
+    let cases = [
+        (PatternType::Stream, rows("1 2 4 3 1 4 2 1 3 4 2 3", 3)),
+        (PatternType::Jumpstream, rows("12 3 4 13 2 4 1 34 2 1 24 3", 3)),
+        (PatternType::Handstream, rows("123 4 1 234 1 3 124 3 1 134 2 4", 3)),
+        (PatternType::Chordjack, rows("12 23 34 14", 6)),
+        (PatternType::Jack, rows("1", 16)),
+        (PatternType::Minijack, rows("1 1 3 3 2 2 4 4", 4)),
+        (PatternType::Trill, rows("1 2", 12)),
+        (PatternType::Roll, rows("1 2 3 4", 6)),
+        (PatternType::Bracket, rows("12 34", 12)),
+        (PatternType::SplitHand, rows("1 3 2 4", 6)),
+        (PatternType::LnInverse, chart_from_objects(&inverse)),
+        (PatternType::LnRelease, chart_from_objects(&release)),
+    ];
+
+    for (pattern, chart) in cases {
+        let patterns = analysis::patterns(&chart);
+        assert_eq!(patterns.sections.len(), 1, "{pattern:?}: {:?}", patterns.sections);
+        assert_eq!(patterns.sections[0].pattern, pattern);
+        assert_eq!(patterns.sections[0].bpm, 150.0);
+    }
+
+    // rolls need to keep going in one direction and wrap back to where they started
+    let sweep = analysis::patterns(&rows("1 2 3 4", 2));
+    assert_eq!(sweep.sections[0].pattern, PatternType::Roll);
+    let there_and_back = analysis::patterns(&rows("1 2 3 4 3 2 1 4", 1));
+    assert_ne!(there_and_back.dominant(), Some(PatternType::Roll));
+    let back_and_forth = analysis::patterns(&rows("1 2 3 4 3 2 1 4", 4));
+    assert_ne!(back_and_forth.dominant(), Some(PatternType::Roll));
+}
+
+#[test]
+fn pattern_sections_test() {
+    // a short first run has no section before it so it joins the one after it
+    for prefix in ["1 1 1 1 1", "1234 1234 1234"] {
+        let chart = chart_from_rows(&format!("{prefix} 2 4 3 1 4 2 1 3 4 2 3 1 2 4 3 1 4 2 1 3 4 2 3"));
+        let patterns = analysis::patterns(&chart);
+        assert_eq!(patterns.sections.len(), 1, "{prefix}: {:?}", patterns.sections);
+        assert_eq!(patterns.sections[0].pattern, analysis::PatternType::Stream);
+        assert_eq!(patterns.sections[0].start_time, 0.0);
+        assert_eq!(patterns.sections[0].bpm, 150.0);
+    }
+
+    // long enough runs keep their own section
+    let chart = chart_from_rows("1 1 1 1 1 1 2 4 3 1 4 2 1 3 4 2 3 1 2 4 3 1 4 2 1 3 4 2 3");
+    let sections: Vec<_> = analysis::patterns(&chart).sections.iter().map(|s| (s.pattern, s.row_count)).collect();
+    assert_eq!(sections, vec![(analysis::PatternType::Jack, 6), (analysis::PatternType::Stream, 23)]);
+}
@@ -0,0 +1,71 @@
+mod test_stuff;
+use test_stuff::*;
+
+const SPINNER_ONLY: &str = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+Mode: 2
+
+[Difficulty]
+HPDrainRate:5
+CircleSize:4
+OverallDifficulty:5
+ApproachRate:8
+SliderMultiplier:1.4
+SliderTickRate:1
+
+[TimingPoints]
+0,500,4,1,0,100,1,0
+
+[HitObjects]
+256,192,1000,12,0,1500,0:0:0:0:
+";
+
+#[test]
+fn legacy_random_banana_positions_test() {
+    let chart = parse::from_osu_catch(SPINNER_ONLY).unwrap();
+
+    // a 500ms spinner halves its spacing down to 62.5ms
+    let bananas: Vec<_> = chart.hitobjects.iter().filter(|obj| obj.is_banana()).collect();
+    let times: Vec<f64> = bananas.iter().map(|obj| obj.time).collect();
+    assert_eq!(times, [1000.0, 1062.0, 1125.0, 1187.0, 1250.0, 1312.0, 1375.0, 1437.0, 1500.0]);
+
+    // every banana takes its x from one draw of osu!'s xorshift seeded with 1337, then 3 more for its looks,
+    // the draws are 274941776, 514112300, 938046240, 1928063929, 2025352051, ...
+    let xs: Vec<f32> = bananas.iter().map(|obj| obj.x).take(6).collect();
+    assert_eq!(xs, [336.0, 371.0, 278.0, 64.0, 377.0, 26.0]);
+}
+
+#[test]
+fn catch_hyperdash_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [Hinsvar's Extra].osu").unwrap();
+    let chart = parse::from_osu_catch(&raw_chart).unwrap();
+
+    // the opening circles before the first slider, worked out by hand with the rule from osu!'s CatchBeatmapProcessor:
+    // CS 4 gives a half catcher width of 60.8475, each pair has (time apart - 4.1667) - (distance - margin) to spare
+    let expected = [
+        (13391.0, None),
+        (13724.0, Some(296.0)),
+        (13891.0, None),
+        (13974.0, None),
+        (14058.0, Some(288.0)),
+        (14224.0, None),
+        (14391.0, None),
+        (14724.0, None),
+    ];
+    let spare = [37.68083, 0.0, 139.68083, 140.68083, 0.0, 63.68083, 369.68085, 120.68083];
+
+    for ((time, jump), spare) in expected.into_iter().zip(spare) {
+        let index = chart.hitobjects.objects.iter().position(|obj| obj.time == time).unwrap();
+        let object = &chart.hitobjects.objects[index];
+        assert_eq!(object.is_hyperdash(), jump.is_some(), "{time}");
+        assert!((object.distance_to_hyperdash - spare).abs() < 1e-3, "{time} {}", object.distance_to_hyperdash);
+
+        if let Some(distance) = jump {
+            let target = &chart.hitobjects.objects[object.hyperdash_target.unwrap()];
+            assert_eq!((target.x - object.x).abs(), distance);
+            assert_eq!(object.note.object_type, common::CatchHitobjectType::Hyperfruit);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+mod test_stuff;
+use test_stuff::*;
+
+// 60bpm so the stream and density rules only kick in where they're meant to
+const CONVERT_CHART: &str = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+Mode: 0
+
+[Difficulty]
+HPDrainRate:2
+CircleSize:4
+OverallDifficulty:5
+ApproachRate:5
+SliderMultiplier:1
+SliderTickRate:1
+
+[TimingPoints]
+0,1000,4,1,0,100,1,0
+
+[HitObjects]
+0,192,1000,1,0,0:0:0:0:
+200,192,2000,1,0,0:0:0:0:
+300,192,3000,1,0,0:0:0:0:
+500,192,4000,1,0,0:0:0:0:
+500,192,5000,1,0,0:0:0:0:
+300,192,6000,2,0,L|400:192,1,50
+256,192,8000,12,0,9000,0:0:0:0:
+";
+
+fn converted_notes(chart: &GenericManiaChart) -> Vec<(f64, u8, Option<f64>)> {
+    chart
+        .hitobjects
+        .iter()
+        .filter(|obj| matches!(obj.key.key_type, KeyType::Normal | KeyType::SliderStart))
+        .map(|obj| (obj.time, obj.lane, obj.key.slider_end_time))
+        .collect()
+}
+
+#[test]
+fn osu_convert_patterns_test() {
+    let chart = parse::from_osu_convert_to_mania(CONVERT_CHART, Some(4)).unwrap();
+    assert_eq!(chart.chartinfo.key_count, 4);
+
+    // traced by hand through osu!'s legacy pattern generators, the seed is round(HP + CS) * 20 + (int)(OD * 41.2) + round(AR) = 331
+    // and its first draws as doubles are 0.127, 0.238, 0.436, 0.897, 0.790, 0.526, 0.268
+    let expected = vec![
+        // a conversion difficulty of 1.56 gives single notes, one draw each, in the column under their x
+        (1000.0, 1, None),
+        (2000.0, 2, None),
+        (3000.0, 3, None),
+        (4000.0, 4, None),
+        // a stacked circle at low density mirrors the previous note
+        (5000.0, 1, None),
+        // the slider rolls 0.790 (under 0.83, so one hold) and then column 2 from 0.526, 50 long at 100 per beat is half a beat
+        (6000.0, 3, Some(6500.0)),
+        // the spinner rolls column 1 from 0.268
+        (8000.0, 2, Some(9000.0)),
+    ];
+    assert_eq!(converted_notes(&chart), expected);
+}
+
+#[test]
+fn osu_convert_key_count_test() {
+    // the key count osu! picks from the share of sliders and spinners and the rounded CS and OD:
+    // (difficulty, objects, sliders and spinners, expected keys)
+    let maps = [
+        ("Fort's Another", 877, 369, 7),
+        ("Hinsvar's Extra", 1145, 344, 7),
+        ("Pap's Easy", 155, 109, 4),
+        ("Tagre's Insane", 744, 344, 7),
+        ("neonat's Extra", 1058, 269, 7),
+        ("neonat's Hard", 615, 363, 6),
+        ("neonat's Normal", 333, 235, 4),
+    ];
+
+    for (difficulty, objects, special_objects, key_count) in maps {
+        let path = format!("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [{difficulty}].osu");
+        let raw_chart = read_file_to_string(&path).unwrap();
+        let osu_file = OsuFile::from_str(&raw_chart).unwrap();
+        assert_eq!(osu_file.hitobjects.count(), objects, "{difficulty}");
+        assert_eq!(objects - osu_file.hitobjects.hit_circle_count(), special_objects, "{difficulty}");
+
+        let chart = parse::from_osu_convert_to_mania(&raw_chart, None).unwrap();
+        assert_eq!(chart.chartinfo.key_count, key_count, "{difficulty}");
+    }
+}
+
+#[test]
+fn osu_convert_opening_spinner_test() {
+    // maps that open with a spinner turn it into a hold on the column of the first draw,
+    // seed 497 draws 0.127 first and 0.127 * 7 is column 0
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [Fort's Another].osu").unwrap();
+    let chart = parse::from_osu_convert_to_mania(&raw_chart, None).unwrap();
+    assert_eq!(converted_notes(&chart)[0], (12058.0, 1, Some(13391.0)));
+}
@@ -0,0 +1,75 @@
+mod test_stuff;
+use test_stuff::*;
+
+use common::TimingChangeType::{Bpm, Sv};
+
+// 120bpm then 240bpm from 2000, a 2x sv at 1000 and a 20x one at 3000, the "lane" group slows down to 0.5x at 1000
+// the note at 2500 on lane 4 is in the "lane" group
+fn scroll_chart() -> GenericManiaChart {
+    let mut chart = chart_from_objects(&[(500.0, 1, None), (1500.0, 1, None), (2500.0, 2, None), (2500.0, 4, None), (3500.0, 3, None)]);
+
+    let point = |time: f64, group: &str, change_type: common::TimingChangeType, value: f32| {
+        generic::TimingPoint::new(time, common::Beat::ZERO, group.to_string(), generic::TimingChange { value, change_type })
+    };
+    chart.timing_points = generic::TimingPoints::new(vec![
+        point(0.0, "", Bpm, 120.0),
+        point(1000.0, "", Sv, 2.0),
+        point(1000.0, "lane", Sv, 0.5),
+        point(2000.0, "", Bpm, 240.0),
+        point(3000.0, "", Sv, 20.0),
+    ]);
+
+    chart.hitobjects.objects[3].group = Some("lane".to_string());
+    chart
+}
+
+fn positions(chart: &GenericManiaChart, rules: generic::ScrollRules) -> Vec<f64> {
+    chart.scroll_positions(rules).positions
+}
+
+#[test]
+fn osu_scroll_positions_test() {
+    let chart = scroll_chart();
+
+    // 120bpm lasts the longest so it's 1x, the red line at 2000 resets the sv and 20x is clamped to 10x
+    // 0-1000 at 1x, 1000-2000 at 2x, 2000-3000 at 2x from the bpm, then 10x sv at 2x bpm
+    assert_eq!(positions(&chart, generic::ScrollRules::Osu), vec![500.0, 2000.0, 4000.0, 2000.0, 15000.0]);
+
+    // osu! has no groups, the other group keeps its sv over the red line and only gets the bpm
+    let scroll = chart.scroll_positions(generic::ScrollRules::Osu);
+    assert_eq!(scroll.position_at(2500.0, "lane"), 2000.0);
+    assert_eq!(scroll.position_at(-500.0, ""), -500.0);
+}
+
+#[test]
+fn quaver_scroll_positions_test() {
+    let mut chart = scroll_chart();
+
+    // nothing resets or clamps the sv, 20x at 2x bpm from 3000
+    chart.chartinfo.bpm_affects_sv = true;
+    assert_eq!(positions(&chart, generic::ScrollRules::Quaver), vec![500.0, 2000.0, 5000.0, 2000.0, 27000.0]);
+
+    // with BPMDoesNotAffectScrollVelocity only the sv counts
+    chart.chartinfo.bpm_affects_sv = false;
+    assert_eq!(positions(&chart, generic::ScrollRules::Quaver), vec![500.0, 2000.0, 4000.0, 1750.0, 15000.0]);
+
+    // quaver's default group names all share the ungrouped svs
+    let scroll = chart.scroll_positions(generic::ScrollRules::Quaver);
+    assert_eq!(scroll.position_at(3500.0, "$Default"), scroll.position_at(3500.0, ""));
+    assert_eq!(scroll.position_at(3500.0, "$Global"), 15000.0);
+}
+
+#[test]
+fn fluxis_scroll_positions_test() {
+    let mut chart = scroll_chart();
+
+    // the bpm never counts, even when the chart says it should
+    chart.chartinfo.bpm_affects_sv = true;
+    assert_eq!(positions(&chart, generic::ScrollRules::FluXis), vec![500.0, 2000.0, 4000.0, 1750.0, 15000.0]);
+
+    // groups without svs of their own scroll at 1x
+    let scroll = chart.scroll_positions(generic::ScrollRules::FluXis);
+    assert_eq!(scroll.position_at(3500.0, "other"), 3500.0);
+    assert_eq!(scroll.position_of(3), Some(1750.0));
+    assert_eq!(scroll.position_of(5), None);
+}
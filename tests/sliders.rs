@@ -0,0 +1,101 @@
+mod test_stuff;
+use test_stuff::*;
+
+use osu::{CurveType, SliderPath};
+
+fn assert_point(actual: (f32, f32), expected: (f32, f32), tolerance: f32) {
+    assert!(
+        (actual.0 - expected.0).abs() <= tolerance && (actual.1 - expected.1).abs() <= tolerance,
+        "{actual:?} is not {expected:?}"
+    );
+}
+
+#[test]
+fn linear_slider_path_test() {
+    let path = SliderPath::new(CurveType::Linear, vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)], None);
+    assert_eq!(path.distance(), 200.0);
+    assert_point(path.position_at(0.25), (50.0, 0.0), 1e-4);
+    assert_point(path.position_at(0.75), (100.0, 50.0), 1e-4);
+
+    // the length in the file cuts the last segment short or stretches it
+    let shorter = SliderPath::new(CurveType::Linear, vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)], Some(150.0));
+    assert_eq!(shorter.distance(), 150.0);
+    assert_point(shorter.end_position(), (100.0, 50.0), 1e-4);
+    let longer = SliderPath::new(CurveType::Linear, vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)], Some(250.0));
+    assert_eq!(longer.distance(), 250.0);
+    assert_point(longer.end_position(), (100.0, 150.0), 1e-4);
+
+    // osu!stable doesn't stretch a path whose last two points are the same
+    let stacked = SliderPath::new(CurveType::Linear, vec![(0.0, 0.0), (100.0, 0.0), (100.0, 0.0)], Some(250.0));
+    assert_eq!(stacked.distance(), 100.0);
+}
+
+#[test]
+fn perfect_curve_slider_path_test() {
+    // half a circle of radius 50 around (50, 0), osu! draws it with 24 chords so it's a bit shorter than 50 * pi
+    let path = SliderPath::new(CurveType::PerfectCurve, vec![(0.0, 0.0), (50.0, 50.0), (100.0, 0.0)], None);
+    assert_eq!(path.calculated_path().len(), 25);
+    assert!((path.distance() - 2400.0 * (std::f64::consts::PI / 48.0).sin()).abs() < 1e-3, "{}", path.distance());
+    assert_point(path.position_at(0.5), (50.0, 50.0), 0.1);
+    assert_point(path.end_position(), (100.0, 0.0), 1e-3);
+
+    // three points on a line have no circle, osu! falls back to a bezier curve
+    let straight = SliderPath::new(CurveType::PerfectCurve, vec![(0.0, 0.0), (50.0, 0.0), (100.0, 0.0)], None);
+    assert!((straight.distance() - 100.0).abs() < 1e-3);
+}
+
+#[test]
+fn bezier_slider_path_test() {
+    // the exact length of this quadratic curve is 147.894
+    let path = SliderPath::new(CurveType::Bezier, vec![(0.0, 0.0), (50.0, 100.0), (100.0, 0.0)], None);
+    assert!((path.distance() - 147.894).abs() < 0.1, "{}", path.distance());
+    assert_point(path.position_at(0.5), (50.0, 50.0), 0.1);
+    assert_point(path.end_position(), (100.0, 0.0), 1e-3);
+
+    // a repeated point starts a new segment, two straight ones here
+    let segments = SliderPath::new(CurveType::Bezier, vec![(0.0, 0.0), (100.0, 0.0), (100.0, 0.0), (100.0, 100.0)], None);
+    assert!((segments.distance() - 200.0).abs() < 1e-3);
+    assert_point(segments.position_at(0.5), (100.0, 0.0), 1e-3);
+}
+
+#[test]
+fn catmull_slider_path_test() {
+    // catmull curves pass through every control point
+    let path = SliderPath::new(CurveType::Catmull, vec![(0.0, 0.0), (100.0, 50.0), (200.0, 0.0)], None);
+    assert!(path.calculated_path().iter().any(|&point| point == (100.0, 50.0)));
+    assert_point(path.end_position(), (200.0, 0.0), 1e-3);
+
+    let straight = SliderPath::new(CurveType::Catmull, vec![(0.0, 0.0), (100.0, 0.0), (200.0, 0.0)], None);
+    assert!((straight.distance() - 200.0).abs() < 1e-3);
+    assert_point(straight.position_at(0.5), (100.0, 0.0), 1e-3);
+}
+
+#[test]
+fn slider_end_time_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [Hinsvar's Extra].osu").unwrap();
+    let osu_file = OsuFile::from_str(&raw_chart).unwrap();
+    let slider_at = |time: f32| osu_file.hitobjects.iter().find(|obj| obj.time == time).unwrap();
+    let end_time = |time: f32| slider_at(time).slider_end_time(&osu_file.timing_points, &osu_file.difficulty);
+
+    // a span lasts length / (SliderMultiplier * 100 * SV) * beatLength, with SliderMultiplier 2.4 and a 333.33ms beat
+    // 90 long at 0.75x: 90 / 180 * 333.33 = 166.67
+    assert_eq!(end_time(24224.0), Some(24390));
+    // 120 long at 1x: 120 / 240 * 333.33 = 166.67
+    assert_eq!(end_time(28057.0), Some(28223));
+    // 60 long at 1x going back once: 2 * 60 / 240 * 333.33 = 166.67
+    assert_eq!(slider_at(26724.0).slider_params().unwrap().2, 2);
+    assert_eq!(end_time(26724.0), Some(26890));
+
+    // sliders don't store their end time, only the timing points can give it
+    assert_eq!(slider_at(24224.0).end_time(), None);
+
+    // the arc is cut off at 90 of its 102.13, the circle goes through (299, 195), (346, 202) and (396, 183)
+    let arc = slider_at(24224.0).slider_path().unwrap();
+    assert_eq!(arc.curve_type, CurveType::PerfectCurve);
+    assert!((arc.distance() - 90.0).abs() < 1e-4);
+    assert_point(arc.end_position(), (385.819, 189.574), 0.1);
+
+    // the line to (481, 227) is 127.47 long, the slider stops 120 along it
+    let line = slider_at(28057.0).slider_path().unwrap();
+    assert_point(line.end_position(), (480.355, 234.447), 1e-3);
+}
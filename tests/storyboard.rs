@@ -0,0 +1,200 @@
+mod test_stuff;
+use test_stuff::*;
+
+use std::str::FromStr;
+
+const STORYBOARD: &str = r#"[Variables]
+$sprite="sb/star.png"
+$sp=Sprite,Foreground,Centre
+
+[Events]
+//Background and Video events
+//Storyboard Layer 0 (Background)
+Sprite,Background,TopLeft,"sb/bg.jpg",0,0
+ F,0,0,1000,0,1
+ F,0,5000,6000,1,0
+//Storyboard Layer 1 (Fail)
+//Storyboard Layer 2 (Pass)
+//Storyboard Layer 3 (Foreground)
+$sp,$sprite,320,240
+ M,0,1000,2000,320,240,420,240
+ L,3000,2
+  S,0,0,500,1,2
+  R,0,500,1000,0,1.5
+ T,HitSoundClap,0,10000
+  C,0,0,100,255,0,0,255,255,255
+//Storyboard Layer 4 (Overlay)
+Animation,Overlay,Centre,"sb/anim.png",100,100,4,50,LoopForever
+ F,0,0,2000,1
+ P,0,1000,,H
+//Storyboard Sound Samples
+Sample,1000,3,"sb/hit.wav",80
+"#;
+
+fn object<'a>(storyboard: &'a osu::Storyboard, filepath: &str) -> &'a osu::StoryboardObject {
+    storyboard.objects.iter().find(|object| object.filepath == filepath).unwrap()
+}
+
+fn visible_files(storyboard: &osu::Storyboard, time: i32) -> Vec<String> {
+    storyboard
+        .visible_objects_at(time)
+        .iter()
+        .map(|(object, _)| object.filepath.clone())
+        .collect()
+}
+
+#[test]
+fn osb_round_trip_test() {
+    let storyboard = parse::from_osb(STORYBOARD).unwrap();
+
+    assert_eq!(storyboard.variables.len(), 2);
+    assert_eq!(storyboard.objects.len(), 3);
+    assert_eq!(storyboard.samples.len(), 1);
+
+    // the variables are filled in before the line is read
+    let star = object(&storyboard, "sb/star.png");
+    assert_eq!(star.layer, osu::Layer::Foreground);
+    assert_eq!(star.origin, osu::Origin::Centre);
+    assert_eq!(star.commands.len(), 1);
+    assert_eq!(star.loops.len(), 1);
+    assert_eq!(star.loops[0].start_time, 3000);
+    assert_eq!(star.loops[0].loop_count, 2);
+    assert_eq!(star.loops[0].commands.len(), 2);
+    assert_eq!(star.triggers.len(), 1);
+    assert_eq!(star.triggers[0].trigger_name, "HitSoundClap");
+    assert_eq!(star.triggers[0].commands.len(), 1);
+
+    let anim = object(&storyboard, "sb/anim.png");
+    assert!(anim.is_animation());
+    assert_eq!(
+        anim.commands[1].values,
+        osu::CommandValues::Parameter(osu::ParameterType::FlipHorizontal)
+    );
+
+    let written = write::to_osb(&storyboard).unwrap();
+    assert!(written.contains("Sprite,Foreground,Centre,\"sb/star.png\",320,240\n"));
+    assert!(written.contains("\n L,3000,2\n  S,0,0,500,1,2\n"));
+    assert!(written.contains("\n T,HitSoundClap,0,10000\n  C,0,0,100,255,0,0,255,255,255\n"));
+
+    let reparsed = parse::from_osb(&written).unwrap();
+    assert_eq!(reparsed, storyboard);
+    assert_eq!(write::to_osb(&reparsed).unwrap(), written);
+}
+
+#[test]
+fn inline_events_round_trip_test() {
+    let (_, events) = STORYBOARD.split_once("[Events]\n").unwrap();
+    let variables = vec![
+        ("$sprite".to_string(), "\"sb/star.png\"".to_string()),
+        ("$sp".to_string(), "Sprite,Foreground,Centre".to_string()),
+    ];
+    let events = format!("0,0,\"bg.jpg\",0,0\n2,20000,25000\n{events}");
+
+    let parsed = osu::Events::from_str_with_variables(&events, &variables).unwrap();
+    assert_eq!(parsed.background.as_ref().unwrap().filename, "bg.jpg");
+    assert_eq!(parsed.breaks.len(), 1);
+    assert_eq!(parsed.samples.len(), 1);
+    assert_eq!(parsed.storyboard.objects.len(), 3);
+    // inline storyboards keep their samples with the other events
+    let osb = parse::from_osb(STORYBOARD).unwrap();
+    assert_eq!(parsed.storyboard.objects, osb.objects);
+    assert!(parsed.storyboard.samples.is_empty());
+
+    // the written events no longer need the variables
+    let written = parsed.to_str();
+    assert!(!written.contains('$'));
+    let reparsed = osu::Events::from_str(&written).unwrap();
+    assert_eq!(reparsed.storyboard, parsed.storyboard);
+    assert_eq!(reparsed.breaks, parsed.breaks);
+    assert_eq!(reparsed.samples, parsed.samples);
+    assert_eq!(reparsed.to_str(), written);
+}
+
+#[test]
+fn storyboard_state_test() {
+    let storyboard = parse::from_osb(STORYBOARD).unwrap();
+
+    // fades in over the first second and out over the last one
+    let bg = object(&storyboard, "sb/bg.jpg");
+    assert_eq!(bg.state_at(-100).alpha, 0.0);
+    assert_eq!(bg.state_at(500).alpha, 0.5);
+    assert_eq!(bg.state_at(3000).alpha, 1.0);
+    assert_eq!(bg.state_at(5500).alpha, 0.5);
+    assert_eq!(bg.state_at(6000).alpha, 0.0);
+
+    // the first move's start value is used before it starts and its end value after it ends
+    let star = object(&storyboard, "sb/star.png");
+    assert_eq!(star.start_time(), Some(1000));
+    assert_eq!(star.end_time(), Some(5000));
+    assert_eq!(star.state_at(500).position, (320.0, 240.0));
+    assert_eq!(star.state_at(1500).position, (370.0, 240.0));
+    assert_eq!(star.state_at(2500).position, (420.0, 240.0));
+
+    // each loop iteration scales up over 500ms, then rotates over the next 500ms
+    assert_eq!(star.state_at(2500).scale, (1.0, 1.0));
+    assert_eq!(star.state_at(3250).scale, (1.5, 1.5));
+    assert_eq!(star.state_at(3750).scale, (2.0, 2.0));
+    assert_eq!(star.state_at(3750).rotation, 0.75);
+    assert_eq!(star.state_at(4000).scale, (1.0, 1.0));
+    assert_eq!(star.state_at(4250).scale, (1.5, 1.5));
+    assert_eq!(star.state_at(4750).rotation, 0.75);
+    assert_eq!(star.state_at(6000).scale, (2.0, 2.0));
+    assert_eq!(star.state_at(6000).rotation, 1.5);
+
+    // triggers only run in game
+    assert_eq!(star.state_at(50).colour, (255, 255, 255));
+
+    let anim = object(&storyboard, "sb/anim.png");
+    assert_eq!(anim.state_at(125).frame, Some(2));
+    assert_eq!(anim.file_at(125), "sb/anim2.png");
+    assert_eq!(anim.state_at(225).frame, Some(0));
+    assert!(!anim.state_at(500).flip_horizontal);
+    // parameters without a duration stay on
+    assert!(anim.state_at(1500).flip_horizontal);
+}
+
+#[test]
+fn storyboard_visible_objects_test() {
+    let storyboard = parse::from_osb(STORYBOARD).unwrap();
+
+    assert!(visible_files(&storyboard, -100).is_empty());
+    // the background hasn't faded in yet
+    assert_eq!(visible_files(&storyboard, 0), vec!["sb/anim.png"]);
+    assert_eq!(visible_files(&storyboard, 500), vec!["sb/bg.jpg", "sb/anim.png"]);
+    assert_eq!(visible_files(&storyboard, 1500), vec!["sb/bg.jpg", "sb/star.png", "sb/anim.png"]);
+    assert_eq!(visible_files(&storyboard, 2500), vec!["sb/bg.jpg", "sb/star.png"]);
+    assert_eq!(visible_files(&storyboard, 4500), vec!["sb/bg.jpg", "sb/star.png"]);
+    assert_eq!(visible_files(&storyboard, 5500), vec!["sb/bg.jpg"]);
+    assert!(visible_files(&storyboard, 6000).is_empty());
+    assert!(visible_files(&storyboard, 7000).is_empty());
+}
+
+#[test]
+fn malformed_storyboard_lines_test() {
+    let events = STORYBOARD
+        .replace(" F,0,5000,6000,1,0\n", " F,0,5000,6000,1,0\n F,0,oops,6000\n")
+        .replace(
+            "//Storyboard Layer 1 (Fail)\n",
+            "//Storyboard Layer 1 (Fail)\nSprite,Nowhere,Centre,\"sb/bad.png\",0,0\n M,0,0,1000,0,0,100,100\n",
+        );
+
+    // the bad lines are skipped, everything around them is still read
+    let storyboard = parse::from_osb(&events).unwrap();
+    let osb = parse::from_osb(STORYBOARD).unwrap();
+    assert_eq!(storyboard.objects, osb.objects);
+    assert_eq!(storyboard.samples, osb.samples);
+
+    // the move under the unreadable sprite doesn't end up on the background
+    let bg = object(&storyboard, "sb/bg.jpg");
+    assert_eq!(bg.commands.len(), 2);
+    assert_eq!(bg.state_at(3000).position, (0.0, 0.0));
+
+    // one bad line in a beatmap's events doesn't stop the rest of the beatmap from loading
+    let raw_chart = read_file_to_string("./tests/Maps/osu/1356087_YoruNiKakeru/Kisaragi Rumii - Yoru ni Kakeru -THE HOME TAKE ver.- (Cut Ver.) (Irone OSU) [Hard].osu").unwrap();
+    let expected = osu::OsuFile::from_str(&raw_chart).unwrap();
+    let broken = raw_chart.replacen("[Events]", "[Events]\nSprite,Nowhere,Centre,\"sb/bad.png\",0,0\n F,0,oops", 1);
+    let osu_file = osu::OsuFile::from_str(&broken).unwrap();
+    assert_eq!(osu_file.hitobjects, expected.hitobjects);
+    assert_eq!(osu_file.timing_points, expected.timing_points);
+    assert!(osu_file.events.storyboard.objects.is_empty());
+}
@@ -5,6 +5,8 @@ use std::io::{self, Read, Write};
 use std::path::Path;
 use std::str;
 
+use rgchart::{parse, GenericManiaChart};
+
 pub fn read_file_to_string(file_path: &str) -> io::Result<String> {
     let mut file = fs::File::open(file_path)?;
     let mut contents = Vec::new();
@@ -57,17 +59,39 @@ pub fn println_test(func_name: &str, color_code: &str, message: &str) {
     writeln!(io::stdout(), "{} || {}{}\x1b[0m", func_name, color_code, message).unwrap();
 }
 
+// 4k osu! chart from (time, lane, hold end) with a 150bpm timing point, so 100ms gaps are 1/4 snaps
+pub fn chart_from_objects(objects: &[(f64, u8, Option<f64>)]) -> GenericManiaChart {
+    let mut raw_chart = String::from("osu file format v14\n\n[General]\nAudioFilename: audio.mp3\nMode: 3\n\n[Metadata]\nTitle:Patterns\nVersion:4K\n\n[Difficulty]\nHPDrainRate:8\nCircleSize:4\nOverallDifficulty:8\n\n[TimingPoints]\n0,400,4,1,0,100,1,0\n\n[HitObjects]\n");
+    for (time, lane, end) in objects {
+        let x = (*lane as u32 - 1) * 128 + 64;
+        match end {
+            Some(end) => raw_chart.push_str(&format!("{x},192,{time},128,0,{end}:0:0:0:0:\n")),
+            None => raw_chart.push_str(&format!("{x},192,{time},1,0,0:0:0:0:\n")),
+        }
+    }
+    parse::from_osu_generic(&raw_chart).unwrap()
+}
+
+// rows like "12 3 4" are 100ms apart
+pub fn chart_from_rows(rows: &str) -> GenericManiaChart {
+    let objects: Vec<_> = rows
+        .split_whitespace()
+        .enumerate()
+        .flat_map(|(index, row)| row.bytes().map(move |lane| (index as f64 * 100.0, lane - b'0', None)))
+        .collect();
+    chart_from_objects(&objects)
+}
+
 #[macro_export]
 macro_rules! parse_and_convert {
     ($name:ident, $file_path:expr, $parse_fn:expr, $convert_fn:expr, $write_to_file:expr) => {{
         use std::time::Instant;
         use std::io::{self, Write};
         use std::path::Path;
-        use crate::GenericManiaChart;
         
         println_test(stringify!($name), "\x1b[34m", "Started running test");
 
-        let result = (|| -> Result<(GenericManiaChart, String), Box<dyn std::error::Error>> {
+        let result = (|| -> Result<(_, String), Box<dyn std::error::Error>> {
             let input_path = $file_path;
             
             let raw_chart = read_file_to_string(input_path).map_err(|e| {
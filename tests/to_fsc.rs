@@ -33,3 +33,70 @@ fn osu_to_fsc_test() {
         true
     );
 }
+
+#[test]
+fn fsc_hidden_barlines_round_trip_test() {
+    let (mut chart, _) = parse_and_convert!(
+        sm_to_fsc,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm_generic,
+        write::to_fsc_generic,
+        false
+    );
+
+    let visible = chart.barlines().len();
+    {
+        let mut bpm_points = chart.timing_points.iter_mut().filter(|tp| tp.change.change_type == common::TimingChangeType::Bpm);
+        bpm_points.nth(2).unwrap().effects = Some(generic::TimingEffects { hide_barlines: true, ..Default::default() });
+        bpm_points.nth(1).unwrap().effects = Some(generic::TimingEffects { omit_first_barline: true, ..Default::default() });
+    }
+
+    let fsc = parse::from_fsc_generic(&write::to_fsc_generic(&chart).unwrap()).unwrap();
+    let osu = parse::from_osu_generic(&write::to_osu_generic(&fsc).unwrap()).unwrap();
+    let round_trip = parse::from_fsc_generic(&write::to_fsc_generic(&osu).unwrap()).unwrap();
+
+    let (expected, barlines) = (chart.barlines(), round_trip.barlines());
+    assert!(expected.len() < visible);
+    assert_eq!(expected.len(), barlines.len());
+    for (expected, barline) in expected.iter().zip(&barlines) {
+        assert!((expected - barline).abs() < 1.0, "{} {}", expected, barline);
+    }
+}
+
+#[test]
+fn fsc_colors_keep_their_roles_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/fluXis/935_hard-coremata/Kobaryo feat. Various Artist - HARD-COREMATA (el_matero) [COREMATA].osu.fsc").unwrap();
+    let chart = parse::from_fsc_generic(&raw_chart).unwrap();
+
+    // the accent is empty, primary must not move into its place
+    let colors = chart.colors.as_ref().unwrap();
+    assert!(colors.accent.is_none());
+    assert_eq!(colors.primary.map(|color| color.to_hex()).as_deref(), Some("#B119AC"));
+    assert_eq!(colors.combo_colors.len(), 3);
+
+    let written = write::to_fsc_generic(&chart).unwrap();
+    assert!(written.contains("\"accent\":\"\""));
+    let round_trip = parse::from_fsc_generic(&written).unwrap();
+    assert_eq!(round_trip.colors.unwrap().theme_colors(), colors.theme_colors());
+
+    // osu! only has combo colors, they fill the theme colors in order
+    let osu = parse::from_osu_generic(&write::to_osu_generic(&chart).unwrap()).unwrap();
+    let theme = osu.colors.as_ref().unwrap().theme_colors();
+    assert_eq!(theme[0], colors.primary);
+    assert!(theme[3].is_none());
+}
+
+#[test]
+fn fsc_hold_end_beats_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/fluXis/935_hard-coremata/Kobaryo feat. Various Artist - HARD-COREMATA (el_matero) [COREMATA].osu.fsc").unwrap();
+    let chart = parse::from_fsc_generic(&raw_chart).unwrap();
+
+    // hold ends get the beat of their own time, not the head's, or rows built from them land on the wrong beat
+    let objects = &chart.hitobjects.objects;
+    assert!(objects.iter().any(|obj| obj.key.key_type == common::KeyType::SliderEnd));
+    for pair in objects.windows(2) {
+        if pair[0].time == pair[1].time {
+            assert_eq!(pair[0].beat, pair[1].beat, "{}", pair[0].time);
+        }
+    }
+}
@@ -1,6 +1,20 @@
 mod test_stuff;
 use test_stuff::*;
 
+#[test]
+fn osu_to_osu_test() {
+    let (chart, converted) = parse_and_convert!(
+        osu_to_osu,
+        "./tests/Maps/osu/1356087_YoruNiKakeru/Kisaragi Rumii - Yoru ni Kakeru -THE HOME TAKE ver.- (Cut Ver.) (Irone OSU) [Hard].osu",
+        parse::from_osu_generic,
+        write::to_osu_generic,
+        true
+    );
+
+    let diff = analysis::diff(&chart, &parse::from_osu_generic(&converted).unwrap());
+    assert!(diff.is_empty(), "{diff:?}");
+}
+
 #[test]
 fn sm_to_osu_test() {
     parse_and_convert!(
@@ -44,3 +58,307 @@ fn fsc_to_osu_test_sv_groups() {
         true
     );
 }
+
+#[test]
+fn timing_map_test() {
+    let point = |time: f64, group: &str, change_type: common::TimingChangeType, value: f32| {
+        generic::TimingPoint::new(time, common::Beat::ZERO, group.to_string(), generic::TimingChange { value, change_type })
+    };
+    use common::TimingChangeType::{Bpm, Stop, Sv};
+
+    // 120bpm from 1000, 240bpm from 3000 with a 500ms stop at 4000, 60bpm from 5500
+    let timing_points = generic::TimingPoints::new(vec![
+        point(1000.0, "", Bpm, 120.0),
+        point(2000.0, "", Sv, 0.5),
+        point(3000.0, "", Bpm, 240.0),
+        point(3000.0, "lane", Sv, 3.0),
+        point(4000.0, "", Stop, 500.0),
+        point(5000.0, "", Sv, 2.0),
+        point(5500.0, "", Bpm, 60.0),
+    ]);
+    let timing_map = generic::TimingMap::new(&timing_points, true, 9500.0);
+
+    // times before the first point count backwards with its bpm, beats don't move during the stop
+    for (time, beat) in [(0.0, -2.0), (2000.0, 2.0), (3500.0, 6.0), (4000.0, 8.0), (4250.0, 8.0), (4750.0, 9.0), (7500.0, 14.0)] {
+        assert_eq!(timing_map.beat_at(time), beat, "beat at {time}");
+    }
+    for (beat, time) in [(-2.0, 0.0), (2.0, 2000.0), (6.0, 3500.0), (8.0, 4000.0), (9.0, 4750.0), (14.0, 7500.0)] {
+        assert_eq!(timing_map.time_at(beat), time, "time at beat {beat}");
+    }
+    assert_eq!(timing_map.bpm_at(0.0), Some(120.0));
+    assert_eq!(timing_map.bpm_at(4250.0), Some(240.0));
+    assert_eq!(timing_map.ms_per_beat_at(6000.0), Some(1000.0));
+
+    // 60bpm lasts 4000ms up to the end, 240bpm 2500ms with the stop and 120bpm 2000ms
+    assert_eq!(timing_map.base_bpm(), 60.0);
+    assert_eq!(generic::TimingMap::new(&timing_points, true, 6000.0).base_bpm(), 240.0);
+
+    assert_eq!(timing_map.sv_at(500.0, "$Default"), 1.0);
+    assert_eq!(timing_map.sv_at(2500.0, ""), 0.5);
+    assert_eq!(timing_map.sv_at(4000.0, "lane"), 3.0);
+    assert_eq!(timing_map.sv_at(4000.0, "other"), 1.0);
+
+    // speed is sv times bpm / 60, nothing moves during the stop
+    let positions = [
+        ("", [(500.0, 1000.0), (2500.0, 4500.0), (4250.0, 7000.0), (5250.0, 10000.0), (7500.0, 16000.0)]),
+        ("lane", [(500.0, 1000.0), (2500.0, 5000.0), (4250.0, 18000.0), (5250.0, 27000.0), (7500.0, 36000.0)]),
+        ("other", [(500.0, 1000.0), (2500.0, 5000.0), (4250.0, 10000.0), (5250.0, 13000.0), (7500.0, 16000.0)]),
+    ];
+    for (group, expected) in positions {
+        for (time, position) in expected {
+            assert_eq!(timing_map.position_at(time, group), position, "{group} position at {time}");
+        }
+    }
+
+    // without the bpm only the sv counts
+    let sv_only = generic::TimingMap::new(&timing_points, false, 9500.0);
+    assert_eq!(sv_only.position_at(2500.0, ""), 2250.0);
+    assert_eq!(sv_only.position_at(7500.0, ""), 8250.0);
+    assert_eq!(sv_only.position_at(7500.0, "other"), 7000.0);
+}
+
+#[test]
+fn osu_snap_divisors_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/360565_HatsuneMikuNoShoushitsu/cosMo@BousouP feat. Hatsune Miku - Hatsune Miku no Shoushitsu (juankristal) [NM].osu").unwrap();
+    let chart = parse::from_osu_generic(&raw_chart).unwrap();
+
+    // the 240bpm point lands between two beats, snaps have to start over there to stay 1/1 and 1/2
+    let snap_divisors = chart.snap_divisors();
+    assert_eq!(snap_divisors.divisors.len(), chart.hitobjects.objects.len());
+    assert!(snap_divisors.divisors.iter().all(|divisor| *divisor == 1 || *divisor == 2));
+    assert!(chart.hitobjects.iter().any(|obj| chart.timing_map().snap(obj.time, &[1, 2, 4, 8, 16, 32]).unwrap().divisor() == 32));
+
+    // 4ths are red like in StepMania
+    let whole = snap_divisors.divisors.iter().position(|divisor| *divisor == 1).unwrap();
+    assert_eq!(snap_divisors.color_of(whole).unwrap().to_hex(), "#FF0000");
+}
+
+#[test]
+fn osu_colours_skip_malformed_lines_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [Tagre's Insane].osu").unwrap();
+    let raw_chart = raw_chart
+        .replace("Combo2 : 253,232,113", "Combo2 : 253,oops,113")
+        .replace("[Colours]", "[Colours]\nSliderBorder : not a colour");
+
+    // osu! drops the bad lines and keeps the rest of the section
+    let chart = parse::from_osu_convert_to_mania(&raw_chart, Some(4)).unwrap();
+    let colors = chart.colors.unwrap();
+    let hex: Vec<String> = colors.combo_colors.iter().map(|color| color.to_hex()).collect();
+    assert_eq!(hex, ["#F5A7E6", "#F5B896", "#87BAF3"]);
+    assert!(colors.slider_border.is_none());
+}
+
+const OSU_V3: &str = "osu file format v3
+
+[General]
+AudioFilename: audio.mp3
+PreviewTime: 1000
+
+[Metadata]
+Title:Old
+Version:Easy
+
+[Difficulty]
+HPDrainRate:5
+CircleSize:4
+OverallDifficulty:6
+SliderMultiplier:1.4
+
+[Events]
+
+[TimingPoints]
+1000,500
+3000,-50
+
+[HitObjects]
+64,192,1000,1,0
+192,192,1500,1,2
+320,192,2000,12,0,2500
+";
+
+const OSU_V5: &str = "osu file format v5
+
+[General]
+AudioFilename: audio.mp3
+
+[Difficulty]
+CircleSize:4
+OverallDifficulty:6
+SliderMultiplier:1.4
+
+[TimingPoints]
+1000,500,3,2,1,60
+
+[HitObjects]
+64,192,1000,1,2,1:2
+64,192,1500,2,0,B|100:192,1,70,2|0,1:0|0:0
+";
+
+const OSU_V12: &str = "\u{feff}osu file format v12
+
+[General]
+AudioFilename: audio.mp3
+Mode: 3
+
+[Difficulty]
+CircleSize:4
+OverallDifficulty:6
+SliderMultiplier:1.4
+
+[TimingPoints]
+1000,500,4,2,1,60,1,1
+
+[HitObjects]
+64,192,1000,128,0,1500:1:0:2:70:hit.wav
+";
+
+#[test]
+fn osu_format_version_test() {
+    assert_eq!(osu::format_version_from_str(OSU_V3), Some(3));
+    assert_eq!(osu::format_version_from_str(OSU_V5), Some(5));
+    assert_eq!(osu::format_version_from_str(OSU_V12), Some(12));
+    assert_eq!(osu::format_version_from_str("\n\nosu file format v7\n"), Some(7));
+    assert_eq!(osu::format_version_from_str("[General]\nosu file format v7\n"), None);
+
+    // v3 and v4 are played 24ms late, their times get moved when parsing
+    let v3 = OsuFile::from_str(OSU_V3).unwrap();
+    assert_eq!(v3.version, 3);
+    assert_eq!(v3.general.preview_time, 1024);
+    let times: Vec<_> = v3.hitobjects.iter().map(|obj| obj.time).collect();
+    assert_eq!(times, vec![1024.0, 1524.0, 2024.0]);
+    assert_eq!(v3.hitobjects.get(2).unwrap().end_time(), Some(2524));
+    // ApproachRate didn't exist yet
+    assert_eq!(v3.difficulty.approach_rate, 6.0);
+
+    // timing points only had a time and beat length, negative ones are inherited
+    let points = &v3.timing_points.timing_points;
+    assert_eq!(points.len(), 2);
+    assert_eq!((points[0].time, points[0].beat_length, points[0].uninherited), (1024.0, 500.0, true));
+    assert_eq!((points[1].time, points[1].beat_length, points[1].uninherited), (3024.0, -50.0, false));
+    assert_eq!((points[0].meter, points[0].volume, points[0].effects), (4, 100, 0));
+
+    // v5 has no offset, and timing points have no uninherited flag or effects
+    let v5 = OsuFile::from_str(OSU_V5).unwrap();
+    assert_eq!(v5.version, 5);
+    let point = &v5.timing_points.timing_points[0];
+    assert_eq!(point.time, 1000.0);
+    assert_eq!((point.meter, point.sample_set, point.sample_index, point.volume), (3, 2, 1, 60));
+    assert!(point.uninherited);
+    assert_eq!(point.effects, 0);
+
+    // hit samples only had the two sets
+    let circle = v5.hitobjects.get(0).unwrap();
+    assert_eq!(circle.time, 1000.0);
+    assert_eq!((circle.hit_sample.normal_set, circle.hit_sample.addition_set), (1, 2));
+    assert_eq!((circle.hit_sample.index, circle.hit_sample.volume), (0, 0));
+    assert!(circle.hit_sample.filename.is_empty());
+
+    // the edge sets have colons but aren't a hit sample
+    let slider = v5.hitobjects.get(1).unwrap();
+    assert_eq!(slider.object_params.len(), 5);
+    assert_eq!(slider.slider_edge_sets(), vec![(1, 0), (0, 0)]);
+    assert_eq!(slider.hit_sample, osu::HitSample::default());
+
+    let v12 = OsuFile::from_str(OSU_V12).unwrap();
+    assert_eq!(v12.version, 12);
+    let point = &v12.timing_points.timing_points[0];
+    assert_eq!((point.time, point.uninherited, point.effects), (1000.0, true, 1));
+    let hold = v12.hitobjects.get(0).unwrap();
+    assert_eq!(hold.time, 1000.0);
+    assert_eq!(hold.end_time(), Some(1500));
+    assert_eq!(hold.hit_sample.normal_set, 1);
+    assert_eq!((hold.hit_sample.index, hold.hit_sample.volume), (2, 70));
+    assert_eq!(hold.hit_sample.filename, "hit.wav");
+}
+
+#[test]
+fn osu_old_version_round_trip_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/1356087_YoruNiKakeru/Kisaragi Rumii - Yoru ni Kakeru -THE HOME TAKE ver.- (Cut Ver.) (Irone OSU) [Hard].osu").unwrap();
+    let chart = parse::from_osu_generic(&raw_chart).unwrap();
+    let first_time = chart.hitobjects.iter().next().unwrap().time;
+    let first_point = chart.timing_points.iter().next().unwrap().time;
+
+    let written = write::to_osu_generic_with_version(&chart, 4).unwrap();
+    assert!(written.starts_with("osu file format v4\n"));
+
+    // written 24ms early with the pre-v6 timing point columns
+    let old_file = OsuFile::from_str(&written).unwrap();
+    let timing_lines: Vec<&str> = written
+        .split("[TimingPoints]\n")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .collect();
+    assert!(!timing_lines.is_empty());
+    assert!(timing_lines.iter().all(|line| line.split(',').count() == 6));
+    assert!(timing_lines[0].starts_with(&format!("{},", first_point - 24.0)));
+    assert_eq!(old_file.hitobjects.get(0).unwrap().time as f64, first_time);
+
+    // notes in a chord can come back in another order
+    let notes = |chart: &GenericManiaChart| {
+        let mut notes: Vec<_> = chart
+            .hitobjects
+            .iter()
+            .map(|obj| (obj.time, obj.lane, obj.key.key_type, obj.key.slider_end_time))
+            .collect();
+        notes.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        notes
+    };
+    let reparsed = parse::from_osu_generic(&written).unwrap();
+    assert_eq!(notes(&reparsed), notes(&chart));
+    let bpms = |chart: &GenericManiaChart| chart.timing_points.bpm_changes().map(|tp| (tp.time, tp.change.value)).collect::<Vec<_>>();
+    assert_eq!(bpms(&reparsed), bpms(&chart));
+}
+
+const OSU_4K_LANES: &str = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+Mode: 3
+
+[Metadata]
+Title:Lanes
+Version:4K
+
+[Difficulty]
+HPDrainRate:8
+CircleSize:4
+OverallDifficulty:8
+
+[TimingPoints]
+0,500,4,1,0,100,1,0
+
+[HitObjects]
+64,192,0,1,0,0:0:0:0:
+192,192,500,1,0,0:0:0:0:
+320,192,1000,1,0,0:0:0:0:
+448,192,1500,128,0,2000:0:0:0:0:
+";
+
+#[test]
+fn osu_mania_lanes_test() {
+    // osu! columns start at 0, generic lanes start at 1
+    let chart = parse::from_osu_generic(OSU_4K_LANES).unwrap();
+    let lanes: Vec<_> = chart.hitobjects.iter().map(|obj| obj.lane).collect();
+    // the hold end is an object in the same lane
+    assert_eq!(lanes, vec![1, 2, 3, 4, 4]);
+
+    // the edges of each 128 wide column, x outside of the playfield goes to the closest lane
+    for (x, lane) in [(-5, 1), (0, 1), (127, 1), (128, 2), (383, 3), (384, 4), (511, 4), (600, 4)] {
+        assert_eq!(osu::HitObject::new(x, 192, 0.0, 1, 0).mania_lane(4), lane, "x {x}");
+    }
+
+    // and go back to the same columns when written
+    let written = write::to_osu_generic(&chart).unwrap();
+    assert!(written.contains("\n0,192,0,1,0,") && written.contains("\n384,192,1500,128,0,2000:"));
+    let reparsed = parse::from_osu_generic(&written).unwrap();
+    let reparsed_lanes: Vec<_> = reparsed.hitobjects.iter().map(|obj| obj.lane).collect();
+    assert_eq!(reparsed_lanes, lanes);
+
+    // every other mania writer takes the same 1-based lanes
+    let lanes_of = |chart: GenericManiaChart| chart.hitobjects.iter().map(|obj| obj.lane).collect::<Vec<_>>();
+    assert_eq!(lanes_of(parse::from_qua_generic(&write::to_qua_generic(&chart).unwrap()).unwrap()), lanes);
+    assert_eq!(lanes_of(parse::from_fsc_generic(&write::to_fsc_generic(&chart).unwrap()).unwrap()), lanes);
+    assert_eq!(lanes_of(parse::from_sm_generic(&write::to_sm_generic(&chart).unwrap()).unwrap()), lanes);
+}
@@ -12,6 +12,17 @@ fn osu_to_qua_test() {
     );
 }
 
+#[test]
+fn osu_convert_to_qua_test() {
+    parse_and_convert!(
+        osu_convert_to_qua,
+        "./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [Tagre's Insane].osu",
+        |raw_chart: &str| parse::from_osu_convert_to_mania(raw_chart, Some(4)),
+        write::to_qua_generic,
+        true
+    );
+}
+
 #[test]
 fn sm_to_qua_test() {
     parse_and_convert!(
@@ -44,3 +55,149 @@ fn fsc_to_qua_test_sv_groups() {
         true
     );
 }
+
+#[test]
+fn osu_to_qua_to_osu_test() {
+    let (chart, converted) = parse_and_convert!(
+        osu_to_qua,
+        "./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [7K OVERLOAD].osu",
+        parse::from_osu_generic,
+        write::to_qua_generic,
+        false
+    );
+
+    // .qua has no unicode artist, video or timing point volumes, everything else should come back
+    let back = write::to_osu_generic(&parse::from_qua_generic(&converted).unwrap()).unwrap();
+    let diff = analysis::diff(&chart, &parse::from_osu_generic(&back).unwrap());
+    assert!(!diff.notes_changed(), "{diff:?}");
+    assert!(diff.timing_points.iter().all(|point| {
+        point.kind == analysis::DiffKind::Changed && point.old_time == point.new_time && point.old_value == point.new_value
+    }), "{diff:?}");
+
+    let fields: Vec<&str> = diff.metadata.iter().map(|change| change.field.as_str()).collect();
+    assert_eq!(fields, ["alt_artist", "video_path"]);
+}
+
+fn sample_files(samples: &[osu::ResolvedSample]) -> Vec<&str> {
+    samples.iter().map(|sample| sample.filename.as_str()).collect()
+}
+
+#[test]
+fn osu_resolve_samples_test() {
+    let soft_point = osu::TimingPoint::new(0.0, 500.0, 4, 2, 0, 70, true, 0);
+    let unset_point = osu::TimingPoint::new(0.0, 500.0, 4, 0, 0, 70, true, 0);
+    let index_point = osu::TimingPoint::new(0.0, 500.0, 4, 2, 2, 70, true, 0);
+    let sample = |normal_set: u8, addition_set: u8, index: usize, volume: u8, filename: &str| osu::HitSample {
+        normal_set,
+        addition_set,
+        index,
+        volume,
+        filename: filename.to_string(),
+    };
+    let resolve = |hit_sound: u8, hit_sample: &osu::HitSample, timing_point: Option<&osu::TimingPoint>| {
+        osu::resolve_samples(hit_sound, hit_sample, timing_point, &osu::SampleSet::Drum)
+    };
+
+    // unset sets come from the timing point, then from the map
+    let samples = resolve(2, &sample(0, 0, 0, 0, ""), Some(&soft_point));
+    assert_eq!(sample_files(&samples), vec!["soft-hitnormal.wav", "soft-hitwhistle.wav"]);
+    let samples = resolve(2, &sample(0, 0, 0, 0, ""), Some(&unset_point));
+    assert_eq!(sample_files(&samples), vec!["drum-hitnormal.wav", "drum-hitwhistle.wav"]);
+    let samples = resolve(2, &sample(0, 0, 0, 0, ""), None);
+    assert_eq!(sample_files(&samples), vec!["drum-hitnormal.wav", "drum-hitwhistle.wav"]);
+
+    // the object's sets win, additions follow the normal set when theirs is unset
+    let samples = resolve(12, &sample(1, 3, 0, 0, ""), Some(&soft_point));
+    assert_eq!(sample_files(&samples), vec!["normal-hitnormal.wav", "drum-hitfinish.wav", "drum-hitclap.wav"]);
+    let samples = resolve(8, &sample(1, 0, 0, 0, ""), Some(&soft_point));
+    assert_eq!(sample_files(&samples), vec!["normal-hitnormal.wav", "normal-hitclap.wav"]);
+    let hitsound_types: Vec<_> = resolve(14, &sample(0, 0, 0, 0, ""), None).iter().map(|s| s.hitsound_type).collect();
+    assert_eq!(
+        hitsound_types,
+        vec![generic::HitSoundType::Normal, generic::HitSoundType::Whistle, generic::HitSoundType::Finish, generic::HitSoundType::Clap]
+    );
+
+    // index 0 is the skin's samples, 1 is the map's without a number and the rest get the number
+    let samples = resolve(0, &sample(0, 0, 0, 0, ""), Some(&soft_point));
+    assert!(samples[0].from_skin);
+    let samples = resolve(0, &sample(0, 0, 1, 0, ""), Some(&soft_point));
+    assert_eq!(sample_files(&samples), vec!["soft-hitnormal.wav"]);
+    assert!(!samples[0].from_skin);
+    let samples = resolve(2, &sample(0, 0, 3, 0, ""), Some(&soft_point));
+    assert_eq!(sample_files(&samples), vec!["soft-hitnormal3.wav", "soft-hitwhistle3.wav"]);
+    assert!(samples.iter().all(|sample| !sample.from_skin));
+    let samples = resolve(0, &sample(0, 0, 0, 0, ""), Some(&index_point));
+    assert_eq!(sample_files(&samples), vec!["soft-hitnormal2.wav"]);
+    let samples = resolve(0, &sample(0, 0, 4, 0, ""), Some(&index_point));
+    assert_eq!(sample_files(&samples), vec!["soft-hitnormal4.wav"]);
+
+    // a custom file replaces the normal sound and the additions
+    let samples = resolve(10, &sample(1, 2, 3, 40, "kick.wav"), Some(&soft_point));
+    assert_eq!(sample_files(&samples), vec!["kick.wav"]);
+    assert_eq!(samples[0].hitsound_type, generic::HitSoundType::Normal);
+    assert!(!samples[0].from_skin);
+    assert_eq!(samples[0].volume, 40);
+
+    // volume 0 takes the timing point's
+    assert_eq!(resolve(0, &sample(0, 0, 0, 0, ""), Some(&soft_point))[0].volume, 70);
+    assert_eq!(resolve(0, &sample(0, 0, 0, 0, "kick.wav"), Some(&soft_point))[0].volume, 70);
+    assert_eq!(resolve(0, &sample(0, 0, 0, 0, ""), None)[0].volume, 100);
+    assert_eq!(resolve(0, &sample(0, 0, 0, 40, ""), Some(&soft_point))[0].volume, 40);
+}
+
+const OSU_KEYSOUNDS: &str = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+SampleSet: Soft
+Mode: 3
+
+[Metadata]
+Title:Keysounds
+Version:4K
+
+[Difficulty]
+HPDrainRate:8
+CircleSize:4
+OverallDifficulty:8
+
+[TimingPoints]
+0,500,4,0,2,80,1,0
+1000,-100,4,3,0,60,0,0
+
+[HitObjects]
+64,192,0,1,0,0:0:0:0:
+192,192,500,1,8,0:0:0:0:
+320,192,1000,1,0,0:0:0:0:
+448,192,1500,1,0,0:0:0:50:kick.wav
+";
+
+#[test]
+fn osu_keysounds_to_qua_test() {
+    let chart = parse::from_osu_generic(OSU_KEYSOUNDS).unwrap();
+
+    // the map's files go into the soundbank, the skin's samples don't
+    let soundbank = chart.soundbank.as_ref().unwrap();
+    assert_eq!(
+        soundbank.get_sample_paths(),
+        vec!["soft-hitnormal2.wav", "soft-hitclap2.wav", "kick.wav"]
+    );
+    // the clap plays as a sound effect next to the note
+    let effects = |soundbank: &generic::SoundBank| {
+        soundbank.sound_effects.iter().map(|effect| (effect.time, effect.volume, effect.sample)).collect::<Vec<_>>()
+    };
+    assert_eq!(effects(soundbank), vec![(500.0, 80, 1)]);
+
+    let keysounds: Vec<_> = chart.hitobjects.iter().map(|obj| (obj.keysound.sample, obj.keysound.volume)).collect();
+    assert_eq!(keysounds, vec![(Some(0), 80), (Some(0), 80), (None, 60), (Some(2), 50)]);
+
+    let qua = write::to_qua_generic(&chart).unwrap();
+    // quaver counts samples from 1
+    assert!(qua.contains("StartTime: 1500\n  Lane: 4\n  Type: Normal\n  KeySounds:\n  - Sample: 3\n    Volume: 50\n"));
+    assert!(qua.contains("CustomAudioSamples:\n- Path: soft-hitnormal2.wav\n- Path: soft-hitclap2.wav\n- Path: kick.wav\n"));
+
+    let reparsed = parse::from_qua_generic(&qua).unwrap();
+    let reparsed_soundbank = reparsed.soundbank.as_ref().unwrap();
+    assert_eq!(reparsed_soundbank.get_sample_paths(), soundbank.get_sample_paths());
+    assert_eq!(effects(reparsed_soundbank), effects(soundbank));
+}
@@ -32,4 +32,55 @@ fn fsc_to_sm_test() {
         write::to_sm_generic,
         true
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn sm_to_sm_test() {
+    let (chart, converted) = parse_and_convert!(
+        sm_to_sm,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm_generic,
+        write::to_sm_generic,
+        false
+    );
+
+    // beats are exact, writing the chart back shouldn't move any note
+    let reparsed = parse::from_sm_generic(&converted).unwrap();
+    let beats = |chart: &GenericManiaChart| {
+        chart.hitobjects.iter().map(|obj| (obj.beat, obj.lane)).collect::<Vec<_>>()
+    };
+    assert_eq!(beats(&chart), beats(&reparsed));
+
+    // stops have to be written too or every note after them ends up early
+    let diff = analysis::diff(&chart, &reparsed);
+    assert!(!diff.notes_changed() && diff.timing_points.is_empty(), "{diff:?}");
+}
+
+#[test]
+fn sm_stop_timing_test() {
+    let raw_chart = "#TITLE:Stops;\n#OFFSET:0.000;\n#BPMS:0.000=120.000;\n#STOPS:4.000=1.000;\n#NOTES:\n     dance-single:\n     :\n     Hard:\n     1:\n     0,0,0,0,0:\n1000\n0000\n0000\n0100\n,\n0010\n1000\n0000\n0000\n;\n";
+    let chart = parse::from_sm_generic(raw_chart).unwrap();
+
+    // 500ms beats, the note on beat 4 is hit as the 1s stop starts and the one on beat 5 after it ends
+    let times: Vec<(f64, u8)> = chart.hitobjects.iter().map(|obj| (obj.time, obj.lane)).collect();
+    assert_eq!(times, vec![(0.0, 1), (1500.0, 2), (2000.0, 3), (3500.0, 1)]);
+}
+
+#[test]
+fn sm_scroll_positions_test() {
+    let (chart, _) = parse_and_convert!(
+        sm_to_sm,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm_generic,
+        write::to_sm_generic,
+        false
+    );
+
+    // stepmania spaces notes by beat, so the scroll position is just the beat at the base bpm
+    let scroll = chart.scroll_positions(generic::ScrollRules::StepMania);
+    let ms_per_beat = 60_000.0 / chart.timing_map().base_bpm() as f64;
+    let start = scroll.position_at(chart.chartinfo.audio_offset, "");
+    for (obj, position) in chart.hitobjects.iter().zip(&scroll.positions) {
+        assert!((position - start - obj.beat.as_f64() * ms_per_beat).abs() < 1e-3);
+    }
+}
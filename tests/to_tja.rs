@@ -0,0 +1,143 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_tja_test() {
+    parse_and_convert!(
+        osu_to_tja,
+        "./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [IMT's Oni].osu",
+        parse::from_osu_taiko,
+        write::to_tja_taiko,
+        true
+    );
+}
+
+const TJA_CHART: &str = "\
+TITLE:Test
+BPM:120
+OFFSET:0
+BALLOON:5
+COURSE:Oni
+LEVEL:8
+
+#START
+1020,
+#BPMCHANGE 240
+#SCROLL 0.6
+#GOGOSTART
+3400,
+700000008,
+#GOGOEND
+1111,
+#END
+";
+
+#[test]
+fn tja_parse_test() {
+    let charts = parse::from_tja_taiko(TJA_CHART).unwrap();
+    assert_eq!(charts.len(), 1);
+    let chart = &charts[0];
+    assert_eq!(chart.metadata.title, "Test");
+    assert_eq!(chart.course, generic::TaikoCourse::Oni);
+    assert_eq!(chart.level, 8);
+
+    let notes: Vec<_> = chart.hitobjects.iter().map(|obj| (obj.time.round(), obj.note.note_type)).collect();
+    assert_eq!(
+        notes,
+        vec![
+            (0.0, common::TaikoHitobjectType::Don),
+            (1000.0, common::TaikoHitobjectType::Kat),
+            // the bpm doubles at the second measure
+            (2000.0, common::TaikoHitobjectType::BonusDon),
+            (2250.0, common::TaikoHitobjectType::BonusKat),
+            (3000.0, common::TaikoHitobjectType::Balloon),
+            (4000.0, common::TaikoHitobjectType::Don),
+            (4250.0, common::TaikoHitobjectType::Don),
+            (4500.0, common::TaikoHitobjectType::Don),
+            (4750.0, common::TaikoHitobjectType::Don),
+        ]
+    );
+    let balloon = chart.hitobjects.iter().find(|obj| obj.note.note_type == common::TaikoHitobjectType::Balloon).unwrap();
+    assert_eq!(balloon.balloon_hits, Some(5));
+    assert!((balloon.note.end_time.unwrap() - 3000.0 - 8000.0 / 9.0).abs() < 1e-6);
+
+    let changes: Vec<_> = chart
+        .timing_points
+        .iter()
+        .map(|tp| (tp.time.round(), tp.change.change_type, tp.change.value))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (0.0, common::TimingChangeType::Bpm, 120.0),
+            (2000.0, common::TimingChangeType::Bpm, 240.0),
+            (2000.0, common::TimingChangeType::Sv, 0.6),
+        ]
+    );
+
+    assert_eq!(chart.gogo_sections.len(), 1);
+    assert!((chart.gogo_sections[0].start_time - 2000.0).abs() < 1e-6);
+    assert!((chart.gogo_sections[0].end_time - 4000.0).abs() < 1e-6);
+
+    // writing it back keeps the values readable and the notes in place
+    let written = write::to_tja_taiko(chart).unwrap();
+    assert!(written.contains("#BPMCHANGE 240\n"));
+    assert!(written.contains("#SCROLL 0.6\n"));
+    let reparsed = parse::from_tja_taiko(&written).unwrap();
+    let reparsed_notes: Vec<_> = reparsed[0].hitobjects.iter().map(|obj| (obj.time.round(), obj.note.note_type)).collect();
+    assert_eq!(reparsed_notes, notes);
+}
+
+// same beats played twice as fast, starting a second later
+fn faster_and_later(chart: &generic::GenericTaikoChart) -> generic::GenericTaikoChart {
+    let retime = |time: f64| 1000.0 + time / 2.0;
+    let mut chart = chart.clone();
+    for object in chart.hitobjects.objects.iter_mut() {
+        object.time = retime(object.time);
+        object.note.end_time = object.note.end_time.map(retime);
+    }
+    for tp in chart.timing_points.iter_mut() {
+        tp.time = retime(tp.time);
+        if tp.change.change_type == common::TimingChangeType::Bpm {
+            tp.change.value *= 2.0;
+        }
+    }
+    for section in chart.gogo_sections.iter_mut() {
+        section.start_time = retime(section.start_time);
+        section.end_time = retime(section.end_time);
+    }
+    chart
+}
+
+#[test]
+fn tja_courses_header_test() {
+    assert!(write::to_tja_taiko_courses(&[]).is_err());
+
+    let oni = parse::from_tja_taiko(TJA_CHART).unwrap().remove(0);
+    let mut hard = faster_and_later(&oni);
+    hard.course = generic::TaikoCourse::Hard;
+
+    // the header follows the course that starts first, the other one catches up with a bpm change and a delay
+    let written = write::to_tja_taiko_courses(&[hard.clone(), oni.clone()]).unwrap();
+    assert!(written.contains("BPM:120\n"));
+    let offset = written.lines().find_map(|line| line.strip_prefix("OFFSET:")).unwrap();
+    assert_eq!(offset.parse::<f64>().unwrap(), 0.0);
+    assert!(written.contains("#START\n#BPMCHANGE 240\n#DELAY 1\n"));
+    assert!(!written.contains("SUBTITLE"));
+
+    let reparsed = parse::from_tja_taiko(&written).unwrap();
+    assert_eq!(reparsed.len(), 2);
+    for (original, course) in [&hard, &oni].into_iter().zip(&reparsed) {
+        assert_eq!(course.course, original.course);
+        let times = |chart: &generic::GenericTaikoChart| chart.hitobjects.iter().map(|obj| obj.time).collect::<Vec<_>>();
+        let (expected, actual) = (times(original), times(course));
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(&actual) {
+            assert!((expected - actual).abs() < 1.0, "{expected} {actual}");
+        }
+    }
+
+    let mut sourced = oni.clone();
+    sourced.metadata.source = "Test Source".to_string();
+    assert!(write::to_tja_taiko(&sourced).unwrap().contains("SUBTITLE:--Test Source\n"));
+}